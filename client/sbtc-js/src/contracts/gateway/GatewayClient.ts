@@ -77,6 +77,8 @@ class GatewayClient extends ContractClient<HandleMsg, QueryMsg, QueryAnswer> {
         height: number,
         tx: Transaction,
         merkleProof: MerkleProof,
+        coinbaseTx: Transaction,
+        coinbaseMerkleProof: MerkleProof,
         gasLimit?: number
     ): Promise<ExecuteResult<void>> {
         const msg = {
@@ -84,6 +86,9 @@ class GatewayClient extends ContractClient<HandleMsg, QueryMsg, QueryAnswer> {
                 height,
                 tx: tx.toBuffer().toString('base64'),
                 merkle_proof: merkleProof.encodeToContractMsg(),
+                coinbase_tx: coinbaseTx.toBuffer().toString('base64'),
+                coinbase_merkle_proof:
+                    coinbaseMerkleProof.encodeToContractMsg(),
             },
         };
         return await this.execute(msg, gasLimit || 10000000, () => void 0);
@@ -93,6 +98,8 @@ class GatewayClient extends ContractClient<HandleMsg, QueryMsg, QueryAnswer> {
         height: number,
         tx: Transaction,
         merkleProof: MerkleProof,
+        coinbaseTx: Transaction,
+        coinbaseMerkleProof: MerkleProof,
         recipientAddress: string,
         feePerVb: number,
         gasLimit?: number
@@ -102,6 +109,9 @@ class GatewayClient extends ContractClient<HandleMsg, QueryMsg, QueryAnswer> {
                 height,
                 tx: tx.toBuffer().toString('base64'),
                 merkle_proof: merkleProof.encodeToContractMsg(),
+                coinbase_tx: coinbaseTx.toBuffer().toString('base64'),
+                coinbase_merkle_proof:
+                    coinbaseMerkleProof.encodeToContractMsg(),
                 recipient_address: recipientAddress,
                 fee_per_vb: feePerVb,
             },
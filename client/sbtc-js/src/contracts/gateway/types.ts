@@ -89,6 +89,8 @@ export interface HandleMsgReleaseBtcByOwner {
 }
 
 export interface HandleMsgReleaseIncorrectAmountBTC {
+    coinbase_merkle_proof: MerkleProofMsg;
+    coinbase_tx: string;
     fee_per_vb: number;
     height: number;
     merkle_proof: MerkleProofMsg;
@@ -127,6 +129,8 @@ export interface SetViewingKey {
 }
 
 export interface VerifyMintTx {
+    coinbase_merkle_proof: MerkleProofMsg;
+    coinbase_tx: string;
     height: number;
     merkle_proof: MerkleProofMsg;
     tx: string;
@@ -568,6 +572,12 @@ const typeMap: any = {
     ),
     HandleMsgReleaseIncorrectAmountBTC: o(
         [
+            {
+                json: 'coinbase_merkle_proof',
+                js: 'coinbase_merkle_proof',
+                typ: r('MerkleProofMsg'),
+            },
+            { json: 'coinbase_tx', js: 'coinbase_tx', typ: '' },
             { json: 'fee_per_vb', js: 'fee_per_vb', typ: 0 },
             { json: 'height', js: 'height', typ: 0 },
             {
@@ -633,6 +643,12 @@ const typeMap: any = {
     SetViewingKey: o([{ json: 'key', js: 'key', typ: '' }], 'any'),
     VerifyMintTx: o(
         [
+            {
+                json: 'coinbase_merkle_proof',
+                js: 'coinbase_merkle_proof',
+                typ: r('MerkleProofMsg'),
+            },
+            { json: 'coinbase_tx', js: 'coinbase_tx', typ: '' },
             { json: 'height', js: 'height', typ: 0 },
             {
                 json: 'merkle_proof',
@@ -188,6 +188,18 @@ class SFPSClient extends ContractClient<SFPSHandleMsg, QueryMsg, QueryAnswer> {
             (answer) => answer.hash_by_index!.height
         );
     }
+
+    // returns the smallest index of the hash at or above the height, and the height of the hash.
+    public async hashIndexForHeight(
+        height: number
+    ): Promise<{ index: number; height: number }> {
+        return await this.query(
+            {
+                hash_index_for_height: { height },
+            },
+            (answer) => answer.hash_index_for_height!
+        );
+    }
 }
 
 // Query response deliver txs in a block, which are elements of last_result_hash of block header.
@@ -246,23 +258,10 @@ class ResponseDeliverTxProofBuilder {
         txHeight: number
     ): Promise<{ headers: Header[]; headerHashIndex: number }> {
         const from = txHeight + 1;
-        let to = undefined;
-        let headerHashIndex = undefined;
-        for (
-            let index = (await this.sfpsClient.hashListLength()) - 1;
-            index >= 0;
-            index--
-        ) {
-            const height = await this.sfpsClient.heightByIndex(index);
-            if (height < from) {
-                break;
-            }
-            to = height;
-            headerHashIndex = index;
-        }
-        if (!to || !headerHashIndex) {
-            throw Error('Header is not synced yed');
-        }
+        const { index: headerHashIndex, height: to } =
+            await this.sfpsClient.hashIndexForHeight(from).catch(() => {
+                throw Error('Header is not synced yed');
+            });
         const headers = [];
         for (let height = from; height <= to; height++) {
             const res =
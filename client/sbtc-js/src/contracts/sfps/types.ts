@@ -32,6 +32,8 @@ export interface QueryAnswer {
     current_highest_header_hash?: CurrentHighestHeaderHash;
     hash_list_length?: HashListLength;
     hash_by_index?: QueryAnswerHashByIndex;
+    hash_index_for_height?: QueryAnswerHashIndexForHeight;
+    hashes?: QueryAnswerHashes;
     verify_response_deliver_tx_proof?: QueryAnswerVerifyResponseDeliverTxProof;
     verify_subsequent_light_blocks?: QueryAnswerVerifySubsequentLightBlocks;
 }
@@ -46,6 +48,15 @@ export interface QueryAnswerHashByIndex {
     height: number;
 }
 
+export interface QueryAnswerHashIndexForHeight {
+    index: number;
+    height: number;
+}
+
+export interface QueryAnswerHashes {
+    hashes: PurpleHeaderHashWithHeight[];
+}
+
 export interface HashListLength {
     length: number;
 }
@@ -71,6 +82,8 @@ export interface PurpleHashes {
     anchor_hash: number[];
     anchor_index: number;
     following_hashes: PurpleHeaderHashWithHeight[];
+    anchor_time: number;
+    highest_time: number;
 }
 
 export interface PurpleHeaderHashWithHeight {
@@ -83,6 +96,8 @@ export interface QueryMsg {
     current_highest_header_hash?: { [key: string]: any };
     hash_list_length?: { [key: string]: any };
     hash_by_index?: QueryMsgHashByIndex;
+    hash_index_for_height?: QueryMsgHashIndexForHeight;
+    hashes?: QueryMsgHashes;
     verify_response_deliver_tx_proof?: QueryMsgVerifyResponseDeliverTxProof;
     verify_subsequent_light_blocks?: QueryMsgVerifySubsequentLightBlocks;
 }
@@ -91,6 +106,15 @@ export interface QueryMsgHashByIndex {
     index: number;
 }
 
+export interface QueryMsgHashIndexForHeight {
+    height: number;
+}
+
+export interface QueryMsgHashes {
+    start: number;
+    limit: number;
+}
+
 export interface QueryMsgVerifyResponseDeliverTxProof {
     block_hash_index: number;
     encryption_key: string;
@@ -129,6 +153,8 @@ export interface FluffyHashes {
     anchor_hash: number[];
     anchor_index: number;
     following_hashes: FluffyHeaderHashWithHeight[];
+    anchor_time: number;
+    highest_time: number;
 }
 
 export interface FluffyHeaderHashWithHeight {
@@ -373,6 +399,16 @@ const typeMap: any = {
                 js: 'hash_by_index',
                 typ: u(undefined, r('QueryAnswerHashByIndex')),
             },
+            {
+                json: 'hash_index_for_height',
+                js: 'hash_index_for_height',
+                typ: u(undefined, r('QueryAnswerHashIndexForHeight')),
+            },
+            {
+                json: 'hashes',
+                js: 'hashes',
+                typ: u(undefined, r('QueryAnswerHashes')),
+            },
             {
                 json: 'verify_response_deliver_tx_proof',
                 js: 'verify_response_deliver_tx_proof',
@@ -400,6 +436,23 @@ const typeMap: any = {
         ],
         'any'
     ),
+    QueryAnswerHashIndexForHeight: o(
+        [
+            { json: 'index', js: 'index', typ: 0 },
+            { json: 'height', js: 'height', typ: 0 },
+        ],
+        'any'
+    ),
+    QueryAnswerHashes: o(
+        [
+            {
+                json: 'hashes',
+                js: 'hashes',
+                typ: a(r('PurpleHeaderHashWithHeight')),
+            },
+        ],
+        'any'
+    ),
     HashListLength: o([{ json: 'length', js: 'length', typ: 0 }], 'any'),
     MaxInterval: o(
         [{ json: 'max_interval', js: 'max_interval', typ: 0 }],
@@ -435,6 +488,8 @@ const typeMap: any = {
                 js: 'following_hashes',
                 typ: a(r('PurpleHeaderHashWithHeight')),
             },
+            { json: 'anchor_time', js: 'anchor_time', typ: 0 },
+            { json: 'highest_time', js: 'highest_time', typ: 0 },
         ],
         'any'
     ),
@@ -467,6 +522,16 @@ const typeMap: any = {
                 js: 'hash_by_index',
                 typ: u(undefined, r('QueryMsgHashByIndex')),
             },
+            {
+                json: 'hash_index_for_height',
+                js: 'hash_index_for_height',
+                typ: u(undefined, r('QueryMsgHashIndexForHeight')),
+            },
+            {
+                json: 'hashes',
+                js: 'hashes',
+                typ: u(undefined, r('QueryMsgHashes')),
+            },
             {
                 json: 'verify_response_deliver_tx_proof',
                 js: 'verify_response_deliver_tx_proof',
@@ -481,6 +546,17 @@ const typeMap: any = {
         'any'
     ),
     QueryMsgHashByIndex: o([{ json: 'index', js: 'index', typ: 0 }], 'any'),
+    QueryMsgHashIndexForHeight: o(
+        [{ json: 'height', js: 'height', typ: 0 }],
+        'any'
+    ),
+    QueryMsgHashes: o(
+        [
+            { json: 'start', js: 'start', typ: 0 },
+            { json: 'limit', js: 'limit', typ: 0 },
+        ],
+        'any'
+    ),
     QueryMsgVerifyResponseDeliverTxProof: o(
         [
             { json: 'block_hash_index', js: 'block_hash_index', typ: 0 },
@@ -548,6 +624,8 @@ const typeMap: any = {
                 js: 'following_hashes',
                 typ: a(r('FluffyHeaderHashWithHeight')),
             },
+            { json: 'anchor_time', js: 'anchor_time', typ: 0 },
+            { json: 'highest_time', js: 'highest_time', typ: 0 },
         ],
         'any'
     ),
@@ -33,6 +33,8 @@ export interface Hashes {
     anchor_hash: number[];
     anchor_index: number;
     following_hashes: HeaderHashWithHeight[];
+    anchor_time: number;
+    highest_time: number;
 }
 
 export interface HeaderHashWithHeight {
@@ -311,6 +313,8 @@ const typeMap: any = {
                 js: 'following_hashes',
                 typ: a(r('HeaderHashWithHeight')),
             },
+            { json: 'anchor_time', js: 'anchor_time', typ: 0 },
+            { json: 'highest_time', js: 'highest_time', typ: 0 },
         ],
         'any'
     ),
@@ -1,16 +1,32 @@
-use crate::contract::CONTRACT_LABEL;
+use crate::contract::query::msg_to_merkle_proof;
+use crate::contract::{CONTRACT_LABEL, PREFIX_PRNG};
 use crate::error::Error;
 use crate::state::chaindb::StorageChainDB;
+use crate::state::compact_pow::{
+    read_compact_pow_commitment, read_last_commit_height, remove_compact_pow_commitment,
+    write_compact_pow_commitment, write_last_commit_height, CompactPowCommitment,
+};
 use crate::state::config::read_config;
+use crate::state::filter_header::{read_filter_header, write_filter_header};
+use bitcoin::blockdata::script::Script;
 use bitcoin::consensus::encode::deserialize;
+use bitcoin::hash_types::{FilterHash, FilterHeader, TxMerkleNode};
 use bitcoin::BlockHeader;
 use bitcoin::Network;
+use bitcoin::Transaction;
+use bitcoin_header_chain::compact_pow::{CompactPowProof, SampledHeader};
 use bitcoin_header_chain::header_chain::HeaderChain;
+use bitcoin_header_chain::signet::{default_signet_challenge, SignetBlockHeader};
 use cosmwasm_std::{Api, Binary, Env, Extern, HandleResponse, Querier, StdResult, Storage};
+use rand::RngCore;
+use secret_toolkit::utils::calls::HandleCallback;
 use secret_toolkit::utils::padding::pad_handle_result;
+use shared_types::bitcoin_spv::{HandleMsg, SampledHeaderMsg, SignetHeaderMsg};
+use shared_types::prng::update_prng;
 use shared_types::state_proxy::client::Secp256k1ApiSigner;
 use shared_types::state_proxy::client::StateProxyDeps;
-use shared_types::{bitcoin_spv::HandleMsg, BLOCK_SIZE};
+use shared_types::BLOCK_SIZE;
+use shared_types::{RelayCallback, RelayCallbackMsg};
 use std::str::FromStr;
 
 pub fn handle<S: Storage, A: Api, Q: Querier>(
@@ -29,8 +45,40 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::AddHeaders {
             tip_height,
             headers,
+            relay_callback,
             ..
-        } => try_add_headers(&mut deps, env, tip_height, headers),
+        } => try_add_headers(&mut deps, env, tip_height, headers, relay_callback),
+        HandleMsg::AddSignetHeaders {
+            tip_height,
+            headers,
+            ..
+        } => try_add_signet_headers(&mut deps, env, tip_height, headers),
+        HandleMsg::AddCheckpoint { height, header } => {
+            try_add_checkpoint(&mut deps, env, height, header)
+        }
+        HandleMsg::CommitCompactPowProof {
+            height,
+            commitment,
+            leaf_count,
+        } => try_commit_compact_pow_proof(&mut deps, env, height, commitment, leaf_count),
+        HandleMsg::AddCompactPowProof {
+            height,
+            commitment,
+            leaf_count,
+            samples,
+        } => try_add_compact_pow_proof(&mut deps, env, height, commitment, leaf_count, samples),
+        HandleMsg::AddFilterHeaders {
+            start_height,
+            filter_hashes,
+            prev_filter_header,
+            ..
+        } => try_add_filter_headers(
+            &mut deps,
+            env,
+            start_height,
+            filter_hashes,
+            prev_filter_header,
+        ),
     };
     let result = match result {
         Ok(mut response) => {
@@ -49,12 +97,21 @@ fn try_add_headers<A: Api, Q: Querier>(
     env: Env,
     tip_height: u32,
     headers: Vec<Binary>,
+    relay_callback: Option<RelayCallback>,
 ) -> Result<HandleResponse, Error> {
     let config = read_config(&deps.storage, &deps.api)?;
     let block_time = env.block.time as u32;
     if headers.is_empty() {
         return Err(Error::contract_err("no header in msg"));
     }
+    // only the callback contract can request the callback, otherwise anyone could credit any relayer.
+    if let Some(relay_callback) = relay_callback.as_ref() {
+        if relay_callback.contract.address != env.message.sender {
+            return Err(Error::contract_err(
+                "relay callback is not requested by the callback contract",
+            ));
+        }
+    }
     let de_headers = {
         let mut de_headers = Vec::with_capacity(headers.len());
         for header in headers {
@@ -67,7 +124,294 @@ fn try_add_headers<A: Api, Q: Querier>(
         StorageChainDB::from_storage(&mut deps.storage),
         Network::from_str(&config.bitcoin_network)?,
     );
-    header_chain.store_headers(tip_height, de_headers, block_time)?;
+    let stored = header_chain.store_headers(tip_height, de_headers, block_time)?;
+    if let Some(retained_header_length) = config.retained_header_length {
+        header_chain.prune_headers(retained_header_length)?;
+    }
+    let mut messages = vec![];
+    if let Some(relay_callback) = relay_callback {
+        if stored.accepted > 0 {
+            messages.push(
+                RelayCallbackMsg::HeadersAccepted {
+                    relayer: relay_callback.relayer,
+                    count: stored.accepted,
+                }
+                .to_cosmos_msg(
+                    relay_callback.contract.hash,
+                    relay_callback.contract.address,
+                    None,
+                )?,
+            );
+        }
+    }
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: None,
+    })
+}
+
+fn try_add_signet_headers<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    tip_height: u32,
+    headers: Vec<SignetHeaderMsg>,
+) -> Result<HandleResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let block_time = env.block.time as u32;
+    if headers.is_empty() {
+        return Err(Error::contract_err("no header in msg"));
+    }
+    let de_headers = {
+        let mut de_headers = Vec::with_capacity(headers.len());
+        for header in headers {
+            de_headers.push(SignetBlockHeader {
+                header: deserialize::<BlockHeader>(header.header.as_slice())?,
+                coinbase_tx: deserialize::<Transaction>(header.coinbase_tx.as_slice())?,
+                coinbase_proof: msg_to_merkle_proof(header.coinbase_merkle_proof)?,
+            });
+        }
+        de_headers
+    };
+    let challenge = match config.signet_challenge {
+        Some(challenge) => Script::from(challenge.0),
+        None => default_signet_challenge(),
+    };
+    let mut header_chain = HeaderChain::new(
+        StorageChainDB::from_storage(&mut deps.storage),
+        Network::from_str(&config.bitcoin_network)?,
+    );
+    header_chain.store_signet_headers(tip_height, de_headers, &challenge, block_time)?;
+    if let Some(retained_header_length) = config.retained_header_length {
+        header_chain.prune_headers(retained_header_length)?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: None,
+    })
+}
+
+fn try_add_checkpoint<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    height: u32,
+    header: Binary,
+) -> Result<HandleResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    if env.message.sender != config.owner {
+        return Err(Error::contract_err("not owner"));
+    }
+    let header = deserialize::<BlockHeader>(header.as_slice())?;
+    let mut header_chain = HeaderChain::new(
+        StorageChainDB::from_storage(&mut deps.storage),
+        Network::from_str(&config.bitcoin_network)?,
+    );
+    header_chain.add_checkpoint(height, header, env.block.time as u32)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: None,
+    })
+}
+
+fn try_commit_compact_pow_proof<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    height: u32,
+    commitment: String,
+    leaf_count: u32,
+) -> Result<HandleResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let compact_pow = config
+        .compact_pow
+        .ok_or_else(|| Error::contract_err("compact pow is disabled"))?;
+    // a prover would redraw the seed until the samples miss the forged headers,
+    // so the commitment is fixed until the proof is accepted,
+    // and the commitments of all the provers are rate limited.
+    let prover = deps.api.canonical_address(&env.message.sender)?;
+    if read_compact_pow_commitment(&deps.storage, &prover)?.is_some() {
+        return Err(Error::contract_err("compact pow commitment is pending"));
+    }
+    if let Some(last_commit_height) = read_last_commit_height(&deps.storage)? {
+        if env.block.height < last_commit_height.saturating_add(compact_pow.commit_interval) {
+            return Err(Error::contract_err("compact pow commitment is too early"));
+        }
+    }
+    let commitment = TxMerkleNode::from_str(&commitment)?;
+    let mut header_chain = HeaderChain::new(
+        StorageChainDB::from_readonly_storage(&deps.storage),
+        Network::from_str(&config.bitcoin_network)?,
+    );
+    let tip = header_chain
+        .tip()?
+        .ok_or_else(|| Error::contract_err("no chain tip found"))?;
+    let tip_height = header_chain.tip_height()?.unwrap();
+    if height.checked_sub(tip_height) != Some(leaf_count) {
+        return Err(Error::contract_err(
+            "leaf count must be the number of headers from the tip",
+        ));
+    }
+    // the seed is drawn after the commitment is fixed, and unknown to the prover until then.
+    // the block is mixed so that the seed differs from the one of a simulated tx.
+    let mut entropy = Vec::with_capacity(48);
+    entropy.extend_from_slice(&commitment[..]);
+    entropy.extend_from_slice(&env.block.height.to_be_bytes());
+    entropy.extend_from_slice(&env.block.time.to_be_bytes());
+    let mut rng = update_prng(&mut deps.storage, PREFIX_PRNG, &prover, &entropy)?;
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    write_compact_pow_commitment(
+        &mut deps.storage,
+        &prover,
+        &CompactPowCommitment {
+            height,
+            commitment,
+            leaf_count,
+            prev_hash: tip.header.block_hash(),
+            seed,
+        },
+    )?;
+    write_last_commit_height(&mut deps.storage, env.block.height)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: None,
+    })
+}
+
+fn try_add_compact_pow_proof<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    height: u32,
+    commitment: String,
+    leaf_count: u32,
+    samples: Vec<SampledHeaderMsg>,
+) -> Result<HandleResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let compact_pow = config
+        .compact_pow
+        .ok_or_else(|| Error::contract_err("compact pow is disabled"))?;
+    let prover = deps.api.canonical_address(&env.message.sender)?;
+    let committed = read_compact_pow_commitment(&deps.storage, &prover)?
+        .ok_or_else(|| Error::contract_err("no compact pow commitment"))?;
+    let proof = {
+        let mut de_samples = Vec::with_capacity(samples.len());
+        for sample in samples {
+            de_samples.push(SampledHeader {
+                header: deserialize::<BlockHeader>(sample.header.as_slice())?,
+                proof: msg_to_merkle_proof(sample.merkle_proof)?,
+                prev_proof: match sample.prev_merkle_proof {
+                    Some(prev_merkle_proof) => Some(msg_to_merkle_proof(prev_merkle_proof)?),
+                    None => None,
+                },
+            });
+        }
+        CompactPowProof {
+            commitment: TxMerkleNode::from_str(&commitment)?,
+            samples: de_samples,
+        }
+    };
+    if committed.height != height
+        || committed.commitment != proof.commitment
+        || committed.leaf_count != leaf_count
+    {
+        return Err(Error::contract_err("unmatched compact pow commitment"));
+    }
+    let mut header_chain = HeaderChain::new(
+        StorageChainDB::from_storage(&mut deps.storage),
+        Network::from_str(&config.bitcoin_network)?,
+    );
+    let tip = header_chain
+        .tip()?
+        .ok_or_else(|| Error::contract_err("no chain tip found"))?;
+    if tip.header.block_hash() != committed.prev_hash {
+        return Err(Error::contract_err("chain tip moved after the commitment"));
+    }
+    header_chain.store_compact_pow_proof(
+        height,
+        &proof,
+        &committed.seed,
+        compact_pow.sample_count,
+        &BlockHeader::u256_from_compact_target(compact_pow.max_target_bits),
+        env.block.time as u32,
+    )?;
+    remove_compact_pow_commitment(&mut deps.storage, &prover);
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: None,
+    })
+}
+
+fn try_add_filter_headers<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    start_height: u32,
+    filter_hashes: Vec<String>,
+    prev_filter_header: Option<String>,
+) -> Result<HandleResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let is_owner = env.message.sender == config.owner;
+    // filter headers are not committed by the blocks, so the submitters are trusted.
+    if !is_owner && !config.filter_header_relayers.contains(&env.message.sender) {
+        return Err(Error::contract_err("not allowed filter header relayer"));
+    }
+    if filter_hashes.is_empty() {
+        return Err(Error::contract_err("no filter hash in msg"));
+    }
+    let block_hashes = {
+        let mut header_chain = HeaderChain::new(
+            StorageChainDB::from_readonly_storage(&deps.storage),
+            Network::from_str(&config.bitcoin_network)?,
+        );
+        let mut block_hashes = Vec::with_capacity(filter_hashes.len() + 1);
+        for height in start_height.saturating_sub(1)..start_height + filter_hashes.len() as u32 {
+            let header = header_chain
+                .header_at(height)?
+                .ok_or_else(|| Error::contract_err("block not found"))?;
+            block_hashes.push(header.header.block_hash());
+        }
+        block_hashes
+    };
+    let (prev_block_hash, block_hashes) = if start_height == 0 {
+        (None, &block_hashes[..])
+    } else {
+        (Some(block_hashes[0]), &block_hashes[1..])
+    };
+
+    // anchor the filter header chain
+    let stored_prev_filter_header = match prev_block_hash {
+        Some(prev_block_hash) => read_filter_header(&deps.storage, &prev_block_hash)?,
+        None => Some(FilterHeader::default()),
+    };
+    let mut prev_filter_header = match (prev_filter_header, stored_prev_filter_header) {
+        (None, Some(stored)) => stored,
+        (None, None) => return Err(Error::contract_err("no previous filter header")),
+        (Some(prev_filter_header), stored) => {
+            let prev_filter_header = FilterHeader::from_str(&prev_filter_header)?;
+            if stored != Some(prev_filter_header) {
+                if !is_owner {
+                    return Err(Error::contract_err("not owner"));
+                }
+                if let Some(prev_block_hash) = prev_block_hash {
+                    write_filter_header(&mut deps.storage, &prev_block_hash, &prev_filter_header)?;
+                }
+            }
+            prev_filter_header
+        }
+    };
+
+    // extend the filter header chain
+    for (block_hash, filter_hash) in block_hashes.iter().zip(filter_hashes.iter()) {
+        let filter_header = FilterHash::from_str(filter_hash)?.filter_header(&prev_filter_header);
+        match read_filter_header(&deps.storage, block_hash)? {
+            Some(stored) if stored == filter_header => {}
+            Some(_) if !is_owner => return Err(Error::contract_err("conflicting filter header")),
+            _ => write_filter_header(&mut deps.storage, block_hash, &filter_header)?,
+        }
+        prev_filter_header = filter_header;
+    }
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
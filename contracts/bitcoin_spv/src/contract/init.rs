@@ -5,9 +5,10 @@ use crate::state::config::write_config;
 use bitcoin::consensus::encode::deserialize;
 use bitcoin::BlockHeader;
 use bitcoin::Network;
-use bitcoin_header_chain::header_chain::HeaderChain;
+use bitcoin_header_chain::header_chain::{min_retained_length, HeaderChain};
 use cosmwasm_std::{Api, Env, Extern, InitResponse, Querier, StdResult, Storage};
 use shared_types::bitcoin_spv::{Config, InitMsg};
+use shared_types::prng::init_prng;
 use shared_types::state_proxy::client::Secp256k1ApiSigner;
 use shared_types::state_proxy::client::StateProxyDeps;
 use std::str::FromStr;
@@ -41,13 +42,39 @@ fn try_init<A: Api, Q: Querier>(
     let config = Config {
         bitcoin_network: msg.bitcoin_network,
         confirmation: msg.confirmation,
+        retained_header_length: msg.retained_header_length,
+        signet_challenge: msg.signet_challenge,
+        compact_pow: msg.compact_pow,
         state_proxy: msg.state_proxy,
+        owner: msg.owner,
+        filter_header_relayers: msg.filter_header_relayers,
     };
+    let network = Network::from_str(&config.bitcoin_network)?;
+    if config.signet_challenge.is_some() && network != Network::Signet {
+        return Err(Error::contract_err("signet challenge is only for signet"));
+    }
+    if let Some(compact_pow) = config.compact_pow.as_ref() {
+        if compact_pow.sample_count == 0 {
+            return Err(Error::contract_err(
+                "compact pow sample count must not be zero",
+            ));
+        }
+    }
+    if let Some(retained_header_length) = config.retained_header_length {
+        // headers must be kept until the tx in them is confirmed
+        if retained_header_length < min_retained_length(network)
+            || retained_header_length < config.confirmation.into()
+        {
+            return Err(Error::contract_err("retained header length too short"));
+        }
+    }
     write_config(&mut deps.storage, config.clone(), &deps.api)?;
+    // draws the seeds of compact pow samples
+    init_prng(&mut deps.storage, PREFIX_PRNG, &env, msg.seed.as_ref())?;
 
     // init bitcoin header chain
     let chaindb = StorageChainDB::from_storage(&mut deps.storage);
-    let mut header_chain = HeaderChain::new(chaindb, Network::from_str(&config.bitcoin_network)?);
+    let mut header_chain = HeaderChain::new(chaindb, network);
     match msg.initial_header {
         Some(initial_header) => {
             let header: BlockHeader = deserialize::<BlockHeader>(initial_header.header.as_slice())?;
@@ -1,18 +1,28 @@
 use crate::contract::CONTRACT_LABEL;
 use crate::error::Error;
 use crate::state::chaindb::StorageChainDB;
+use crate::state::compact_pow::read_compact_pow_commitment;
 use crate::state::config::read_config;
+use crate::state::filter_header::read_filter_header;
 use bitcoin::blockdata::transaction::Transaction;
 use bitcoin::consensus::encode::deserialize;
 use bitcoin::consensus::encode::serialize;
-use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::hash_types::{FilterHeader, TxMerkleNode};
+use bitcoin::util::bip158::BlockFilter;
+use bitcoin::util::merkleblock::MerkleBlock;
+use bitcoin::Address;
+use bitcoin::BlockHeader;
 use bitcoin::Network;
+use bitcoin_header_chain::coinbase_proof::{block_subsidy, CoinbaseProof};
+use bitcoin_header_chain::compact_pow::sample_indexes;
 use bitcoin_header_chain::header_chain::{HeaderChain, StoredBlockHeader};
-use bitcoin_header_chain::merkle_proof::MerkleProof;
+use bitcoin_header_chain::merkle_proof::{validate_tx_length, MerkleProof};
+use bitcoin_header_chain::witness_proof::WitnessProof;
 use cosmwasm_std::{
-    to_binary, Api, Binary, Extern, Querier, QueryResponse, QueryResult, StdError, Storage,
+    to_binary, Api, Binary, Extern, HumanAddr, Querier, QueryResponse, QueryResult, StdError,
+    Storage,
 };
-use shared_types::bitcoin_spv::{MerkleProofMsg, QueryAnswer, QueryMsg};
+use shared_types::bitcoin_spv::{BlockRewardMsg, MerkleProofMsg, QueryAnswer, QueryMsg, TxOutMsg};
 use shared_types::state_proxy::client::Secp256k1ApiSigner;
 use shared_types::state_proxy::client::StateProxyDeps;
 use std::str::FromStr;
@@ -29,17 +39,93 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
     let result = match msg {
         QueryMsg::Config {} => query_config(&deps),
         QueryMsg::BestHeaderHash {} => query_best_header_hash(&deps),
+        QueryMsg::OldestProvableHeight {} => query_oldest_provable_height(&deps),
         QueryMsg::BlockHeader { height, .. } => query_block_header(&deps, height),
         QueryMsg::VerifyMerkleProof {
             height,
             tx,
             merkle_proof,
+            coinbase_merkle_proof,
+            coinbase_tx,
             ..
-        } => query_verify_merkle_proof(&deps, height, tx, merkle_proof),
+        } => query_verify_merkle_proof(
+            &deps,
+            height,
+            tx,
+            merkle_proof,
+            coinbase_merkle_proof,
+            coinbase_tx,
+        ),
+        QueryMsg::VerifyAndDecodeTx {
+            height,
+            tx,
+            merkle_proof,
+            coinbase_merkle_proof,
+            coinbase_tx,
+            ..
+        } => query_verify_and_decode_tx(
+            &deps,
+            height,
+            tx,
+            merkle_proof,
+            coinbase_merkle_proof,
+            coinbase_tx,
+        ),
+        QueryMsg::VerifyTxOutProof { tx, proof, .. } => query_verify_tx_out_proof(&deps, tx, proof),
+        QueryMsg::VerifyWitnessMerkleProof {
+            height,
+            tx,
+            coinbase_tx,
+            coinbase_merkle_proof,
+            witness_merkle_proof,
+            ..
+        } => query_verify_witness_merkle_proof(
+            &deps,
+            height,
+            tx,
+            coinbase_tx,
+            coinbase_merkle_proof,
+            witness_merkle_proof,
+        ),
+        QueryMsg::VerifyBlockFilter {
+            height,
+            filter,
+            prev_filter_header,
+            scripts,
+            ..
+        } => query_verify_block_filter(&deps, height, filter, prev_filter_header, scripts),
+        QueryMsg::CompactPowSampleIndexes { prover, .. } => {
+            query_compact_pow_sample_indexes(&deps, prover)
+        }
     };
     Ok(result?)
 }
 
+fn query_compact_pow_sample_indexes<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    prover: HumanAddr,
+) -> Result<QueryResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let compact_pow = config
+        .compact_pow
+        .ok_or_else(|| Error::contract_err("compact pow is disabled"))?;
+    let committed =
+        read_compact_pow_commitment(&deps.storage, &deps.api.canonical_address(&prover)?)?
+            .ok_or_else(|| Error::contract_err("no compact pow commitment"))?;
+    let indexes = sample_indexes(
+        &committed.seed,
+        committed.leaf_count,
+        compact_pow.sample_count,
+    )
+    .map_err(|e| Error::contract_err(e.to_string()))?;
+    Ok(to_binary(&QueryAnswer::CompactPowSampleIndexes {
+        height: committed.height,
+        commitment: committed.commitment.to_string(),
+        leaf_count: committed.leaf_count,
+        indexes,
+    })?)
+}
+
 fn query_config<A: Api, Q: Querier>(deps: &StateProxyDeps<A, Q>) -> Result<QueryResponse, Error> {
     let config = read_config(&deps.storage, &deps.api)?;
     Ok(to_binary(&QueryAnswer::Config(config))?)
@@ -60,6 +146,18 @@ fn query_best_header_hash<A: Api, Q: Querier>(
     Ok(to_binary(&result)?)
 }
 
+fn query_oldest_provable_height<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+) -> Result<QueryResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    let mut header_chain = HeaderChain::new(chaindb, Network::from_str(&config.bitcoin_network)?);
+    let height = header_chain
+        .oldest_height()?
+        .ok_or_else(|| StdError::generic_err("no oldest height of chain db"))?;
+    Ok(to_binary(&QueryAnswer::OldestProvableHeight { height })?)
+}
+
 fn query_block_header<A: Api, Q: Querier>(
     deps: &StateProxyDeps<A, Q>,
     height: u32,
@@ -81,13 +179,82 @@ fn query_verify_merkle_proof<A: Api, Q: Querier>(
     height: u32,
     tx: Binary,
     merkle_proof: MerkleProofMsg,
+    coinbase_merkle_proof: Option<MerkleProofMsg>,
+    coinbase_tx: Option<Binary>,
+) -> Result<QueryResponse, Error> {
+    let tx: Transaction = deserialize::<Transaction>(tx.as_slice())?;
+    verify_merkle_proof(
+        deps,
+        height,
+        &tx,
+        merkle_proof,
+        coinbase_merkle_proof,
+        coinbase_tx,
+    )?;
+    Ok(to_binary(&QueryAnswer::VerifyMerkleProof {
+        success: true,
+    })?)
+}
+
+fn query_verify_and_decode_tx<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    height: u32,
+    tx: Binary,
+    merkle_proof: MerkleProofMsg,
+    coinbase_merkle_proof: Option<MerkleProofMsg>,
+    coinbase_tx: Option<Binary>,
 ) -> Result<QueryResponse, Error> {
     let config = read_config(&deps.storage, &deps.api)?;
-    let chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    let network = Network::from_str(&config.bitcoin_network)?;
     let tx: Transaction = deserialize::<Transaction>(tx.as_slice())?;
+    let (confirmations, header, coinbase_proof) = verify_merkle_proof(
+        deps,
+        height,
+        &tx,
+        merkle_proof,
+        coinbase_merkle_proof,
+        coinbase_tx,
+    )?;
+    let outputs = tx
+        .output
+        .iter()
+        .map(|output| TxOutMsg {
+            value: output.value,
+            script_pubkey: Binary(output.script_pubkey.to_bytes()),
+            address: Address::from_script(&output.script_pubkey, network)
+                .map(|address| address.to_string()),
+        })
+        .collect();
+    Ok(to_binary(&QueryAnswer::VerifyAndDecodeTx {
+        txid: tx.txid().to_string(),
+        outputs,
+        confirmations,
+        block_time: header.time,
+        block_reward: coinbase_proof.map(|coinbase_proof| BlockRewardMsg {
+            subsidy: block_subsidy(height, network),
+            coinbase_value: coinbase_proof.coinbase_value(),
+        }),
+    })?)
+}
+
+/// verifies that the tx is confirmed in the block at the height.
+/// if the coinbase tx is given, the height is also checked against the coinbase tx.
+/// returns the number of confirmations, the block header and the verified coinbase proof.
+fn verify_merkle_proof<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    height: u32,
+    tx: &Transaction,
+    merkle_proof: MerkleProofMsg,
+    coinbase_merkle_proof: Option<MerkleProofMsg>,
+    coinbase_tx: Option<Binary>,
+) -> Result<(u32, BlockHeader, Option<CoinbaseProof>), Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    validate_tx_length(tx)?;
     let txid = tx.txid();
     let merkle_proof = msg_to_merkle_proof(merkle_proof)?;
-    let mut header_chain = HeaderChain::new(chaindb, Network::from_str(&config.bitcoin_network)?);
+    let network = Network::from_str(&config.bitcoin_network)?;
+    let mut header_chain = HeaderChain::new(chaindb, network);
     let tip_height = header_chain
         .tip_height()?
         .ok_or_else(|| Error::contract_err("no tip"))?;
@@ -103,8 +270,37 @@ fn query_verify_merkle_proof<A: Api, Q: Querier>(
         return Err(Error::contract_err("merkle path and tx does not match"));
     }
 
+    let coinbase_proof = match (coinbase_merkle_proof, coinbase_tx) {
+        (Some(coinbase_merkle_proof), Some(coinbase_tx)) => {
+            let coinbase_proof = CoinbaseProof {
+                coinbase_tx: deserialize::<Transaction>(coinbase_tx.as_slice())?,
+                coinbase_proof: msg_to_merkle_proof(coinbase_merkle_proof)?,
+            };
+            // check if merkle path has the same depth as the coinbase
+            merkle_proof
+                .verify_depth(&coinbase_proof.coinbase_tx, &coinbase_proof.coinbase_proof)?;
+            // check if the coinbase tx of the block commits to the height
+            if coinbase_proof.verify(height, network)? != header.header.merkle_root {
+                return Err(Error::contract_err("invalid coinbase merkle root"));
+            }
+            Some(coinbase_proof)
+        }
+        (None, None) => None,
+        (Some(_), None) => {
+            return Err(Error::contract_err(
+                "coinbase merkle proof requires coinbase tx",
+            ))
+        }
+        (None, Some(_)) => {
+            return Err(Error::contract_err(
+                "coinbase tx requires coinbase merkle proof",
+            ))
+        }
+    };
+
     // get block header from storage
-    if tip_height.saturating_sub(height) + 1 < config.confirmation.into() {
+    let confirmations = tip_height.saturating_sub(height) + 1;
+    if confirmations < config.confirmation.into() {
         return Err(Error::contract_err("not confirmed yet"));
     }
 
@@ -112,8 +308,134 @@ fn query_verify_merkle_proof<A: Api, Q: Querier>(
     if merkle_proof.merkle_root()? != header.header.merkle_root {
         return Err(Error::contract_err("invalid merkle root"));
     }
-    Ok(to_binary(&QueryAnswer::VerifyMerkleProof {
+    Ok((confirmations, header.header, coinbase_proof))
+}
+
+fn query_verify_tx_out_proof<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    tx: Binary,
+    proof: Binary,
+) -> Result<QueryResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    let tx: Transaction = deserialize::<Transaction>(tx.as_slice())?;
+    validate_tx_length(&tx)?;
+    let txid = tx.txid();
+    let merkle_block: MerkleBlock = deserialize::<MerkleBlock>(proof.as_slice())?;
+    let mut header_chain = HeaderChain::new(chaindb, Network::from_str(&config.bitcoin_network)?);
+    let tip_height = header_chain
+        .tip_height()?
+        .ok_or_else(|| Error::contract_err("no tip"))?;
+    let height = header_chain
+        .height_of(&merkle_block.header.block_hash())?
+        .ok_or_else(|| Error::contract_err("block not found"))?;
+
+    //
+    // Validate Tx Confirmed
+    //
+    // check if the partial merkle tree commits to merkle root of the block and contains tx
+    let mut matches = vec![];
+    let mut indexes = vec![];
+    merkle_block.extract_matches(&mut matches, &mut indexes)?;
+    if !matches.contains(&txid) {
+        return Err(Error::contract_err("merkle block and tx does not match"));
+    }
+
+    if tip_height.saturating_sub(height) + 1 < config.confirmation.into() {
+        return Err(Error::contract_err("not confirmed yet"));
+    }
+    Ok(to_binary(&QueryAnswer::VerifyTxOutProof {
         success: true,
+        height,
+    })?)
+}
+
+fn query_verify_witness_merkle_proof<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    height: u32,
+    tx: Binary,
+    coinbase_tx: Binary,
+    coinbase_merkle_proof: MerkleProofMsg,
+    witness_merkle_proof: MerkleProofMsg,
+) -> Result<QueryResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    let tx: Transaction = deserialize::<Transaction>(tx.as_slice())?;
+    validate_tx_length(&tx)?;
+    let wtxid = tx.wtxid();
+    let witness_proof = WitnessProof {
+        coinbase_tx: deserialize::<Transaction>(coinbase_tx.as_slice())?,
+        coinbase_proof: msg_to_merkle_proof(coinbase_merkle_proof)?,
+        witness_proof: msg_to_merkle_proof(witness_merkle_proof)?,
+    };
+    let mut header_chain = HeaderChain::new(chaindb, Network::from_str(&config.bitcoin_network)?);
+    let tip_height = header_chain
+        .tip_height()?
+        .ok_or_else(|| Error::contract_err("no tip"))?;
+    let header = header_chain
+        .header_at(height)?
+        .ok_or_else(|| Error::contract_err("block not found"))?;
+
+    //
+    // Validate Tx Confirmed
+    //
+    // check if the coinbase tx commits to wtxid
+    let merkle_root = witness_proof.verify(&wtxid)?;
+
+    if tip_height.saturating_sub(height) + 1 < config.confirmation.into() {
+        return Err(Error::contract_err("not confirmed yet"));
+    }
+
+    // check if the block contains merkle root of coinbase merkle path
+    if merkle_root != header.header.merkle_root {
+        return Err(Error::contract_err("invalid merkle root"));
+    }
+    Ok(to_binary(&QueryAnswer::VerifyWitnessMerkleProof {
+        success: true,
+    })?)
+}
+
+fn query_verify_block_filter<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    height: u32,
+    filter: Binary,
+    prev_filter_header: String,
+    scripts: Vec<Binary>,
+) -> Result<QueryResponse, Error> {
+    let config = read_config(&deps.storage, &deps.api)?;
+    let chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    let filter = BlockFilter::new(filter.as_slice());
+    let prev_filter_header = FilterHeader::from_str(&prev_filter_header)?;
+    let mut header_chain = HeaderChain::new(chaindb, Network::from_str(&config.bitcoin_network)?);
+    let tip_height = header_chain
+        .tip_height()?
+        .ok_or_else(|| Error::contract_err("no tip"))?;
+    let header = header_chain
+        .header_at(height)?
+        .ok_or_else(|| Error::contract_err("block not found"))?;
+    let block_hash = header.header.block_hash();
+
+    //
+    // Validate Filter
+    //
+    // check if the filter is committed by the stored filter header
+    let filter_header = read_filter_header(&deps.storage, &block_hash)?
+        .ok_or_else(|| Error::contract_err("filter header not found"))?;
+    if filter.filter_header(&prev_filter_header) != filter_header {
+        return Err(Error::contract_err("invalid filter header"));
+    }
+
+    let confirmations = tip_height.saturating_sub(height) + 1;
+    if confirmations < config.confirmation.into() {
+        return Err(Error::contract_err("not confirmed yet"));
+    }
+    let matched = filter.match_any(
+        &block_hash,
+        &mut scripts.iter().map(|script| script.as_slice()),
+    )?;
+    Ok(to_binary(&QueryAnswer::VerifyBlockFilter {
+        matched,
+        confirmations,
     })?)
 }
 
@@ -121,7 +443,7 @@ pub fn msg_to_merkle_proof(msg: MerkleProofMsg) -> Result<MerkleProof, Error> {
     let siblings: Result<Vec<TxMerkleNode>, _> = msg
         .siblings
         .iter()
-        .map(|x| TxMerkleNode::from_str(&x))
+        .map(|x| TxMerkleNode::from_str(x))
         .collect();
     Ok(MerkleProof {
         prefix: msg.prefix,
@@ -1,15 +1,28 @@
 use super::*;
 use crate::state::chaindb::StorageChainDB;
-use bitcoin::consensus::encode::deserialize;
-use bitcoin::{BlockHeader, Network};
+use bitcoin::blockdata::constants::genesis_block;
+use bitcoin::blockdata::script::Script;
+use bitcoin::consensus::encode::{deserialize, serialize};
+use bitcoin::hash_types::{BlockHash, FilterHash, FilterHeader};
+use bitcoin::hashes::Hash;
+use bitcoin::{BlockHeader, Network, Transaction, TxOut};
+use bitcoin_header_chain::compact_pow::{self, CompactPowProof};
 use bitcoin_header_chain::header_chain::{
     chaindb::ReadonlyChainDB, HeaderChain, StoredBlockHeader,
 };
+use bitcoin_header_chain::regtest::RegtestChain;
+use contract_test_utils::context::{
+    Context, STATE_PROXY_CONTRACT_ADDRESS, STATE_PROXY_CONTRACT_HASH,
+};
 use contract_test_utils::contract_runner::ContractRunner;
-use cosmwasm_std::{Binary, StdError};
-use shared_types::bitcoin_spv::{HandleMsg, QueryMsg};
+use cosmwasm_std::{from_binary, Binary, StdError};
+use shared_types::bitcoin_spv::{
+    CompactPowConfig, HandleMsg, InitMsg, QueryAnswer, QueryMsg, SampledHeaderMsg,
+};
 use shared_types::state_proxy::client::Secp256k1ApiSigner;
+use shared_types::state_proxy::client::Seed;
 use shared_types::state_proxy::client::StateProxyDeps;
+use shared_types::{ContractReference, RelayCallback, RelayCallbackMsg};
 
 #[test]
 fn test_add_headers_sanity() {
@@ -21,6 +34,7 @@ fn test_add_headers_sanity() {
         let handle_msg = HandleMsg::AddHeaders {
             tip_height: i as u32,
             headers: vec![Binary::from(header.clone())],
+            relay_callback: None,
         };
         BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg).unwrap();
         let deps = context.client_deps();
@@ -54,6 +68,7 @@ fn test_add_headers_sanity_twice() {
         let handle_msg = HandleMsg::AddHeaders {
             tip_height: i as u32,
             headers: vec![Binary::from(regtest_block_header(i))],
+            relay_callback: None,
         };
         BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg.clone()).unwrap();
         BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg.clone()).unwrap();
@@ -90,6 +105,7 @@ fn test_add_headers_bulk() {
     let handle_msg = HandleMsg::AddHeaders {
         tip_height: 100,
         headers,
+        relay_callback: None,
     };
     BitcoinSPVRunner::run_handle(
         &mut context,
@@ -126,6 +142,7 @@ fn test_add_headers_empty() {
     let handle_msg = HandleMsg::AddHeaders {
         tip_height: 1,
         headers: vec![],
+        relay_callback: None,
     };
     let err = BitcoinSPVRunner::run_handle(
         &mut context,
@@ -138,3 +155,429 @@ fn test_add_headers_empty() {
         StdError::generic_err("contract error no header in msg")
     )
 }
+
+#[test]
+fn test_add_headers_relay_callback() {
+    let mut context = init_helper();
+    let handle_msg = HandleMsg::AddHeaders {
+        tip_height: 1,
+        headers: vec![Binary::from(regtest_block_header(1))],
+        relay_callback: Some(RelayCallback {
+            contract: ContractReference {
+                address: "shuriken".into(),
+                hash: "shuriken_hash".into(),
+            },
+            relayer: "lebron".into(),
+        }),
+    };
+
+    // CASE: the callback is requested by the relayer directly
+    let err = BitcoinSPVRunner::run_handle(
+        &mut context,
+        contract_test_utils::mock_env("lebron", &[]),
+        handle_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "contract error relay callback is not requested by the callback contract"
+        )
+    );
+
+    // CASE: the callback is requested by the callback contract
+    let response = BitcoinSPVRunner::run_handle(
+        &mut context,
+        contract_test_utils::mock_env("shuriken", &[]),
+        handle_msg,
+    )
+    .unwrap();
+    assert_eq!(response.messages.len(), 2);
+    contract_test_utils::assert_handle_response_message(
+        &response.messages[1],
+        "shuriken",
+        "shuriken_hash",
+        &RelayCallbackMsg::HeadersAccepted {
+            relayer: "lebron".into(),
+            count: 1,
+        },
+    );
+}
+
+#[test]
+fn test_add_headers_prune_headers() {
+    let mut context = init_helper_with_retained_header_length(Some(20));
+    let env = contract_test_utils::mock_env("lebron", &[]);
+    for i in 1..101 {
+        let handle_msg = HandleMsg::AddHeaders {
+            tip_height: i as u32,
+            headers: vec![Binary::from(regtest_block_header(i))],
+            relay_callback: None,
+        };
+        BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg).unwrap();
+    }
+
+    // assert old headers pruned
+    let err = BitcoinSPVRunner::run_query(&mut context, QueryMsg::BlockHeader { height: 80 })
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("header chain error header pruned")
+    );
+    // assert genesis retained as difficulty adjustment header, but not provable
+    let err =
+        BitcoinSPVRunner::run_query(&mut context, QueryMsg::BlockHeader { height: 0 }).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("header chain error header pruned")
+    );
+    let deps = context.client_deps();
+    let deps = StateProxyDeps::restore(
+        &deps.storage,
+        &deps.api,
+        &deps.querier,
+        CONTRACT_LABEL,
+        &Secp256k1ApiSigner::new(&deps.api),
+    )
+    .unwrap();
+    let mut chaindb = StorageChainDB::from_readonly_storage(&deps.storage);
+    assert!(chaindb.header_at(0).unwrap().is_some());
+    // assert latest headers retained
+    for i in 81..101 {
+        let query_result =
+            BitcoinSPVRunner::run_query(&mut context, QueryMsg::BlockHeader { height: i as u32 })
+                .unwrap();
+        match from_binary(&query_result).unwrap() {
+            QueryAnswer::BlockHeader { header } => {
+                assert_eq!(header, Binary::from(regtest_block_header(i)))
+            }
+            _ => panic!("Unexpected"),
+        }
+    }
+}
+
+#[test]
+fn test_add_checkpoint() {
+    let mut context = init_helper();
+    let env = contract_test_utils::mock_env("owner", &[]);
+    // the tx confirmed on the chain before the checkpoint
+    let tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: Script::new(),
+        }],
+    };
+    let mut chain = RegtestChain::new();
+    chain.mine_empty_blocks(1);
+    chain.mine_block(vec![tx.clone()]);
+    chain.mine_empty_blocks(6);
+    add_regtest_headers(&mut context, &chain, 1);
+    let verify_msg = QueryMsg::VerifyMerkleProof {
+        height: 2,
+        tx: Binary::from(serialize(&tx)),
+        merkle_proof: merkle_proof_msg(&chain.merkle_proof(2, 1)),
+        coinbase_merkle_proof: Some(merkle_proof_msg(&chain.merkle_proof(2, 0))),
+        coinbase_tx: Some(Binary::from(serialize(&chain.block_at(2).txdata[0]))),
+    };
+    BitcoinSPVRunner::run_query(&mut context, verify_msg.clone()).unwrap();
+
+    let regtest_header: BlockHeader = deserialize(&regtest_block_header(1)).unwrap();
+    let checkpoint = gen_block_header(
+        BlockHash::default(),
+        env.block.time as u32,
+        regtest_header.target(),
+        random_merkle_root(),
+    );
+
+    // only owner can add checkpoint
+    let handle_msg = HandleMsg::AddCheckpoint {
+        height: 4032,
+        header: Binary::from(serialize(&checkpoint)),
+    };
+    let err = BitcoinSPVRunner::run_handle(
+        &mut context,
+        contract_test_utils::mock_env("lebron", &[]),
+        handle_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("contract error not owner"));
+
+    BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg.clone()).unwrap();
+    let query_result =
+        BitcoinSPVRunner::run_query(&mut context, QueryMsg::BestHeaderHash {}).unwrap();
+    match from_binary(&query_result).unwrap() {
+        QueryAnswer::BestHeaderHash { hash } => {
+            assert_eq!(hash, checkpoint.block_hash().to_string())
+        }
+        _ => panic!("Unexpected"),
+    }
+
+    // the tx before the checkpoint is no longer provable
+    let err = BitcoinSPVRunner::run_query(&mut context, verify_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("header chain error header pruned")
+    );
+
+    // checkpoint can not move the tip backwards
+    let err = BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("header chain error checkpoint must be ahead of the tip")
+    );
+
+    // headers are extended from the checkpoint
+    let next_header = gen_block_header(
+        checkpoint.block_hash(),
+        checkpoint.time + 1,
+        regtest_header.target(),
+        random_merkle_root(),
+    );
+    let handle_msg = HandleMsg::AddHeaders {
+        tip_height: 4033,
+        headers: vec![Binary::from(serialize(&next_header))],
+        relay_callback: None,
+    };
+    BitcoinSPVRunner::run_handle(&mut context, env, handle_msg).unwrap();
+}
+
+#[test]
+fn test_add_compact_pow_proof() {
+    let mut context = Context::new::<()>(vec![]);
+    let genesis = genesis_block(Network::Regtest).header;
+    let init_msg = InitMsg {
+        bitcoin_network: "regtest".to_string(),
+        initial_header: None,
+        confirmation: 6,
+        retained_header_length: None,
+        signet_challenge: None,
+        compact_pow: Some(CompactPowConfig {
+            sample_count: 16,
+            max_target_bits: genesis.bits,
+            commit_interval: 10,
+        }),
+        seed: Seed::default(),
+        state_proxy: ContractReference {
+            address: STATE_PROXY_CONTRACT_ADDRESS.into(),
+            hash: STATE_PROXY_CONTRACT_HASH.into(),
+        },
+        owner: "owner".into(),
+        filter_header_relayers: vec![],
+    };
+    BitcoinSPVRunner::run_init(&mut context, mock_env("instantiator", &[]), init_msg).unwrap();
+
+    let mut headers = Vec::with_capacity(2016);
+    let mut prev_header = genesis;
+    for _ in 0..2016 {
+        let header = gen_block_header(
+            prev_header.block_hash(),
+            prev_header.time + 600,
+            genesis.target(),
+            random_merkle_root(),
+        );
+        headers.push(header);
+        prev_header = header;
+    }
+    let commitment = compact_pow::commit(&headers).unwrap().to_string();
+    let env = contract_test_utils::mock_env("lebron", &[]);
+    let commit_msg = |leaf_count| HandleMsg::CommitCompactPowProof {
+        height: 2016,
+        commitment: commitment.clone(),
+        leaf_count,
+    };
+    let query_indexes = |context: &mut Context, prover: &str| {
+        let query_msg = QueryMsg::CompactPowSampleIndexes {
+            prover: prover.into(),
+        };
+        BitcoinSPVRunner::run_query(context, query_msg).map(|result| {
+            match from_binary(&result).unwrap() {
+                QueryAnswer::CompactPowSampleIndexes { indexes, .. } => indexes,
+                _ => panic!("Unexpected"),
+            }
+        })
+    };
+    let add_msg = |indexes: &[u32], leaf_count| {
+        let proof = CompactPowProof::build(&headers, indexes).unwrap();
+        HandleMsg::AddCompactPowProof {
+            height: 2016,
+            commitment: proof.commitment.to_string(),
+            leaf_count,
+            samples: proof
+                .samples
+                .iter()
+                .map(|sample| SampledHeaderMsg {
+                    header: Binary::from(serialize(&sample.header)),
+                    merkle_proof: merkle_proof_msg(&sample.proof),
+                    prev_merkle_proof: sample.prev_proof.as_ref().map(merkle_proof_msg),
+                })
+                .collect(),
+        }
+    };
+
+    // leaf count not matching the height
+    let err =
+        BitcoinSPVRunner::run_handle(&mut context, env.clone(), commit_msg(2015)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "contract error leaf count must be the number of headers from the tip"
+        )
+    );
+    // samples before the commitment
+    let seed_indexes = compact_pow::sample_indexes(&[0; 32], 2016, 16).unwrap();
+    let err = BitcoinSPVRunner::run_handle(&mut context, env.clone(), add_msg(&seed_indexes, 2016))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error no compact pow commitment")
+    );
+
+    BitcoinSPVRunner::run_handle(&mut context, env.clone(), commit_msg(2016)).unwrap();
+    let indexes = query_indexes(&mut context, "lebron").unwrap();
+    assert_eq!(indexes.len(), 18);
+    assert_eq!((indexes[0], indexes[17]), (0, 2015));
+    assert!(query_indexes(&mut context, "other").is_err());
+
+    // the commitment can not be replaced to redraw the seed
+    let err =
+        BitcoinSPVRunner::run_handle(&mut context, env.clone(), commit_msg(2016)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error compact pow commitment is pending")
+    );
+    // the other provers wait for the commit interval
+    let mut other_env = contract_test_utils::mock_env("other", &[]);
+    let err = BitcoinSPVRunner::run_handle(&mut context, other_env.clone(), commit_msg(2016))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error compact pow commitment is too early")
+    );
+    other_env.block.height += 10;
+    BitcoinSPVRunner::run_handle(&mut context, other_env, commit_msg(2016)).unwrap();
+    assert_ne!(query_indexes(&mut context, "other").unwrap(), indexes);
+
+    // samples of the other seed
+    let err = BitcoinSPVRunner::run_handle(&mut context, env.clone(), add_msg(&seed_indexes, 2016))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "header chain error compact pow error merkle path is not for the sampled leaf"
+        )
+    );
+    // samples of the other prover
+    let err = BitcoinSPVRunner::run_handle(
+        &mut context,
+        contract_test_utils::mock_env("third", &[]),
+        add_msg(&indexes, 2016),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error no compact pow commitment")
+    );
+
+    BitcoinSPVRunner::run_handle(&mut context, env.clone(), add_msg(&indexes, 2016)).unwrap();
+    let query_result =
+        BitcoinSPVRunner::run_query(&mut context, QueryMsg::BestHeaderHash {}).unwrap();
+    match from_binary(&query_result).unwrap() {
+        QueryAnswer::BestHeaderHash { hash } => {
+            assert_eq!(hash, prev_header.block_hash().to_string())
+        }
+        _ => panic!("Unexpected"),
+    }
+    // the commitment is consumed
+    assert!(query_indexes(&mut context, "lebron").is_err());
+
+    // compact pow is disabled by default
+    let mut context = init_helper();
+    let err = BitcoinSPVRunner::run_handle(&mut context, env, commit_msg(2016)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error compact pow is disabled")
+    );
+}
+
+#[test]
+fn test_add_filter_headers() {
+    let mut context = init_helper();
+    let mut chain = RegtestChain::new();
+    chain.mine_empty_blocks(4);
+    add_regtest_headers(&mut context, &chain, 1);
+    let filter_hashes: Vec<String> = chain
+        .blocks
+        .iter()
+        .map(|block| FilterHash::hash(&block_filter(block).content).to_string())
+        .collect();
+    let relayer = contract_test_utils::mock_env("relayer", &[]);
+    let owner = contract_test_utils::mock_env("owner", &[]);
+
+    // CASE: filter headers are trusted, so only the allowed relayers and the owner add them
+    let handle_msg = HandleMsg::AddFilterHeaders {
+        start_height: 0,
+        filter_hashes: filter_hashes.clone(),
+        prev_filter_header: None,
+    };
+    let err = BitcoinSPVRunner::run_handle(
+        &mut context,
+        contract_test_utils::mock_env("attacker", &[]),
+        handle_msg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error not allowed filter header relayer")
+    );
+
+    // CASE: no filter header before the start height
+    let handle_msg = HandleMsg::AddFilterHeaders {
+        start_height: 2,
+        filter_hashes: filter_hashes[2..].to_vec(),
+        prev_filter_header: None,
+    };
+    let err = BitcoinSPVRunner::run_handle(&mut context, relayer.clone(), handle_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error no previous filter header")
+    );
+
+    // CASE: only the owner can anchor the filter header chain
+    let anchor = FilterHash::hash(&[1]).filter_header(&FilterHeader::default());
+    let handle_msg = HandleMsg::AddFilterHeaders {
+        start_height: 2,
+        filter_hashes: filter_hashes[2..].to_vec(),
+        prev_filter_header: Some(anchor.to_string()),
+    };
+    let err = BitcoinSPVRunner::run_handle(&mut context, relayer.clone(), handle_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, StdError::generic_err("contract error not owner"));
+    BitcoinSPVRunner::run_handle(&mut context, owner.clone(), handle_msg).unwrap();
+
+    // CASE: relayers can not replace the filter headers
+    let handle_msg = HandleMsg::AddFilterHeaders {
+        start_height: 0,
+        filter_hashes: filter_hashes.clone(),
+        prev_filter_header: None,
+    };
+    let err = BitcoinSPVRunner::run_handle(&mut context, relayer.clone(), handle_msg.clone())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error conflicting filter header")
+    );
+    BitcoinSPVRunner::run_handle(&mut context, owner, handle_msg).unwrap();
+
+    // relayers extend the filter header chain
+    chain.mine_empty_blocks(1);
+    add_regtest_headers(&mut context, &chain, 5);
+    let handle_msg = HandleMsg::AddFilterHeaders {
+        start_height: 5,
+        filter_hashes: vec![FilterHash::hash(&block_filter(chain.tip()).content).to_string()],
+        prev_filter_header: None,
+    };
+    BitcoinSPVRunner::run_handle(&mut context, relayer, handle_msg).unwrap();
+}
@@ -1,20 +1,24 @@
 use super::*;
+use bitcoin::consensus::encode::serialize;
 use bitcoin::hash_types::{BlockHash, TxMerkleNode};
 use bitcoin::hashes::Hash;
+use bitcoin::util::bip158::{self, BlockFilter};
 use bitcoin::util::uint::Uint256;
-use bitcoin::BlockHeader;
+use bitcoin::{Block, BlockHeader};
 use bitcoin_header_chain::header_chain::is_valid_proof_of_work_hash;
+use bitcoin_header_chain::merkle_proof::MerkleProof;
+use bitcoin_header_chain::regtest::RegtestChain;
 use contract_test_utils::context::{
     ClientDeps, Context, STATE_PROXY_CONTRACT_ADDRESS, STATE_PROXY_CONTRACT_HASH,
 };
 use contract_test_utils::contract_runner::ContractRunner;
 use cosmwasm_std::Binary;
+use cosmwasm_std::Env;
 use cosmwasm_std::HandleResult;
 use cosmwasm_std::InitResponse;
 use cosmwasm_std::StdResult;
-use cosmwasm_std::{BlockInfo, Coin, ContractInfo, Env, Extern, HumanAddr, MessageInfo};
 use rand::{thread_rng, Rng};
-use shared_types::bitcoin_spv::{HandleMsg, InitMsg, QueryMsg};
+use shared_types::bitcoin_spv::{HandleMsg, InitMsg, MerkleProofMsg, QueryMsg};
 use shared_types::state_proxy::client::Seed;
 use shared_types::ContractReference;
 use std::string::ToString;
@@ -40,6 +44,10 @@ impl contract_test_utils::contract_runner::ContractRunner for BitcoinSPVRunner {
 }
 
 pub fn init_helper() -> Context {
+    init_helper_with_retained_header_length(None)
+}
+
+pub fn init_helper_with_retained_header_length(retained_header_length: Option<u32>) -> Context {
     let mut context = Context::new::<()>(vec![]);
     let env = mock_env("instantiator", &[]);
 
@@ -47,11 +55,16 @@ pub fn init_helper() -> Context {
         bitcoin_network: "regtest".to_string(),
         initial_header: None,
         confirmation: 6,
+        retained_header_length,
+        signet_challenge: None,
+        compact_pow: None,
         seed: Seed::default(),
         state_proxy: ContractReference {
             address: STATE_PROXY_CONTRACT_ADDRESS.into(),
             hash: STATE_PROXY_CONTRACT_HASH.into(),
         },
+        owner: "owner".into(),
+        filter_header_relayers: vec!["relayer".into()],
     };
     BitcoinSPVRunner::run_init(&mut context, env, init_msg).unwrap();
     context
@@ -193,3 +206,31 @@ pub fn gen_block_header(
         header.nonce += 1;
     }
 }
+
+pub fn merkle_proof_msg(proof: &MerkleProof) -> MerkleProofMsg {
+    MerkleProofMsg {
+        prefix: proof.prefix.clone(),
+        siblings: proof.siblings.iter().map(|x| x.to_string()).collect(),
+    }
+}
+
+/// adds the headers of the chain from the height.
+pub fn add_regtest_headers(context: &mut Context, chain: &RegtestChain, from: u32) {
+    let handle_msg = HandleMsg::AddHeaders {
+        tip_height: chain.tip_height(),
+        headers: chain
+            .headers_from(from)
+            .iter()
+            .map(|header| Binary::from(serialize(header)))
+            .collect(),
+        relay_callback: None,
+    };
+    let env = mock_env("relayer", &[]);
+    BitcoinSPVRunner::run_handle(context, env, handle_msg).unwrap();
+}
+
+/// BIP158 basic filter of the block whose txs spend no output.
+pub fn block_filter(block: &Block) -> BlockFilter {
+    BlockFilter::new_script_filter(block, |outpoint| Err(bip158::Error::UtxoMissing(*outpoint)))
+        .unwrap()
+}
@@ -1,9 +1,13 @@
 // Init Tests
 use super::*;
-use crate::state::config::read_config;
+use contract_test_utils::context::{
+    Context, STATE_PROXY_CONTRACT_ADDRESS, STATE_PROXY_CONTRACT_HASH,
+};
 use contract_test_utils::contract_runner::ContractRunner;
-use cosmwasm_std::from_binary;
-use shared_types::bitcoin_spv::{QueryAnswer, QueryMsg};
+use cosmwasm_std::{from_binary, Binary, StdError};
+use shared_types::bitcoin_spv::{InitMsg, QueryAnswer, QueryMsg};
+use shared_types::state_proxy::client::Seed;
+use shared_types::ContractReference;
 
 #[test]
 fn test_init_sanity() {
@@ -20,3 +24,57 @@ fn test_init_sanity() {
     assert_eq!(config.bitcoin_network, "regtest");
     assert_eq!(config.confirmation, 6);
 }
+
+#[test]
+fn test_init_retained_header_length_too_short() {
+    let mut context = Context::new::<()>(vec![]);
+    let env = mock_env("instantiator", &[]);
+    for retained_header_length in [5, 15].iter() {
+        let init_msg = InitMsg {
+            bitcoin_network: "regtest".to_string(),
+            initial_header: None,
+            confirmation: 6,
+            retained_header_length: Some(*retained_header_length),
+            signet_challenge: None,
+            compact_pow: None,
+            seed: Seed::default(),
+            state_proxy: ContractReference {
+                address: STATE_PROXY_CONTRACT_ADDRESS.into(),
+                hash: STATE_PROXY_CONTRACT_HASH.into(),
+            },
+            owner: "owner".into(),
+            filter_header_relayers: vec![],
+        };
+        let err = BitcoinSPVRunner::run_init(&mut context, env.clone(), init_msg).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("contract error retained header length too short")
+        );
+    }
+}
+
+#[test]
+fn test_init_signet_challenge_not_signet() {
+    let mut context = Context::new::<()>(vec![]);
+    let env = mock_env("instantiator", &[]);
+    let init_msg = InitMsg {
+        bitcoin_network: "regtest".to_string(),
+        initial_header: None,
+        confirmation: 6,
+        retained_header_length: None,
+        signet_challenge: Some(Binary::from(vec![0x51])),
+        compact_pow: None,
+        seed: Seed::default(),
+        state_proxy: ContractReference {
+            address: STATE_PROXY_CONTRACT_ADDRESS.into(),
+            hash: STATE_PROXY_CONTRACT_HASH.into(),
+        },
+        owner: "owner".into(),
+        filter_header_relayers: vec![],
+    };
+    let err = BitcoinSPVRunner::run_init(&mut context, env, init_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error signet challenge is only for signet")
+    );
+}
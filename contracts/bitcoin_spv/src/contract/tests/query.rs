@@ -1,13 +1,21 @@
 use super::*;
 use crate::state::chaindb::StorageChainDB;
+use bitcoin::blockdata::script::Script;
 use bitcoin::consensus::encode::{deserialize, serialize};
-use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::hash_types::{FilterHash, FilterHeader, TxMerkleNode, Txid, WitnessMerkleNode};
+use bitcoin::hashes::{Hash, HashEngine};
+use bitcoin::util::bip158::BlockFilter;
 use bitcoin::util::hash::bitcoin_merkle_root;
-use bitcoin::{Address, BlockHeader, Network, Transaction, TxOut};
+use bitcoin::util::merkleblock::MerkleBlock;
+use bitcoin::{Address, Block, BlockHeader, Network, OutPoint, Transaction, TxIn, TxOut};
 use bitcoin_header_chain::header_chain::HeaderChain;
+use bitcoin_header_chain::regtest::{self, RegtestChain};
+use contract_test_utils::context::Context;
 use contract_test_utils::contract_runner::ContractRunner;
-use cosmwasm_std::{from_binary, Binary, StdError};
-use shared_types::bitcoin_spv::{Config, MerkleProofMsg, QueryAnswer, QueryMsg};
+use cosmwasm_std::{from_binary, Binary, HumanAddr, StdError};
+use shared_types::bitcoin_spv::{
+    BlockRewardMsg, Config, HandleMsg, MerkleProofMsg, QueryAnswer, QueryMsg, TxOutMsg,
+};
 use shared_types::state_proxy::client::Secp256k1ApiSigner;
 use shared_types::state_proxy::client::StateProxyDeps;
 use std::str::FromStr;
@@ -35,7 +43,7 @@ fn test_query_tip() {
             .store_headers(i, vec![deserialized_header], deserialized_header.time)
             .unwrap();
         let msgs = &deps.storage.cosmos_msgs().unwrap();
-        context.exec_state_contract_messages(&msgs);
+        context.exec_state_contract_messages(msgs);
         let query_msg = QueryMsg::BestHeaderHash {};
         let query_result = BitcoinSPVRunner::run_query(&mut context, query_msg);
         let tip_block_hash: String = match from_binary(&query_result.unwrap()).unwrap() {
@@ -73,7 +81,7 @@ fn test_query_block() {
     let time = headers.last().unwrap().time;
     header_chain.store_headers(100, headers, time).unwrap();
     let msgs = &deps.storage.cosmos_msgs().unwrap();
-    context.exec_state_contract_messages(&msgs);
+    context.exec_state_contract_messages(msgs);
     for i in 1..101 {
         let header = Binary::from(regtest_block_header(i));
         let query_msg = QueryMsg::BlockHeader { height: i as u32 };
@@ -86,6 +94,46 @@ fn test_query_block() {
     }
 }
 
+#[test]
+fn test_query_oldest_provable_height() {
+    let mut context = init_helper_with_retained_header_length(Some(20));
+    let query_oldest_provable_height = |context: &mut Context| -> u32 {
+        let query_result =
+            BitcoinSPVRunner::run_query(context, QueryMsg::OldestProvableHeight {}).unwrap();
+        match from_binary(&query_result).unwrap() {
+            QueryAnswer::OldestProvableHeight { height } => height,
+            _ => panic!("Unexpected"),
+        }
+    };
+    assert_eq!(query_oldest_provable_height(&mut context), 0);
+
+    let env = contract_test_utils::mock_env("lebron", &[]);
+    let mut headers = vec![];
+    for i in 1..11 {
+        headers.push(Binary::from(regtest_block_header(i)));
+    }
+    let handle_msg = HandleMsg::AddHeaders {
+        tip_height: 10,
+        headers,
+        relay_callback: None,
+    };
+    BitcoinSPVRunner::run_handle(&mut context, env.clone(), handle_msg).unwrap();
+    // assert nothing pruned yet
+    assert_eq!(query_oldest_provable_height(&mut context), 0);
+
+    let mut headers = vec![];
+    for i in 11..51 {
+        headers.push(Binary::from(regtest_block_header(i)));
+    }
+    let handle_msg = HandleMsg::AddHeaders {
+        tip_height: 50,
+        headers,
+        relay_callback: None,
+    };
+    BitcoinSPVRunner::run_handle(&mut context, env, handle_msg).unwrap();
+    assert_eq!(query_oldest_provable_height(&mut context), 31);
+}
+
 #[test]
 fn test_query_config() {
     let mut context = init_helper();
@@ -95,15 +143,25 @@ fn test_query_config() {
         QueryAnswer::Config(Config {
             confirmation,
             bitcoin_network,
+            retained_header_length,
+            signet_challenge,
+            compact_pow,
             state_proxy,
+            owner,
+            filter_header_relayers,
         }) => {
             assert_eq!(confirmation, 6);
+            assert!(retained_header_length.is_none());
+            assert!(signet_challenge.is_none());
+            assert!(compact_pow.is_none());
             assert_eq!(bitcoin_network, "regtest");
             assert_eq!(
                 state_proxy.address.to_string(),
                 "state_proxy_address".to_string()
             );
             assert_eq!(state_proxy.hash, "state_proxy_hash".to_string());
+            assert_eq!(owner, HumanAddr::from("owner"));
+            assert_eq!(filter_header_relayers, vec![HumanAddr::from("relayer")]);
         }
         _ => panic!("Unexpected"),
     };
@@ -114,7 +172,7 @@ fn test_verify_merkle_proof() {
     let mut context = init_helper();
     let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
 
-    let txdata = vec![
+    let txdata = [
         // sibling
         Transaction {
             version: 1,
@@ -159,7 +217,7 @@ fn test_verify_merkle_proof() {
         tip.header.target(),
         merkle_root,
     );
-    let mut headers = vec![confirmed_header.clone()];
+    let mut headers = vec![confirmed_header];
     let mut prev_header = confirmed_header;
     for _ in 0..5 {
         let header = gen_block_header(
@@ -168,7 +226,7 @@ fn test_verify_merkle_proof() {
             prev_header.target(),
             random_merkle_root(),
         );
-        headers.push(header.clone());
+        headers.push(header);
         prev_header = header;
     }
     header_chain
@@ -180,7 +238,7 @@ fn test_verify_merkle_proof() {
         .unwrap();
 
     let msgs = &deps.storage.cosmos_msgs().unwrap();
-    context.exec_state_contract_messages(&msgs);
+    context.exec_state_contract_messages(msgs);
     // query verify merkle proof
     let msg = QueryMsg::VerifyMerkleProof {
         height: tip_height + 1,
@@ -189,11 +247,13 @@ fn test_verify_merkle_proof() {
             prefix: vec![true],
             siblings: vec![txdata[1].txid().to_string(), txdata[0].txid().to_string()],
         },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
     };
     let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
     match from_binary(&response).unwrap() {
         QueryAnswer::VerifyMerkleProof { success } => {
-            assert_eq!(success, true)
+            assert!(success)
         }
         _ => unreachable!(),
     }
@@ -204,7 +264,7 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
     let mut context = init_helper();
     let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
 
-    let txdata = vec![
+    let txdata = [
         // sibling
         Transaction {
             version: 1,
@@ -250,7 +310,7 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
         tip.header.target(),
         merkle_root,
     );
-    let mut headers = vec![confirmed_header.clone()];
+    let mut headers = vec![confirmed_header];
     let mut prev_header = confirmed_header;
     for _ in 0..5 {
         let header = gen_block_header(
@@ -259,7 +319,7 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
             prev_header.target(),
             random_merkle_root(),
         );
-        headers.push(header.clone());
+        headers.push(header);
         prev_header = header;
     }
     header_chain
@@ -270,7 +330,7 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
         )
         .unwrap();
     let msgs = &deps.storage.cosmos_msgs().unwrap();
-    context.exec_state_contract_messages(&msgs);
+    context.exec_state_contract_messages(msgs);
 
     // CASE: no sibling
     let msg = QueryMsg::VerifyMerkleProof {
@@ -280,6 +340,8 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
             prefix: vec![],
             siblings: vec![],
         },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
     };
     let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
     assert_eq!(err, StdError::generic_err("merkle path error no sibling"));
@@ -292,6 +354,8 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
             prefix: vec![false],
             siblings: vec![txdata[0].txid().to_string(), txdata[1].txid().to_string()],
         },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
     };
     let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
     assert_eq!(
@@ -307,6 +371,8 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
             prefix: vec![true],
             siblings: vec![txdata[1].txid().to_string(), txdata[0].txid().to_string()],
         },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
     };
     let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
     assert_eq!(
@@ -315,12 +381,56 @@ fn test_verify_merkle_proof_invalid_merkle_proof() {
     );
 }
 
+#[test]
+fn test_verify_merkle_proof_regtest_chain_reorg() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+    let mint_tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    let verify_msg = |chain: &RegtestChain| QueryMsg::VerifyMerkleProof {
+        height: 2,
+        tx: Binary::from(serialize(&mint_tx)),
+        merkle_proof: merkle_proof_msg(&chain.merkle_proof(2, 1)),
+        coinbase_merkle_proof: Some(merkle_proof_msg(&chain.merkle_proof(2, 0))),
+        coinbase_tx: Some(Binary::from(serialize(&chain.block_at(2).txdata[0]))),
+    };
+
+    let mut chain = RegtestChain::new();
+    chain.mine_empty_blocks(1);
+    chain.mine_block(vec![mint_tx.clone()]);
+    chain.mine_empty_blocks(4);
+    add_regtest_headers(&mut context, &chain, 1);
+    let err = BitcoinSPVRunner::run_query(&mut context, verify_msg(&chain)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error not confirmed yet")
+    );
+
+    // the fork without the mint tx replaces the chain before the confirmation.
+    // the confirmed block can not be replaced, since the confirmation is longer than UNTRUSTED_LENGTH.
+    let mut fork = chain.fork_at(1);
+    fork.mine_empty_blocks(7);
+    add_regtest_headers(&mut context, &fork, 2);
+    let err = BitcoinSPVRunner::run_query(&mut context, verify_msg(&chain)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error invalid coinbase merkle root")
+    );
+}
+
 #[test]
 fn test_verify_mint_tx_not_confirmed() {
     let mut context = init_helper();
     let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
 
-    let txdata = vec![
+    let txdata = [
         // sibling
         Transaction {
             version: 1,
@@ -365,7 +475,7 @@ fn test_verify_mint_tx_not_confirmed() {
         merkle_root,
     );
     // CASE: confirm only 5 times
-    let mut headers = vec![confirmed_header.clone()];
+    let mut headers = vec![confirmed_header];
     let mut prev_header = confirmed_header;
     for _ in 0..4 {
         let header = gen_block_header(
@@ -374,7 +484,7 @@ fn test_verify_mint_tx_not_confirmed() {
             prev_header.target(),
             random_merkle_root(),
         );
-        headers.push(header.clone());
+        headers.push(header);
         prev_header = header;
     }
     header_chain
@@ -385,7 +495,7 @@ fn test_verify_mint_tx_not_confirmed() {
         )
         .unwrap();
     let msgs = &deps.storage.cosmos_msgs().unwrap();
-    context.exec_state_contract_messages(&msgs);
+    context.exec_state_contract_messages(msgs);
     // handle verify mint tx
     let msg = QueryMsg::VerifyMerkleProof {
         height: tip_height + 1,
@@ -394,6 +504,8 @@ fn test_verify_mint_tx_not_confirmed() {
             prefix: vec![true],
             siblings: vec![txdata[1].txid().to_string(), txdata[0].txid().to_string()],
         },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
     };
     let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
     assert_eq!(
@@ -401,3 +513,729 @@ fn test_verify_mint_tx_not_confirmed() {
         StdError::generic_err("contract error not confirmed yet")
     );
 }
+
+#[test]
+fn test_verify_tx_out_proof() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+
+    let txdata = [
+        // sibling
+        Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![],
+            output: vec![],
+        },
+        // mint tx
+        Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: address.script_pubkey(),
+            }],
+        },
+    ];
+    let txids: Vec<Txid> = txdata.iter().map(|tx| tx.txid()).collect();
+    let merkle_root =
+        TxMerkleNode::from_hash(bitcoin_merkle_root(txids.iter().map(|txid| txid.as_hash())));
+
+    // set block headers to header chain
+    let deps = context.client_deps();
+    let mut deps = StateProxyDeps::restore(
+        &deps.storage,
+        &deps.api,
+        &deps.querier,
+        CONTRACT_LABEL,
+        &Secp256k1ApiSigner::new(&deps.api),
+    )
+    .unwrap();
+    let chain_db = StorageChainDB::from_storage(&mut deps.storage);
+    let mut header_chain = HeaderChain::new(chain_db, Network::Regtest);
+    let tip = header_chain.tip().unwrap().unwrap();
+    let tip_height = header_chain.tip_height().unwrap().unwrap();
+    // mint tx confirmed block header
+    let confirmed_header = gen_block_header(
+        tip.header.block_hash(),
+        contract_test_utils::mock_timestamp(),
+        tip.header.target(),
+        merkle_root,
+    );
+    // confirm 5 times
+    let mut headers = vec![confirmed_header];
+    let mut prev_header = confirmed_header;
+    for _ in 0..4 {
+        let header = gen_block_header(
+            prev_header.block_hash(),
+            prev_header.time + 600,
+            prev_header.target(),
+            random_merkle_root(),
+        );
+        headers.push(header);
+        prev_header = header;
+    }
+    header_chain
+        .store_headers(
+            tip_height + 5,
+            headers,
+            contract_test_utils::mock_timestamp(),
+        )
+        .unwrap();
+    let msgs = &deps.storage.cosmos_msgs().unwrap();
+    context.exec_state_contract_messages(msgs);
+
+    let merkle_block =
+        MerkleBlock::from_header_txids_with_predicate(&confirmed_header, &txids, |txid| {
+            txid == &txids[1]
+        });
+
+    // CASE: not confirmed yet
+    let msg = QueryMsg::VerifyTxOutProof {
+        tx: Binary::from(serialize(&txdata[1])),
+        proof: Binary::from(serialize(&merkle_block)),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error not confirmed yet")
+    );
+
+    // confirm 6 times
+    let deps = context.client_deps();
+    let mut deps = StateProxyDeps::restore(
+        &deps.storage,
+        &deps.api,
+        &deps.querier,
+        CONTRACT_LABEL,
+        &Secp256k1ApiSigner::new(&deps.api),
+    )
+    .unwrap();
+    let chain_db = StorageChainDB::from_storage(&mut deps.storage);
+    let mut header_chain = HeaderChain::new(chain_db, Network::Regtest);
+    let header = gen_block_header(
+        prev_header.block_hash(),
+        prev_header.time + 600,
+        prev_header.target(),
+        random_merkle_root(),
+    );
+    header_chain
+        .store_headers(
+            tip_height + 6,
+            vec![header],
+            contract_test_utils::mock_timestamp(),
+        )
+        .unwrap();
+    let msgs = &deps.storage.cosmos_msgs().unwrap();
+    context.exec_state_contract_messages(msgs);
+
+    // CASE: success
+    let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyTxOutProof { success, height } => {
+            assert!(success);
+            assert_eq!(height, tip_height + 1);
+        }
+        _ => unreachable!(),
+    }
+
+    // CASE: tx is not matched in merkle block
+    let msg = QueryMsg::VerifyTxOutProof {
+        tx: Binary::from(serialize(&txdata[0])),
+        proof: Binary::from(serialize(&merkle_block)),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error merkle block and tx does not match")
+    );
+
+    // CASE: partial merkle tree does not commit to merkle root of the block
+    let invalid_merkle_block =
+        MerkleBlock::from_header_txids_with_predicate(&confirmed_header, &txids[1..], |_| true);
+    let msg = QueryMsg::VerifyTxOutProof {
+        tx: Binary::from(serialize(&txdata[1])),
+        proof: Binary::from(serialize(&invalid_merkle_block)),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("merkle block error MerkleRootMismatch")
+    );
+
+    // CASE: block not found in header chain
+    let unknown_header = gen_block_header(
+        tip.header.block_hash(),
+        contract_test_utils::mock_timestamp() + 1,
+        tip.header.target(),
+        merkle_root,
+    );
+    let unknown_merkle_block =
+        MerkleBlock::from_header_txids_with_predicate(&unknown_header, &txids, |txid| {
+            txid == &txids[1]
+        });
+    let msg = QueryMsg::VerifyTxOutProof {
+        tx: Binary::from(serialize(&txdata[1])),
+        proof: Binary::from(serialize(&unknown_merkle_block)),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("contract error block not found"));
+}
+
+// stores a block header with the merkle root and confirms it 6 times.
+// returns the height of the block.
+fn store_confirmed_header(context: &mut Context, merkle_root: TxMerkleNode) -> u32 {
+    let deps = context.client_deps();
+    let mut deps = StateProxyDeps::restore(
+        &deps.storage,
+        &deps.api,
+        &deps.querier,
+        CONTRACT_LABEL,
+        &Secp256k1ApiSigner::new(&deps.api),
+    )
+    .unwrap();
+    let chain_db = StorageChainDB::from_storage(&mut deps.storage);
+    let mut header_chain = HeaderChain::new(chain_db, Network::Regtest);
+    let tip = header_chain.tip().unwrap().unwrap();
+    let tip_height = header_chain.tip_height().unwrap().unwrap();
+    let confirmed_header = gen_block_header(
+        tip.header.block_hash(),
+        contract_test_utils::mock_timestamp(),
+        tip.header.target(),
+        merkle_root,
+    );
+    let mut headers = vec![confirmed_header];
+    let mut prev_header = confirmed_header;
+    for _ in 0..5 {
+        let header = gen_block_header(
+            prev_header.block_hash(),
+            prev_header.time + 600,
+            prev_header.target(),
+            random_merkle_root(),
+        );
+        headers.push(header);
+        prev_header = header;
+    }
+    header_chain
+        .store_headers(
+            tip_height + 6,
+            headers,
+            contract_test_utils::mock_timestamp(),
+        )
+        .unwrap();
+    let msgs = &deps.storage.cosmos_msgs().unwrap();
+    context.exec_state_contract_messages(msgs);
+    tip_height + 1
+}
+
+fn merkle_parent(left: &TxMerkleNode, right: &TxMerkleNode) -> TxMerkleNode {
+    let mut engine = TxMerkleNode::engine();
+    engine.input(&left[..]);
+    engine.input(&right[..]);
+    TxMerkleNode::from_engine(engine)
+}
+
+#[test]
+fn test_verify_merkle_proof_with_coinbase_merkle_proof() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+    // the confirmed header is stored next to the genesis.
+    let coinbase = regtest::coinbase_tx(1, 0);
+    let mint_tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    // merkle tree of 4 leaves
+    let leaves = [
+        TxMerkleNode::from_hash(coinbase.txid().as_hash()),
+        TxMerkleNode::from_hash(mint_tx.txid().as_hash()),
+        random_merkle_root(),
+        random_merkle_root(),
+    ];
+    let inner_left = merkle_parent(&leaves[0], &leaves[1]);
+    let inner_right = merkle_parent(&leaves[2], &leaves[3]);
+    let height = store_confirmed_header(&mut context, merkle_parent(&inner_left, &inner_right));
+    assert_eq!(height, 1);
+
+    let merkle_proof = MerkleProofMsg {
+        prefix: vec![true, false],
+        siblings: vec![
+            leaves[1].to_string(),
+            leaves[0].to_string(),
+            inner_right.to_string(),
+        ],
+    };
+    let coinbase_merkle_proof = MerkleProofMsg {
+        prefix: vec![false, false],
+        siblings: vec![
+            leaves[0].to_string(),
+            leaves[1].to_string(),
+            inner_right.to_string(),
+        ],
+    };
+
+    // CASE: success
+    let msg = QueryMsg::VerifyMerkleProof {
+        height,
+        tx: Binary::from(serialize(&mint_tx)),
+        merkle_proof: merkle_proof.clone(),
+        coinbase_merkle_proof: Some(coinbase_merkle_proof),
+        coinbase_tx: Some(Binary::from(serialize(&coinbase))),
+    };
+    let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyMerkleProof { success } => {
+            assert!(success)
+        }
+        _ => unreachable!(),
+    }
+
+    // CASE: coinbase merkle proof is not of the coinbase tx
+    let msg = QueryMsg::VerifyMerkleProof {
+        height,
+        tx: Binary::from(serialize(&mint_tx)),
+        merkle_proof: merkle_proof.clone(),
+        coinbase_merkle_proof: Some(merkle_proof.clone()),
+        coinbase_tx: Some(Binary::from(serialize(&coinbase))),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("merkle path error unmatched coinbase tx with coinbase proof")
+    );
+
+    // CASE: coinbase merkle proof is not for the first leaf
+    let mut swapped_coinbase_merkle_proof = merkle_proof.clone();
+    swapped_coinbase_merkle_proof.siblings.swap(0, 1);
+    let msg = QueryMsg::VerifyMerkleProof {
+        height,
+        tx: Binary::from(serialize(&mint_tx)),
+        merkle_proof: merkle_proof.clone(),
+        coinbase_merkle_proof: Some(swapped_coinbase_merkle_proof),
+        coinbase_tx: Some(Binary::from(serialize(&coinbase))),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("merkle path error not coinbase proof")
+    );
+
+    // CASE: coinbase merkle proof has the different depth
+    let msg = QueryMsg::VerifyMerkleProof {
+        height,
+        tx: Binary::from(serialize(&mint_tx)),
+        merkle_proof,
+        coinbase_merkle_proof: Some(MerkleProofMsg {
+            prefix: vec![false],
+            siblings: vec![leaves[0].to_string(), inner_right.to_string()],
+        }),
+        coinbase_tx: Some(Binary::from(serialize(&coinbase))),
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("merkle path error unmatched depth with coinbase proof")
+    );
+}
+
+#[test]
+fn test_verify_merkle_proof_inner_node_as_tx_attack() {
+    let mut context = init_helper();
+    // 64 bytes tx, whose serialization is two 32 bytes hashes.
+    let tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint::default(),
+            script_sig: Script::new(),
+            sequence: 0xffffffff,
+            witness: vec![],
+        }],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: Script::from(vec![0x51, 0x51, 0x51, 0x51]),
+        }],
+    };
+    let bytes = serialize(&tx);
+    assert_eq!(bytes.len(), 64);
+    // merkle tree of 4 leaves. the first 2 leaves are the halves of the tx.
+    let leaves = [
+        TxMerkleNode::from_slice(&bytes[..32]).unwrap(),
+        TxMerkleNode::from_slice(&bytes[32..]).unwrap(),
+        random_merkle_root(),
+        random_merkle_root(),
+    ];
+    let inner_left = merkle_parent(&leaves[0], &leaves[1]);
+    let inner_right = merkle_parent(&leaves[2], &leaves[3]);
+    assert_eq!(inner_left.as_hash(), tx.txid().as_hash());
+    let height = store_confirmed_header(&mut context, merkle_parent(&inner_left, &inner_right));
+
+    // the inner node is presented as the tx
+    let msg = QueryMsg::VerifyMerkleProof {
+        height,
+        tx: Binary::from(bytes),
+        merkle_proof: MerkleProofMsg {
+            prefix: vec![false],
+            siblings: vec![inner_left.to_string(), inner_right.to_string()],
+        },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("merkle path error ambiguous tx length")
+    );
+}
+
+#[test]
+fn test_verify_witness_merkle_proof() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+    let tx = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_hash(random_merkle_root().as_hash()),
+                vout: 0,
+            },
+            script_sig: Script::new(),
+            sequence: 0xffffffff,
+            witness: vec![vec![0x01; 72], vec![0x02; 33]],
+        }],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    let witness_reserved_value = [0u8; 32];
+    let witness_leaves = [
+        TxMerkleNode::default(),
+        TxMerkleNode::from_hash(tx.wtxid().as_hash()),
+    ];
+    let witness_root = merkle_parent(&witness_leaves[0], &witness_leaves[1]);
+    let commitment = Block::compute_witness_commitment(
+        &WitnessMerkleNode::from_hash(witness_root.as_hash()),
+        &witness_reserved_value,
+    );
+    let mut commitment_script = vec![0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+    commitment_script.extend_from_slice(&commitment[..]);
+    let coinbase_tx = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: Script::from(vec![0x01, 0x01]),
+            sequence: 0xffffffff,
+            witness: vec![witness_reserved_value.to_vec()],
+        }],
+        output: vec![
+            TxOut {
+                value: 5000000000,
+                script_pubkey: address.script_pubkey(),
+            },
+            TxOut {
+                value: 0,
+                script_pubkey: Script::from(commitment_script),
+            },
+        ],
+    };
+    let leaves = [
+        TxMerkleNode::from_hash(coinbase_tx.txid().as_hash()),
+        TxMerkleNode::from_hash(tx.txid().as_hash()),
+    ];
+    let height = store_confirmed_header(&mut context, merkle_parent(&leaves[0], &leaves[1]));
+
+    let coinbase_merkle_proof = MerkleProofMsg {
+        prefix: vec![false],
+        siblings: vec![leaves[0].to_string(), leaves[1].to_string()],
+    };
+    let witness_merkle_proof = MerkleProofMsg {
+        prefix: vec![true],
+        siblings: vec![witness_leaves[1].to_string(), witness_leaves[0].to_string()],
+    };
+
+    // CASE: success
+    let msg = QueryMsg::VerifyWitnessMerkleProof {
+        height,
+        tx: Binary::from(serialize(&tx)),
+        coinbase_tx: Binary::from(serialize(&coinbase_tx)),
+        coinbase_merkle_proof: coinbase_merkle_proof.clone(),
+        witness_merkle_proof: witness_merkle_proof.clone(),
+    };
+    let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyWitnessMerkleProof { success } => {
+            assert!(success)
+        }
+        _ => unreachable!(),
+    }
+
+    // CASE: witness is replaced
+    let mut malleated_tx = tx.clone();
+    malleated_tx.input[0].witness = vec![vec![0x03; 72], vec![0x02; 33]];
+    let msg = QueryMsg::VerifyWitnessMerkleProof {
+        height,
+        tx: Binary::from(serialize(&malleated_tx)),
+        coinbase_tx: Binary::from(serialize(&coinbase_tx)),
+        coinbase_merkle_proof: coinbase_merkle_proof.clone(),
+        witness_merkle_proof: MerkleProofMsg {
+            prefix: vec![true],
+            siblings: vec![
+                malleated_tx.wtxid().to_string(),
+                witness_leaves[0].to_string(),
+            ],
+        },
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("witness proof error unmatched witness commitment")
+    );
+
+    // CASE: coinbase tx of the other block
+    let msg = QueryMsg::VerifyWitnessMerkleProof {
+        height: height - 1,
+        tx: Binary::from(serialize(&tx)),
+        coinbase_tx: Binary::from(serialize(&coinbase_tx)),
+        coinbase_merkle_proof,
+        witness_merkle_proof,
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error invalid merkle root")
+    );
+}
+
+#[test]
+fn test_verify_and_decode_tx() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+    let op_return = Script::from(vec![0x6a, 0x01, 0x00]);
+    // the confirmed header is stored next to the genesis.
+    let coinbase = regtest::coinbase_tx(1, 0);
+    let tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![
+            TxOut {
+                value: 1000,
+                script_pubkey: address.script_pubkey(),
+            },
+            TxOut {
+                value: 0,
+                script_pubkey: op_return.clone(),
+            },
+        ],
+    };
+    let merkle_root = merkle_parent(
+        &TxMerkleNode::from_hash(coinbase.txid().as_hash()),
+        &TxMerkleNode::from_hash(tx.txid().as_hash()),
+    );
+    let height = store_confirmed_header(&mut context, merkle_root);
+    assert_eq!(height, 1);
+
+    let msg = QueryMsg::VerifyAndDecodeTx {
+        height,
+        tx: Binary::from(serialize(&tx)),
+        merkle_proof: MerkleProofMsg {
+            prefix: vec![true],
+            siblings: vec![tx.txid().to_string(), coinbase.txid().to_string()],
+        },
+        coinbase_merkle_proof: Some(MerkleProofMsg {
+            prefix: vec![false],
+            siblings: vec![coinbase.txid().to_string(), tx.txid().to_string()],
+        }),
+        coinbase_tx: Some(Binary::from(serialize(&coinbase))),
+    };
+    let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyAndDecodeTx {
+            txid,
+            outputs,
+            confirmations,
+            block_time,
+            block_reward,
+        } => {
+            assert_eq!(txid, tx.txid().to_string());
+            assert_eq!(
+                outputs,
+                vec![
+                    TxOutMsg {
+                        value: 1000,
+                        script_pubkey: Binary::from(address.script_pubkey().to_bytes()),
+                        address: Some(address.to_string()),
+                    },
+                    TxOutMsg {
+                        value: 0,
+                        script_pubkey: Binary::from(op_return.to_bytes()),
+                        address: None,
+                    },
+                ]
+            );
+            assert_eq!(confirmations, 6);
+            assert_eq!(block_time, contract_test_utils::mock_timestamp());
+            assert_eq!(
+                block_reward,
+                Some(BlockRewardMsg {
+                    subsidy: 50 * 100_000_000,
+                    coinbase_value: 50 * 100_000_000,
+                })
+            );
+        }
+        _ => unreachable!(),
+    }
+
+    // CASE: tx is not in the block
+    let msg = QueryMsg::VerifyAndDecodeTx {
+        height,
+        tx: Binary::from(serialize(&coinbase)),
+        merkle_proof: MerkleProofMsg {
+            prefix: vec![true],
+            siblings: vec![tx.txid().to_string(), coinbase.txid().to_string()],
+        },
+        coinbase_merkle_proof: None,
+        coinbase_tx: None,
+    };
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error merkle path and tx does not match")
+    );
+}
+
+#[test]
+fn test_verify_block_filter() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+    let mint_tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    let mut chain = RegtestChain::new();
+    chain.mine_block(vec![mint_tx]);
+    chain.mine_empty_blocks(5);
+    add_regtest_headers(&mut context, &chain, 1);
+    let filters: Vec<BlockFilter> = chain.blocks.iter().map(block_filter).collect();
+    let handle_msg = HandleMsg::AddFilterHeaders {
+        start_height: 0,
+        filter_hashes: filters
+            .iter()
+            .map(|filter| FilterHash::hash(&filter.content).to_string())
+            .collect(),
+        prev_filter_header: None,
+    };
+    let env = contract_test_utils::mock_env("relayer", &[]);
+    BitcoinSPVRunner::run_handle(&mut context, env, handle_msg).unwrap();
+
+    let genesis_filter_header = filters[0].filter_header(&FilterHeader::default());
+    let verify_msg =
+        |script: &Script, prev_filter_header: &FilterHeader| QueryMsg::VerifyBlockFilter {
+            height: 1,
+            filter: Binary::from(filters[1].content.clone()),
+            prev_filter_header: prev_filter_header.to_string(),
+            scripts: vec![Binary::from(script.to_bytes())],
+        };
+    // CASE: the block has an output to the address
+    let msg = verify_msg(&address.script_pubkey(), &genesis_filter_header);
+    let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyBlockFilter {
+            matched,
+            confirmations,
+        } => {
+            assert!(matched);
+            assert_eq!(confirmations, 6);
+        }
+        _ => unreachable!(),
+    }
+
+    // CASE: the block has no output to the other address
+    let other = Address::from_str("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn").unwrap();
+    let msg = verify_msg(&other.script_pubkey(), &genesis_filter_header);
+    let response = BitcoinSPVRunner::run_query(&mut context, msg).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyBlockFilter { matched, .. } => assert!(!matched),
+        _ => unreachable!(),
+    }
+
+    // CASE: the filter is not committed by the filter header
+    let msg = verify_msg(&address.script_pubkey(), &FilterHeader::default());
+    let err = BitcoinSPVRunner::run_query(&mut context, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error invalid filter header")
+    );
+}
+
+#[test]
+fn test_verify_and_decode_tx_with_coinbase_tx() {
+    let mut context = init_helper();
+    let address = Address::from_str("mqkhEMH6NCeYjFybv7pvFC22MFeaNT9AQC").unwrap();
+    let mint_tx = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 1000,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    let mut chain = RegtestChain::new();
+    chain.mine_block(vec![]);
+    chain.mine_block(vec![mint_tx.clone()]);
+    chain.mine_empty_blocks(6);
+    add_regtest_headers(&mut context, &chain, 1);
+    let msg =
+        |height: u32, coinbase_merkle_proof: Option<MerkleProofMsg>| QueryMsg::VerifyAndDecodeTx {
+            height,
+            tx: Binary::from(serialize(&mint_tx)),
+            merkle_proof: merkle_proof_msg(&chain.merkle_proof(2, 1)),
+            coinbase_merkle_proof,
+            coinbase_tx: Some(Binary::from(serialize(&chain.block_at(2).txdata[0]))),
+        };
+    let coinbase_merkle_proof = merkle_proof_msg(&chain.merkle_proof(2, 0));
+
+    let response =
+        BitcoinSPVRunner::run_query(&mut context, msg(2, Some(coinbase_merkle_proof.clone())))
+            .unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::VerifyAndDecodeTx { block_reward, .. } => assert_eq!(
+            block_reward,
+            Some(BlockRewardMsg {
+                subsidy: 50 * 100_000_000,
+                coinbase_value: 50 * 100_000_000,
+            })
+        ),
+        _ => unreachable!(),
+    }
+
+    // CASE: the height is not of the block
+    let err =
+        BitcoinSPVRunner::run_query(&mut context, msg(1, Some(coinbase_merkle_proof))).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("coinbase proof error coinbase tx and height does not match")
+    );
+
+    // CASE: no coinbase merkle proof
+    let err = BitcoinSPVRunner::run_query(&mut context, msg(2, None)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("contract error coinbase tx requires coinbase merkle proof")
+    );
+}
@@ -1,13 +1,20 @@
 use bitcoin::consensus::encode;
 use bitcoin::hashes;
-use bitcoin_header_chain::{header_chain, merkle_proof};
+use bitcoin::util::bip158;
+use bitcoin::util::merkleblock::MerkleBlockError;
+use bitcoin_header_chain::{coinbase_proof, header_chain, merkle_proof, witness_proof};
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
-    BitcoinEncode(encode::Error),
+    BitcoinEncode(Box<encode::Error>),
     HashHexError(hashes::hex::Error),
     HeaderChain(header_chain::Error),
     MerkleProof(merkle_proof::Error),
+    WitnessProof(witness_proof::Error),
+    CoinbaseProof(coinbase_proof::Error),
+    MerkleBlock(MerkleBlockError),
+    BlockFilter(bip158::Error),
     StdIoError(std::io::Error),
     Cosmwasm(cosmwasm_std::StdError),
     Contract(String),
@@ -20,6 +27,10 @@ impl std::fmt::Display for Error {
             Error::HashHexError(ref e) => write!(f, "hash hex error {}", e),
             Error::HeaderChain(ref e) => write!(f, "header chain error {}", e),
             Error::MerkleProof(ref e) => write!(f, "merkle path error {}", e),
+            Error::WitnessProof(ref e) => write!(f, "witness proof error {}", e),
+            Error::CoinbaseProof(ref e) => write!(f, "coinbase proof error {}", e),
+            Error::MerkleBlock(ref e) => write!(f, "merkle block error {:?}", e),
+            Error::BlockFilter(ref e) => write!(f, "block filter error {}", e),
             Error::StdIoError(ref e) => write!(f, "std io error {}", e),
             Error::Cosmwasm(ref e) => write!(f, "cosmwasm std error {}", e),
             Error::Contract(ref msg) => write!(f, "contract error {}", msg),
@@ -29,7 +40,7 @@ impl std::fmt::Display for Error {
 
 impl From<encode::Error> for Error {
     fn from(e: encode::Error) -> Error {
-        Error::BitcoinEncode(e)
+        Error::BitcoinEncode(Box::new(e))
     }
 }
 impl From<hashes::hex::Error> for Error {
@@ -47,6 +58,26 @@ impl From<merkle_proof::Error> for Error {
         Error::MerkleProof(e)
     }
 }
+impl From<witness_proof::Error> for Error {
+    fn from(e: witness_proof::Error) -> Error {
+        Error::WitnessProof(e)
+    }
+}
+impl From<coinbase_proof::Error> for Error {
+    fn from(e: coinbase_proof::Error) -> Error {
+        Error::CoinbaseProof(e)
+    }
+}
+impl From<MerkleBlockError> for Error {
+    fn from(e: MerkleBlockError) -> Error {
+        Error::MerkleBlock(e)
+    }
+}
+impl From<bip158::Error> for Error {
+    fn from(e: bip158::Error) -> Error {
+        Error::BlockFilter(e)
+    }
+}
 
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Error {
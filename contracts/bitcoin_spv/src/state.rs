@@ -1,3 +1,5 @@
 pub mod chaindb;
+pub mod compact_pow;
 pub mod config;
+pub mod filter_header;
 pub mod prefix;
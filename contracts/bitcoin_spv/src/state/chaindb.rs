@@ -1,24 +1,99 @@
 use super::prefix::PREFIX_CHAIN_DB;
-use bitcoin_header_chain::header_chain::chaindb::{ChainDB, ChainDBResult, ReadonlyChainDB};
-use bitcoin_header_chain::header_chain::StoredBlockHeader;
+use bitcoin_header_chain::header_chain::chaindb::{
+    ChainDB, ChainDBResult, ReadonlyChainDB, Timestamps,
+};
+use bitcoin_header_chain::header_chain::{BlockHash, StoredBlockHeader, UNTRUSTED_LENGTH};
 use cosmwasm_std::{ReadonlyStorage, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use secret_toolkit::serialization::Bincode2;
 use secret_toolkit::storage::Item;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryInto;
 
 /// StorageChainDB stores bitcoin chain db on Storage.
 /// It has cache to save storage reading gas cost.
+/// The timestamp window is kept in memory while storing headers
+/// and written to storage once per batch on `flush`.
 #[derive(Debug)]
 pub struct StorageChainDB<S: ReadonlyStorage> {
     pub storage: S,
     header_cache: HashMap<u32, StoredBlockHeader>,
     tip_height: Option<u32>,
+    oldest_height: Option<u32>,
+    timestamp_window: Option<TimestampWindow>,
+    timestamp_window_changed: bool,
+    epoch_start: Option<EpochStart>,
 }
 
 const TIP_HEIGHT_KEY: &[u8] = b"tip_hash";
+const OLDEST_HEIGHT_KEY: &[u8] = b"oldest_height";
 const PREFIX_HEADERS: &[u8] = b"headers";
+const PREFIX_HEIGHTS: &[u8] = b"heights";
+const TIMESTAMP_WINDOW_KEY: &[u8] = b"timestamp_window";
+const EPOCH_START_KEY: &[u8] = b"epoch_start";
+
+/// number of timestamps used to calculate median time past.
+const MPT_SPAN: usize = 11;
+/// capacity of the timestamp window.
+/// extra timestamps are kept so that the window survives replacing untrusted headers.
+const TIMESTAMP_WINDOW_CAPACITY: usize = MPT_SPAN + UNTRUSTED_LENGTH as usize;
+
+/// TimestampWindow is timestamps of the latest stored headers ordered by height.
+/// The last timestamp is of the header at `height`.
+/// If it has less than MPT_SPAN timestamps, it starts from the lowest stored header.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TimestampWindow {
+    height: u32,
+    timestamps: Vec<u32>,
+}
+
+impl TimestampWindow {
+    fn start_height(&self) -> u32 {
+        self.height + 1 - self.timestamps.len() as u32
+    }
+
+    /// returns timestamps of the headers below the height, if the window has enough of them.
+    fn timestamps_below(&self, height: u32) -> Option<Timestamps> {
+        if height == 0 || height - 1 > self.height || height < self.start_height() {
+            return None;
+        }
+        let end = self.timestamps.len() - (self.height - (height - 1)) as usize;
+        if end >= MPT_SPAN {
+            Some(Timestamps(self.timestamps[end - MPT_SPAN..end].to_vec()))
+        } else if self.timestamps.len() < MPT_SPAN {
+            Some(Timestamps(self.timestamps[..end].to_vec()))
+        } else {
+            None
+        }
+    }
+
+    /// appends timestamp of the header at the height.
+    /// returns false if the window can not be extended to the height.
+    fn store(&mut self, height: u32, timestamp: u32) -> bool {
+        if height > self.height + 1 || height < self.start_height() {
+            return false;
+        }
+        let truncated_len = self.timestamps.len() - (self.height + 1 - height) as usize;
+        if truncated_len < MPT_SPAN && self.timestamps.len() >= MPT_SPAN {
+            return false;
+        }
+        self.timestamps.truncate(truncated_len);
+        self.timestamps.push(timestamp);
+        if self.timestamps.len() > TIMESTAMP_WINDOW_CAPACITY {
+            self.timestamps.remove(0);
+        }
+        self.height = height;
+        true
+    }
+}
+
+/// EpochStart is timestamp of the latest stored header at difficulty adjustment height.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct EpochStart {
+    height: u32,
+    time: u32,
+}
 
 impl<S: ReadonlyStorage> StorageChainDB<S> {
     fn new(storage: S) -> Self {
@@ -26,7 +101,29 @@ impl<S: ReadonlyStorage> StorageChainDB<S> {
             storage,
             header_cache: HashMap::new(),
             tip_height: None,
+            oldest_height: None,
+            timestamp_window: None,
+            timestamp_window_changed: false,
+            epoch_start: None,
+        }
+    }
+
+    fn load_timestamp_window(&mut self) -> ChainDBResult<Option<TimestampWindow>> {
+        if self.timestamp_window.is_none() {
+            self.timestamp_window = Item::<TimestampWindow, Bincode2>::new(TIMESTAMP_WINDOW_KEY)
+                .may_load(&self.storage)
+                .map_err(|e| e.to_string())?;
         }
+        Ok(self.timestamp_window.clone())
+    }
+
+    fn load_epoch_start(&mut self) -> ChainDBResult<Option<EpochStart>> {
+        if self.epoch_start.is_none() {
+            self.epoch_start = Item::<EpochStart, Bincode2>::new(EPOCH_START_KEY)
+                .may_load(&self.storage)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(self.epoch_start.clone())
     }
 }
 
@@ -54,6 +151,15 @@ impl<S: ReadonlyStorage> ReadonlyChainDB for StorageChainDB<S> {
         }
         Ok(self.tip_height)
     }
+    fn oldest_height(&mut self) -> ChainDBResult<Option<u32>> {
+        if self.oldest_height.is_none() {
+            if let Some(bytes) = self.storage.get(OLDEST_HEIGHT_KEY) {
+                let height = u32::from_be_bytes(bytes.try_into().unwrap());
+                self.oldest_height = Some(height);
+            }
+        }
+        Ok(self.oldest_height)
+    }
     fn header_at(&mut self, height: u32) -> ChainDBResult<Option<StoredBlockHeader>> {
         if let Some(cached_header) = self.header_cache.get(&height) {
             return Ok(Some(cached_header.clone()));
@@ -69,20 +175,143 @@ impl<S: ReadonlyStorage> ReadonlyChainDB for StorageChainDB<S> {
             Ok(None)
         }
     }
+
+    fn height_of(&mut self, block_hash: &BlockHash) -> ChainDBResult<Option<u32>> {
+        let storage = ReadonlyPrefixedStorage::new(PREFIX_HEIGHTS, &self.storage);
+        Ok(storage
+            .get(block_hash.as_ref())
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap())))
+    }
+
+    /// reads median time past from the timestamp window.
+    /// falls back to reading headers if the window does not cover the height.
+    fn mpt_at(&mut self, height: u32) -> ChainDBResult<Option<u32>> {
+        if let Some(timestamps) = self
+            .load_timestamp_window()?
+            .and_then(|window| window.timestamps_below(height))
+        {
+            return Ok(timestamps.mid());
+        }
+        Ok(self.last_timestamps_at(height)?.mid())
+    }
+
+    /// reads epoch start time from the stored epoch start.
+    /// falls back to reading the header if the stored one is of another epoch.
+    fn epoch_start_time_at(&mut self, height: u32) -> ChainDBResult<Option<u32>> {
+        let epoch_height = height - height % 2016;
+        match self.load_epoch_start()? {
+            Some(epoch_start) if epoch_start.height == epoch_height => Ok(Some(epoch_start.time)),
+            _ => Ok(self
+                .header_at(epoch_height)?
+                .map(|stored_header| stored_header.header.time)),
+        }
+    }
+}
+
+impl<S: Storage> StorageChainDB<S> {
+    fn store_timestamp(&mut self, height: u32, timestamp: u32) -> ChainDBResult<()> {
+        let window = match self.load_timestamp_window()? {
+            Some(mut window) => {
+                if window.store(height, timestamp) {
+                    window
+                } else {
+                    self.rebuild_timestamp_window(height, timestamp)?
+                }
+            }
+            None => self.rebuild_timestamp_window(height, timestamp)?,
+        };
+        self.timestamp_window = Some(window);
+        self.timestamp_window_changed = true;
+        Ok(())
+    }
+
+    /// rebuilds the window from the stored headers.
+    fn rebuild_timestamp_window(
+        &mut self,
+        height: u32,
+        timestamp: u32,
+    ) -> ChainDBResult<TimestampWindow> {
+        let mut timestamps = Vec::with_capacity(TIMESTAMP_WINDOW_CAPACITY);
+        let start = height.saturating_sub(TIMESTAMP_WINDOW_CAPACITY as u32 - 1);
+        for h in (start..height).rev() {
+            match self.header_at(h)? {
+                Some(header) => timestamps.push(header.header.time),
+                None => break,
+            }
+        }
+        timestamps.reverse();
+        timestamps.push(timestamp);
+        Ok(TimestampWindow { height, timestamps })
+    }
+
+    fn store_epoch_start(&mut self, height: u32, time: u32) -> ChainDBResult<()> {
+        let epoch_start = EpochStart { height, time };
+        Item::<EpochStart, Bincode2>::new(EPOCH_START_KEY)
+            .save(&mut self.storage, &epoch_start)
+            .map_err(|e| e.to_string())?;
+        self.epoch_start = Some(epoch_start);
+        Ok(())
+    }
 }
 
 /// Writable ChainDB impls ChainDB trait.
 impl<S: Storage> ChainDB for StorageChainDB<S> {
     fn store_header(&mut self, height: u32, block_header: StoredBlockHeader) -> ChainDBResult<()> {
+        let time = block_header.header.time;
         let mut storage = PrefixedStorage::new(PREFIX_HEADERS, &mut self.storage);
         Item::<StoredBlockHeader, Bincode2>::new(&height.to_be_bytes())
             .save(&mut storage, &block_header)
             .map_err(|e| e.to_string())?;
+        let mut storage = PrefixedStorage::new(PREFIX_HEIGHTS, &mut self.storage);
+        storage.set(
+            block_header.header.block_hash().as_ref(),
+            &height.to_be_bytes(),
+        );
+        self.store_timestamp(height, time)?;
+        if height % 2016 == 0 {
+            self.store_epoch_start(height, time)?;
+        }
         self.header_cache.insert(height, block_header);
         if self.tip_height.unwrap_or_default() <= height {
             self.storage.set(TIP_HEIGHT_KEY, &height.to_be_bytes());
             self.tip_height = Some(height);
         }
+        if self.oldest_height()?.is_none() {
+            self.store_oldest_height(height)?;
+        }
+        Ok(())
+    }
+
+    fn remove_header(&mut self, height: u32) -> ChainDBResult<()> {
+        if let Some(stored_header) = self.header_at(height)? {
+            let block_hash = stored_header.header.block_hash();
+            if self.height_of(&block_hash)? == Some(height) {
+                let mut storage = PrefixedStorage::new(PREFIX_HEIGHTS, &mut self.storage);
+                storage.remove(block_hash.as_ref());
+            }
+        }
+        let mut storage = PrefixedStorage::new(PREFIX_HEADERS, &mut self.storage);
+        storage.remove(&height.to_be_bytes());
+        self.header_cache.remove(&height);
+        Ok(())
+    }
+
+    fn store_oldest_height(&mut self, height: u32) -> ChainDBResult<()> {
+        self.storage.set(OLDEST_HEIGHT_KEY, &height.to_be_bytes());
+        self.oldest_height = Some(height);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ChainDBResult<()> {
+        if !self.timestamp_window_changed {
+            return Ok(());
+        }
+        if let Some(window) = &self.timestamp_window {
+            Item::<TimestampWindow, Bincode2>::new(TIMESTAMP_WINDOW_KEY)
+                .save(&mut self.storage, window)
+                .map_err(|e| e.to_string())?;
+        }
+        self.timestamp_window_changed = false;
         Ok(())
     }
 }
@@ -283,4 +512,215 @@ mod test {
         // assert data cached
         assert_eq!(db.header_cache.get(&100).unwrap(), &header);
     }
+
+    #[test]
+    fn test_oldest_height() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        assert!(db.oldest_height().unwrap().is_none());
+
+        // assert the first stored height is the oldest height
+        db.store_header(100, block_header()).unwrap();
+        db.store_header(101, block_header()).unwrap();
+        assert_eq!(db.oldest_height().unwrap().unwrap(), 100);
+        assert_eq!(
+            db.storage.get(OLDEST_HEIGHT_KEY).unwrap(),
+            100u32.to_be_bytes()
+        );
+
+        // assert oldest height updated
+        db.store_oldest_height(101).unwrap();
+        assert_eq!(db.oldest_height.unwrap(), 101);
+        assert_eq!(
+            db.storage.get(OLDEST_HEIGHT_KEY).unwrap(),
+            101u32.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn test_remove_block_header() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        db.store_header(100, block_header()).unwrap();
+
+        db.remove_header(100).unwrap();
+        // assert header removed from cache
+        assert!(!db.header_cache.contains_key(&100));
+        // assert header removed from storage
+        assert!(db.header_at(100).unwrap().is_none());
+        let storage =
+            ReadonlyPrefixedStorage::multilevel(&[PREFIX_CHAIN_DB, PREFIX_HEADERS], &storage);
+        assert!(
+            Item::<StoredBlockHeader, Bincode2>::new(&100u32.to_be_bytes())
+                .may_load(&storage)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_height_of() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        let header = block_header();
+        let block_hash = header.header.block_hash();
+        assert!(db.height_of(&block_hash).unwrap().is_none());
+
+        // assert height stored with block hash
+        db.store_header(100, header.clone()).unwrap();
+        assert_eq!(db.height_of(&block_hash).unwrap().unwrap(), 100);
+
+        // assert height removed with header
+        db.remove_header(100).unwrap();
+        assert!(db.height_of(&block_hash).unwrap().is_none());
+    }
+
+    fn header_with_time(time: u32) -> StoredBlockHeader {
+        let mut header = block_header();
+        header.header.time = time;
+        header
+    }
+
+    #[test]
+    fn test_timestamp_window_from_base() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        for height in 2016..2016 + 5 {
+            db.store_header(height, header_with_time(height * 10))
+                .unwrap();
+        }
+        // assert window starts from the lowest stored header
+        let window = db.timestamp_window.clone().unwrap();
+        assert_eq!(window.height, 2020);
+        assert_eq!(window.timestamps, vec![20160, 20170, 20180, 20190, 20200]);
+        // assert window is not stored until flushed
+        assert!(Item::<TimestampWindow, Bincode2>::new(TIMESTAMP_WINDOW_KEY)
+            .may_load(&db.storage)
+            .unwrap()
+            .is_none());
+        db.flush().unwrap();
+        assert!(!db.timestamp_window_changed);
+        // assert window stored to storage
+        assert_eq!(
+            Item::<TimestampWindow, Bincode2>::new(TIMESTAMP_WINDOW_KEY)
+                .load(&db.storage)
+                .unwrap(),
+            window
+        );
+        // assert mpt is same as the one calculated from headers
+        for height in 2016..2016 + 6 {
+            assert_eq!(
+                db.mpt_at(height).unwrap(),
+                db.last_timestamps_at(height).unwrap().mid()
+            );
+        }
+    }
+
+    #[test]
+    fn test_timestamp_window_rolling() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        for height in 0..40 {
+            db.store_header(height, header_with_time(1000 + (height * 7919) % 100))
+                .unwrap();
+        }
+        let window = db.timestamp_window.clone().unwrap();
+        assert_eq!(window.height, 39);
+        assert_eq!(window.timestamps.len(), TIMESTAMP_WINDOW_CAPACITY);
+        for height in window.start_height() + MPT_SPAN as u32..41 {
+            assert_eq!(
+                window.timestamps_below(height).unwrap().mid(),
+                db.last_timestamps_at(height).unwrap().mid()
+            );
+        }
+        // assert window does not cover heights which need older timestamps
+        assert!(window
+            .timestamps_below(window.start_height() + MPT_SPAN as u32 - 1)
+            .is_none());
+        assert!(window.timestamps_below(41).is_none());
+    }
+
+    #[test]
+    fn test_timestamp_window_replace_headers() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        for height in 0..30 {
+            db.store_header(height, header_with_time(1000 + height))
+                .unwrap();
+        }
+        // replace untrusted headers
+        for height in 30 - UNTRUSTED_LENGTH..32 {
+            db.store_header(height, header_with_time(2000 + height))
+                .unwrap();
+        }
+        let window = db.timestamp_window.clone().unwrap();
+        assert_eq!(window.height, 31);
+        assert_eq!(window.timestamps.len(), TIMESTAMP_WINDOW_CAPACITY);
+        for height in window.start_height() + MPT_SPAN as u32..33 {
+            assert_eq!(
+                db.mpt_at(height).unwrap(),
+                db.last_timestamps_at(height).unwrap().mid()
+            );
+        }
+    }
+
+    #[test]
+    fn test_timestamp_window_rebuild() {
+        let mut mock_storage = MockStorage::new();
+        // store headers without window manually
+        let mut storage =
+            PrefixedStorage::multilevel(&[PREFIX_CHAIN_DB, PREFIX_HEADERS], &mut mock_storage);
+        for height in 0..30u32 {
+            Item::<StoredBlockHeader, Bincode2>::new(&height.to_be_bytes())
+                .save(&mut storage, &header_with_time(1000 + height))
+                .unwrap();
+        }
+        let mut db = StorageChainDB::from_storage(&mut mock_storage);
+        db.storage.set(TIP_HEIGHT_KEY, &29u32.to_be_bytes());
+
+        // assert mpt falls back to reading headers
+        assert!(db.load_timestamp_window().unwrap().is_none());
+        assert_eq!(db.mpt_at(30).unwrap().unwrap(), 1024);
+
+        // assert window is rebuilt from stored headers
+        db.store_header(30, header_with_time(1030)).unwrap();
+        let window = db.timestamp_window.clone().unwrap();
+        assert_eq!(window.height, 30);
+        assert_eq!(
+            window.timestamps,
+            (30 - TIMESTAMP_WINDOW_CAPACITY as u32 + 1..=30)
+                .map(|height| 1000 + height)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(db.mpt_at(31).unwrap().unwrap(), 1025);
+    }
+
+    #[test]
+    fn test_epoch_start() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageChainDB::from_storage(&mut storage);
+        db.store_header(2016, header_with_time(100)).unwrap();
+        db.store_header(2017, header_with_time(200)).unwrap();
+
+        // assert epoch start stored
+        let epoch_start = EpochStart {
+            height: 2016,
+            time: 100,
+        };
+        assert_eq!(db.epoch_start.clone().unwrap(), epoch_start);
+        assert_eq!(
+            Item::<EpochStart, Bincode2>::new(EPOCH_START_KEY)
+                .load(&db.storage)
+                .unwrap(),
+            epoch_start
+        );
+        assert_eq!(db.epoch_start_time_at(2016).unwrap().unwrap(), 100);
+        assert_eq!(db.epoch_start_time_at(4031).unwrap().unwrap(), 100);
+
+        // assert falls back to reading header of another epoch
+        assert!(db.epoch_start_time_at(4032).unwrap().is_none());
+        db.store_header(4032, header_with_time(300)).unwrap();
+        assert_eq!(db.epoch_start_time_at(4032).unwrap().unwrap(), 300);
+        assert_eq!(db.epoch_start_time_at(2017).unwrap().unwrap(), 100);
+    }
 }
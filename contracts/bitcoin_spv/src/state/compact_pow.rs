@@ -0,0 +1,53 @@
+use super::prefix::{LAST_COMPACT_POW_COMMIT_KEY, PREFIX_COMPACT_POW};
+use bitcoin::hash_types::{BlockHash, TxMerkleNode};
+use bitcoin_header_chain::compact_pow::Seed;
+use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdResult, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use secret_toolkit::serialization::Bincode2;
+use secret_toolkit::storage::Item;
+use serde::{Deserialize, Serialize};
+
+/// commitment of compact proof of work waiting for the samples.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompactPowCommitment {
+    pub height: u32,
+    pub commitment: TxMerkleNode,
+    pub leaf_count: u32,
+    /// tip of the chain at the commitment. the samples are connected to it.
+    pub prev_hash: BlockHash,
+    /// drawn from the prng after the commitment is fixed.
+    pub seed: Seed,
+}
+
+/// reads the commitment of the prover. each prover has at most one commitment,
+/// and it is never replaced until the proof is accepted.
+pub fn read_compact_pow_commitment<S: ReadonlyStorage>(
+    storage: &S,
+    prover: &CanonicalAddr,
+) -> StdResult<Option<CompactPowCommitment>> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_COMPACT_POW, storage);
+    Item::<CompactPowCommitment, Bincode2>::new(prover.as_slice()).may_load(&storage)
+}
+
+pub fn write_compact_pow_commitment<S: Storage>(
+    storage: &mut S,
+    prover: &CanonicalAddr,
+    commitment: &CompactPowCommitment,
+) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_COMPACT_POW, storage);
+    Item::<CompactPowCommitment, Bincode2>::new(prover.as_slice()).save(&mut storage, commitment)
+}
+
+pub fn remove_compact_pow_commitment<S: Storage>(storage: &mut S, prover: &CanonicalAddr) {
+    let mut storage = PrefixedStorage::new(PREFIX_COMPACT_POW, storage);
+    Item::<CompactPowCommitment, Bincode2>::new(prover.as_slice()).remove(&mut storage)
+}
+
+/// reads the block height of the last commitment of any provers.
+pub fn read_last_commit_height<S: ReadonlyStorage>(storage: &S) -> StdResult<Option<u64>> {
+    Item::<u64, Bincode2>::new(LAST_COMPACT_POW_COMMIT_KEY).may_load(storage)
+}
+
+pub fn write_last_commit_height<S: Storage>(storage: &mut S, height: u64) -> StdResult<()> {
+    Item::<u64, Bincode2>::new(LAST_COMPACT_POW_COMMIT_KEY).save(storage, &height)
+}
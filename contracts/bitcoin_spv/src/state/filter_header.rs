@@ -0,0 +1,25 @@
+use super::prefix::PREFIX_FILTER_HEADERS;
+use bitcoin::hash_types::{BlockHash, FilterHeader};
+use cosmwasm_std::{ReadonlyStorage, StdResult, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use secret_toolkit::serialization::Bincode2;
+use secret_toolkit::storage::Item;
+
+/// reads BIP157 filter header of the block.
+/// filter headers are keyed by block hash, so that ones of reorged blocks are never read.
+pub fn read_filter_header<S: ReadonlyStorage>(
+    storage: &S,
+    block_hash: &BlockHash,
+) -> StdResult<Option<FilterHeader>> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_FILTER_HEADERS, storage);
+    Item::<FilterHeader, Bincode2>::new(&block_hash[..]).may_load(&storage)
+}
+
+pub fn write_filter_header<S: Storage>(
+    storage: &mut S,
+    block_hash: &BlockHash,
+    filter_header: &FilterHeader,
+) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_FILTER_HEADERS, storage);
+    Item::<FilterHeader, Bincode2>::new(&block_hash[..]).save(&mut storage, filter_header)
+}
@@ -1,2 +1,5 @@
 pub const PREFIX_CHAIN_DB: &[u8] = b"chaindb";
 pub const CONFIG_KEY: &[u8] = b"config";
+pub const PREFIX_FILTER_HEADERS: &[u8] = b"filter_headers";
+pub const PREFIX_COMPACT_POW: &[u8] = b"compact_pow";
+pub const LAST_COMPACT_POW_COMMIT_KEY: &[u8] = b"last_compact_pow_commit";
@@ -59,20 +59,30 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             height,
             tx,
             merkle_proof,
-            ..
+            coinbase_tx,
+            coinbase_merkle_proof,
         } => {
             if suspension_switch.verify_mint_tx {
                 return Err(Error::contract_err("verify mint tx is being suspended").into());
             }
-            try_verify_mint_tx(&mut deps, env, height, tx, merkle_proof)
+            try_verify_mint_tx(
+                &mut deps,
+                env,
+                height,
+                tx,
+                merkle_proof,
+                coinbase_tx,
+                coinbase_merkle_proof,
+            )
         }
         HandleMsg::ReleaseIncorrectAmountBTC {
             height,
             tx,
             merkle_proof,
+            coinbase_tx,
+            coinbase_merkle_proof,
             recipient_address,
             fee_per_vb,
-            ..
         } => {
             if suspension_switch.release_incorrect_amount_btc {
                 return Err(
@@ -85,6 +95,8 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                 height,
                 tx,
                 merkle_proof,
+                coinbase_tx,
+                coinbase_merkle_proof,
                 recipient_address,
                 fee_per_vb,
             )
@@ -248,6 +260,8 @@ fn try_verify_mint_tx<A: Api, Q: Querier>(
     height: u32,
     tx: Binary,
     merkle_proof: bitcoin_spv::MerkleProofMsg,
+    coinbase_tx: Binary,
+    coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg,
 ) -> Result<HandleResponse, Error> {
     let mintor = deps.api.canonical_address(&env.message.sender)?;
     let config = read_config(&deps.storage, &deps.api)?;
@@ -260,6 +274,8 @@ fn try_verify_mint_tx<A: Api, Q: Querier>(
             height,
             tx: tx.clone(),
             merkle_proof,
+            coinbase_merkle_proof: Some(coinbase_merkle_proof),
+            coinbase_tx: Some(coinbase_tx),
         })
         .query(
             deps.querier,
@@ -341,12 +357,15 @@ fn try_verify_mint_tx<A: Api, Q: Querier>(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 fn try_release_incorrect_amount_btc<A: Api, Q: Querier>(
     deps: &mut StateProxyDeps<A, Q>,
     env: Env,
     height: u32,
     tx: Binary,
     merkle_proof: bitcoin_spv::MerkleProofMsg,
+    coinbase_tx: Binary,
+    coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg,
     recipient_address: String,
     fee_per_vb: u64,
 ) -> Result<HandleResponse, Error> {
@@ -361,6 +380,8 @@ fn try_release_incorrect_amount_btc<A: Api, Q: Querier>(
             height,
             tx: tx.clone(),
             merkle_proof,
+            coinbase_merkle_proof: Some(coinbase_merkle_proof),
+            coinbase_tx: Some(coinbase_tx),
         })
         .query(
             deps.querier,
@@ -513,6 +534,7 @@ fn try_claim_released_btc<A: Api, Q: Querier>(
         headers,
         block_hash_index,
         encryption_key,
+        0,
     )? {
         HandleAnswer::RequestReleaseBtc { request_key } => request_key,
         _ => return Err(Error::contract_err("failed to deserialize decrypted text")),
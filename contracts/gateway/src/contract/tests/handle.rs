@@ -259,6 +259,8 @@ fn test_verify_mint_tx_sanity() {
                     height: 1,
                     tx: bin_mint_tx,
                     merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                    coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                    coinbase_tx: Some(Binary::default()),
                 })
                 .unwrap(),
                 contract_addr: config.bitcoin_spv.address,
@@ -272,6 +274,8 @@ fn test_verify_mint_tx_sanity() {
             height: 1,
             tx: Binary::from(serialize(&mint_tx)),
             merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+            coinbase_tx: Binary::default(),
+            coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
         };
         let handle_response = GatewayRunner::run_handle(
             &mut context,
@@ -350,6 +354,8 @@ fn test_suspend_verify_mint_tx() {
         height: 0,
         tx: Binary::from(&[]),
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
     };
     GatewayRunner::run_handle(
         &mut context,
@@ -431,6 +437,8 @@ fn test_verify_mint_tx_merkle_proof_verification_failure() {
                 height: 1,
                 tx: bin_mint_tx.clone(),
                 merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                coinbase_tx: Some(Binary::default()),
             })
             .unwrap(),
             contract_addr: config.bitcoin_spv.address.clone(),
@@ -445,6 +453,8 @@ fn test_verify_mint_tx_merkle_proof_verification_failure() {
                 height: 2,
                 tx: bin_mint_tx.clone(),
                 merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                coinbase_tx: Some(Binary::default()),
             })
             .unwrap(),
             contract_addr: config.bitcoin_spv.address,
@@ -463,6 +473,8 @@ fn test_verify_mint_tx_merkle_proof_verification_failure() {
         height: 1,
         tx: bin_mint_tx.clone(),
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
     };
     let err = GatewayRunner::run_handle(
         &mut context,
@@ -480,6 +492,8 @@ fn test_verify_mint_tx_merkle_proof_verification_failure() {
         height: 2,
         tx: bin_mint_tx,
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
     };
     let err = GatewayRunner::run_handle(
         &mut context,
@@ -541,6 +555,8 @@ fn test_verify_mint_tx_no_output() {
                 height: 1,
                 tx: bin_mint_tx.clone(),
                 merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                coinbase_tx: Some(Binary::default()),
             })
             .unwrap(),
             contract_addr: config.bitcoin_spv.address,
@@ -553,6 +569,8 @@ fn test_verify_mint_tx_no_output() {
         height: 1,
         tx: bin_mint_tx,
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
     };
     let err = GatewayRunner::run_handle(
         &mut context,
@@ -628,6 +646,8 @@ fn test_verify_mint_tx_invalid_mint_address() {
                 height: 1,
                 tx: bin_mint_tx.clone(),
                 merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                coinbase_tx: Some(Binary::default()),
             })
             .unwrap(),
             contract_addr: config.bitcoin_spv.address,
@@ -640,6 +660,8 @@ fn test_verify_mint_tx_invalid_mint_address() {
         height: 1,
         tx: bin_mint_tx,
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
     };
     let err = GatewayRunner::run_handle(
         &mut context,
@@ -714,6 +736,8 @@ fn test_verify_mint_tx_invalid_tx_value() {
                 height: 1,
                 tx: bin_mint_tx.clone(),
                 merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                coinbase_tx: Some(Binary::default()),
             })
             .unwrap(),
             contract_addr: config.bitcoin_spv.address,
@@ -726,6 +750,8 @@ fn test_verify_mint_tx_invalid_tx_value() {
         height: 1,
         tx: bin_mint_tx,
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
     };
     let err = GatewayRunner::run_handle(
         &mut context,
@@ -793,6 +819,8 @@ fn test_release_incorrect_amount_btc() {
                 height: 1,
                 tx: bin_mint_tx.clone(),
                 merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+                coinbase_merkle_proof: Some(bitcoin_spv::MerkleProofMsg::default()),
+                coinbase_tx: Some(Binary::default()),
             })
             .unwrap(),
             contract_addr: config.bitcoin_spv.address,
@@ -814,6 +842,8 @@ fn test_release_incorrect_amount_btc() {
         height: 1,
         tx: bin_mint_tx.clone(),
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
         recipient_address: recipient_address.to_string(),
         fee_per_vb: 200,
     };
@@ -865,6 +895,8 @@ fn test_suspend_release_incorrect_amount_btc() {
         height: 0,
         tx: Binary::from(&[]),
         merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
+        coinbase_tx: Binary::default(),
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg::default(),
         recipient_address: String::default(),
         fee_per_vb: 0,
     };
@@ -1129,6 +1161,7 @@ fn test_claim_release_btc_sanity() {
                 headers: vec![],
                 block_hash_index: 1,
                 encryption_key: Binary::from(b"encryption_key"),
+                msg_index: 0,
             })
             .unwrap(),
             contract_addr: config.sfps.address,
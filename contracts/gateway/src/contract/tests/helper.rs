@@ -30,10 +30,15 @@ pub fn init_helper() -> Context {
         bitcoin_spv::QueryAnswer::Config(bitcoin_spv::Config {
             bitcoin_network: "regtest".into(),
             confirmation: 6,
+            retained_header_length: None,
+            signet_challenge: None,
+            compact_pow: None,
             state_proxy: ContractReference {
                 address: STATE_PROXY_CONTRACT_ADDRESS.into(),
                 hash: STATE_PROXY_CONTRACT_HASH.into(),
             },
+            owner: "owner".into(),
+            filter_header_relayers: vec![],
         }),
     )]);
 
@@ -0,0 +1,118 @@
+//! Validates raw 80-byte block headers in a file with the header chain.
+//!
+//! usage: verify_headers <network> <headers file> [start height] [--db <chaindb file>]
+//!
+//! The first header in the file is at the start height, which must be a difficulty adjustment height.
+//! It is trusted as the initial header, or as the genesis if the start height is 0.
+//! With `--db`, the chain db is kept in the file, and the headers continue from its tip.
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::{BlockHeader, Network};
+use bitcoin_header_chain::header_chain::chaindb::file::FileChainDB;
+use bitcoin_header_chain::header_chain::chaindb::memory::MemoryChainDB;
+use bitcoin_header_chain::header_chain::chaindb::ChainDB;
+use bitcoin_header_chain::header_chain::HeaderChain;
+use std::process::exit;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HEADER_SIZE: usize = 80;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Err(e) = run(&args[1..]) {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut positional = vec![];
+    let mut db_path = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--db" {
+            db_path = Some(iter.next().ok_or("--db needs a file path")?);
+        } else {
+            positional.push(arg);
+        }
+    }
+    if positional.len() < 2 {
+        return Err(
+            "usage: verify_headers <network> <headers file> [start height] [--db <chaindb file>]"
+                .into(),
+        );
+    }
+    let network = Network::from_str(positional[0]).map_err(|e| e.to_string())?;
+    let bytes = std::fs::read(positional[1]).map_err(|e| e.to_string())?;
+    if bytes.len() % HEADER_SIZE != 0 {
+        return Err(format!(
+            "file size is not a multiple of {} bytes",
+            HEADER_SIZE
+        ));
+    }
+    let headers = bytes
+        .chunks(HEADER_SIZE)
+        .map(deserialize::<BlockHeader>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let start_height = match positional.get(2) {
+        Some(height) => height.parse::<u32>().map_err(|e| e.to_string())?,
+        None => 0,
+    };
+    match db_path {
+        Some(path) => verify(
+            HeaderChain::new(FileChainDB::open(path)?, network),
+            start_height,
+            headers,
+        ),
+        None => verify(
+            HeaderChain::new(MemoryChainDB::new(), network),
+            start_height,
+            headers,
+        ),
+    }
+}
+
+fn verify<C: ChainDB>(
+    mut header_chain: HeaderChain<C>,
+    start_height: u32,
+    headers: Vec<BlockHeader>,
+) -> Result<(), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs() as u32;
+    let mut headers = headers.into_iter();
+    let mut height = match header_chain.tip_height().map_err(|e| e.to_string())? {
+        Some(tip_height) => tip_height,
+        None => {
+            let header = headers.next().ok_or("no headers")?;
+            header_chain
+                .init_to_header(start_height, header, now)
+                .map_err(|e| format!("invalid header at height {}: {}", start_height, e))?;
+            start_height
+        }
+    };
+    let mut result = Ok(());
+    for header in headers {
+        height += 1;
+        if let Err(e) = header_chain.store_headers(height, vec![header], now) {
+            result = Err(format!("invalid header at height {}: {}", height, e));
+            break;
+        }
+    }
+    let tip = header_chain
+        .tip()
+        .map_err(|e| e.to_string())?
+        .ok_or("no tip")?;
+    println!(
+        "tip height: {}",
+        header_chain
+            .tip_height()
+            .map_err(|e| e.to_string())?
+            .unwrap()
+    );
+    println!("tip hash: {}", tip.header.block_hash());
+    println!("total work: {}", tip.work);
+    result
+}
@@ -0,0 +1,174 @@
+use crate::merkle_proof::{self, MerkleProof};
+use bitcoin::blockdata::script::Builder;
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::{Network, Transaction};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Merkle proof error
+    MerkleProof(merkle_proof::Error),
+    /// coinbase tx is not coinbase
+    NotCoinbaseTx,
+    /// coinbase proof is not the proof of the coinbase tx
+    NotCoinbaseProof,
+    /// BIP34 is not active at the height
+    Bip34NotActive,
+    /// coinbase script sig does not start with the height
+    UnmatchedHeight,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MerkleProof(ref e) => write!(f, "merkle path error {}", e),
+            Error::NotCoinbaseTx => write!(f, "not coinbase tx"),
+            Error::NotCoinbaseProof => write!(f, "not coinbase proof"),
+            Error::Bip34NotActive => write!(f, "bip34 is not active"),
+            Error::UnmatchedHeight => write!(f, "coinbase tx and height does not match"),
+        }
+    }
+}
+
+impl From<merkle_proof::Error> for Error {
+    fn from(e: merkle_proof::Error) -> Error {
+        Error::MerkleProof(e)
+    }
+}
+
+/// proof binding a block to its height with the coinbase tx, following BIP34.
+///
+/// The coinbase tx is proven by txid against the merkle root of the block header,
+/// and its script sig must start with the height of the block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinbaseProof {
+    pub coinbase_tx: Transaction,
+    pub coinbase_proof: MerkleProof,
+}
+
+impl CoinbaseProof {
+    /// verifies that the coinbase tx commits to the height,
+    /// and returns the merkle root of the block which the coinbase tx belongs to.
+    ///
+    /// The returned merkle root must be checked against the block header by the caller.
+    pub fn verify(&self, height: u32, network: Network) -> Result<TxMerkleNode, Error> {
+        if height < bip34_height(network) {
+            return Err(Error::Bip34NotActive);
+        }
+        if !self.coinbase_tx.is_coin_base() {
+            return Err(Error::NotCoinbaseTx);
+        }
+        merkle_proof::validate_tx_length(&self.coinbase_tx)?;
+        if !self.coinbase_proof.is_coinbase_proof()
+            || self.coinbase_proof.leaf()?.as_hash() != self.coinbase_tx.txid().as_hash()
+        {
+            return Err(Error::NotCoinbaseProof);
+        }
+        // same as the check of bitcoind: the script sig starts with `CScript() << height`.
+        let expected = Builder::new().push_int(height as i64).into_script();
+        if !self.coinbase_tx.input[0]
+            .script_sig
+            .as_bytes()
+            .starts_with(expected.as_bytes())
+        {
+            return Err(Error::UnmatchedHeight);
+        }
+        Ok(self.coinbase_proof.merkle_root()?)
+    }
+
+    /// total value of the coinbase outputs, which is the subsidy and the fees claimed by the miner.
+    pub fn coinbase_value(&self) -> u64 {
+        self.coinbase_tx
+            .output
+            .iter()
+            .map(|output| output.value)
+            .sum()
+    }
+}
+
+/// height from which coinbase txs commit to the height.
+pub fn bip34_height(network: Network) -> u32 {
+    match network {
+        Network::Bitcoin => 227931,
+        Network::Testnet => 21111,
+        _ => 1,
+    }
+}
+
+/// block subsidy in satoshi at the height.
+pub fn block_subsidy(height: u32, network: Network) -> u64 {
+    let halving_interval = match network {
+        Network::Regtest => 150,
+        _ => 210000,
+    };
+    let halvings = height / halving_interval;
+    if halvings >= 64 {
+        return 0;
+    }
+    (50 * 100_000_000u64) >> halvings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::regtest::{self, RegtestChain};
+
+    #[test]
+    fn test_verify() {
+        let mut chain = RegtestChain::new();
+        chain.mine_empty_blocks(20);
+        for height in 1..=20 {
+            let proof = CoinbaseProof {
+                coinbase_tx: chain.block_at(height).txdata[0].clone(),
+                coinbase_proof: chain.merkle_proof(height, 0),
+            };
+            assert_eq!(
+                proof.verify(height, Network::Regtest).unwrap(),
+                chain.header_at(height).merkle_root
+            );
+            assert!(matches!(
+                proof.verify(height + 1, Network::Regtest),
+                Err(Error::UnmatchedHeight)
+            ));
+            assert_eq!(proof.coinbase_value(), regtest::SUBSIDY);
+        }
+    }
+
+    #[test]
+    fn test_verify_not_coinbase() {
+        let mut chain = RegtestChain::new();
+        let tx = regtest::coinbase_tx(1, 1);
+        let mut not_coinbase = tx.clone();
+        not_coinbase.input[0].previous_output.vout = 0;
+        chain.mine_block(vec![not_coinbase.clone()]);
+        let proof = CoinbaseProof {
+            coinbase_tx: not_coinbase,
+            coinbase_proof: chain.merkle_proof(1, 1),
+        };
+        assert!(matches!(
+            proof.verify(1, Network::Regtest),
+            Err(Error::NotCoinbaseTx)
+        ));
+        let proof = CoinbaseProof {
+            coinbase_tx: tx,
+            coinbase_proof: chain.merkle_proof(1, 0),
+        };
+        assert!(matches!(
+            proof.verify(1, Network::Regtest),
+            Err(Error::NotCoinbaseProof)
+        ));
+        assert!(matches!(
+            proof.verify(1, Network::Bitcoin),
+            Err(Error::Bip34NotActive)
+        ));
+    }
+
+    #[test]
+    fn test_block_subsidy() {
+        assert_eq!(block_subsidy(0, Network::Bitcoin), 5_000_000_000);
+        assert_eq!(block_subsidy(210000, Network::Bitcoin), 2_500_000_000);
+        assert_eq!(block_subsidy(840000, Network::Bitcoin), 312_500_000);
+        assert_eq!(block_subsidy(150, Network::Regtest), 2_500_000_000);
+        assert_eq!(block_subsidy(64 * 210000, Network::Bitcoin), 0);
+    }
+}
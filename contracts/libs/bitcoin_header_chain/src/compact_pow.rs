@@ -0,0 +1,464 @@
+//! Compact proof of work to bootstrap the header chain far ahead of the tip,
+//! in the manner of FlyClient.
+//!
+//! Bitcoin headers do not commit to an MMR of the previous blocks.
+//! Instead, the prover commits to the skipped headers with a merkle root of their block hashes,
+//! then the verifier draws a random seed, and the headers to be sampled are derived from the seed.
+//! Each sampled header must have valid proof of work under the target floor,
+//! and must be connected to the previous leaf of the commitment.
+//!
+//! The seed must be unknown to the prover until the commitment is fixed.
+//! The commitment is not bound to any header, so a seed derived from the commitment
+//! can be re-rolled at the cost of a merkle root,
+//! and a seed derived from the tip header alone lets the prover build the commitment around the samples.
+//!
+//! Trade-offs against submitting all the headers:
+//! - the work is probabilistic. let f be the fraction of the leaves which are headers
+//!   with valid proof of work connected to the previous leaf.
+//!   a commitment passes a seed with probability at most f^sample_count,
+//!   and a prover who can draw n seeds for its commitments passes with probability at most n * f^sample_count.
+//!   the sample count is the security parameter, and drawing a seed must be costly for the prover.
+//! - the target floor is the only bound of the sampled targets,
+//!   retargets and timestamps of the skipped headers are not validated.
+//! - the cumulative work of the skipped headers is estimated from the sampled headers.
+use crate::header_chain::{is_valid_proof_of_work_hash, BlockHash};
+use crate::merkle_proof::{self, MerkleProof};
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::hashes::{sha256d, Hash, HashEngine};
+use bitcoin::util::uint::Uint256;
+use bitcoin::BlockHeader;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+/// random seed of the samples.
+pub type Seed = [u8; 32];
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// malformed merkle proof
+    InvalidMerkleProof,
+    /// no header to commit
+    NoHeaders,
+    /// sample count must not be zero
+    NoSamples,
+    /// number of the sampled headers is different from the sample count
+    UnmatchedSampleCount,
+    /// merkle proof is not for the sampled leaf
+    UnmatchedLeafIndex,
+    /// merkle proof is not against the commitment
+    UnmatchedCommitment,
+    /// leaf of merkle proof is not the hash of the sampled header
+    UnmatchedBlockHash,
+    /// sampled header is not connected to the previous leaf
+    UnconnectedHeader,
+    /// target of sampled header is easier than the target floor.
+    /// proof of work under the floor is too cheap to be sampled.
+    TargetAboveFloor,
+    /// bad proof of work of sampled header
+    BadProofOfWork,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidMerkleProof => write!(f, "invalid merkle path"),
+            Error::NoHeaders => write!(f, "no headers"),
+            Error::NoSamples => write!(f, "no samples"),
+            Error::UnmatchedSampleCount => write!(f, "unmatched sample count"),
+            Error::UnmatchedLeafIndex => write!(f, "merkle path is not for the sampled leaf"),
+            Error::UnmatchedCommitment => write!(f, "merkle path and commitment does not match"),
+            Error::UnmatchedBlockHash => write!(f, "merkle path and header does not match"),
+            Error::UnconnectedHeader => write!(f, "unconnected header"),
+            Error::TargetAboveFloor => write!(f, "target is above the floor"),
+            Error::BadProofOfWork => write!(f, "bad proof of work"),
+        }
+    }
+}
+
+impl From<merkle_proof::Error> for Error {
+    fn from(_: merkle_proof::Error) -> Error {
+        Error::InvalidMerkleProof
+    }
+}
+
+/// header sampled from the commitment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SampledHeader {
+    pub header: BlockHeader,
+    /// merkle proof of the block hash of the header.
+    pub proof: MerkleProof,
+    /// merkle proof of the previous leaf.
+    /// None for the first leaf, which is connected to the tip of the header chain.
+    pub prev_proof: Option<MerkleProof>,
+}
+
+/// compact proof of the headers from the next of the tip.
+/// the first leaf is always sampled to connect to the tip,
+/// and the last leaf is always sampled as the new tip.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompactPowProof {
+    /// merkle root of the block hashes of the skipped headers.
+    pub commitment: TxMerkleNode,
+    pub samples: Vec<SampledHeader>,
+}
+
+/// commits to the headers with the merkle root of their block hashes.
+pub fn commit(headers: &[BlockHeader]) -> Result<TxMerkleNode, Error> {
+    Ok(header_tree(headers)?.last().unwrap()[0])
+}
+
+impl CompactPowProof {
+    /// builds proof of the headers following the tip,
+    /// with the sample indexes derived from the seed drawn by the verifier after the commitment.
+    pub fn build(headers: &[BlockHeader], indexes: &[u32]) -> Result<Self, Error> {
+        let tree = header_tree(headers)?;
+        let commitment = tree.last().unwrap()[0];
+        if indexes.iter().any(|index| *index as usize >= headers.len()) {
+            return Err(Error::UnmatchedLeafIndex);
+        }
+        let samples = indexes
+            .iter()
+            .map(|index| SampledHeader {
+                header: headers[*index as usize],
+                proof: merkle_proof(&tree, *index),
+                prev_proof: match index {
+                    0 => None,
+                    _ => Some(merkle_proof(&tree, index - 1)),
+                },
+            })
+            .collect();
+        Ok(Self {
+            commitment,
+            samples,
+        })
+    }
+
+    /// the new tip header.
+    pub fn tip(&self) -> Option<&BlockHeader> {
+        self.samples.last().map(|sample| &sample.header)
+    }
+
+    /// verifies the sampled headers of `leaf_count` headers following the block of `prev_hash`.
+    /// the seed must have been drawn after the commitment, see the module doc.
+    /// returns the estimated work of all the committed headers.
+    pub fn verify(
+        &self,
+        prev_hash: &BlockHash,
+        leaf_count: u32,
+        seed: &Seed,
+        sample_count: u32,
+        max_target: &Uint256,
+    ) -> Result<Uint256, Error> {
+        let indexes = sample_indexes(seed, leaf_count, sample_count)?;
+        if self.samples.len() != indexes.len() {
+            return Err(Error::UnmatchedSampleCount);
+        }
+        let depth = tree_depth(leaf_count);
+        let mut sampled_work = Uint256::default();
+        for (i, (index, sample)) in indexes.iter().zip(self.samples.iter()).enumerate() {
+            self.verify_leaf(&sample.proof, *index, depth)?;
+            if *sample.proof.leaf()? != leaf(&sample.header.block_hash()) {
+                return Err(Error::UnmatchedBlockHash);
+            }
+            let prev_leaf = match sample.prev_proof {
+                None if *index == 0 => leaf(prev_hash),
+                Some(ref prev_proof) if *index > 0 => {
+                    self.verify_leaf(prev_proof, index - 1, depth)?;
+                    *prev_proof.leaf()?
+                }
+                _ => return Err(Error::UnmatchedLeafIndex),
+            };
+            if leaf(&sample.header.prev_blockhash) != prev_leaf {
+                return Err(Error::UnconnectedHeader);
+            }
+            let target = sample.header.target();
+            if target > *max_target {
+                return Err(Error::TargetAboveFloor);
+            }
+            if !is_valid_proof_of_work_hash(&target, &sample.header.block_hash()) {
+                return Err(Error::BadProofOfWork);
+            }
+            // the first and the last leaves are not random samples.
+            if i > 0 && i < indexes.len() - 1 {
+                sampled_work = sampled_work + sample.header.work();
+            }
+        }
+        // sample_indexes ensures at least one random sample.
+        let tip_work = self.tip().unwrap().work();
+        let random_sample_count = Uint256::from_u64(sample_count as u64).unwrap();
+        Ok(sampled_work.mul_u32(leaf_count - 1) / random_sample_count + tip_work)
+    }
+
+    fn verify_leaf(&self, proof: &MerkleProof, index: u32, depth: usize) -> Result<(), Error> {
+        if proof.depth() != depth || leaf_index(proof) != index {
+            return Err(Error::UnmatchedLeafIndex);
+        }
+        if proof.merkle_root()? != self.commitment {
+            return Err(Error::UnmatchedCommitment);
+        }
+        Ok(())
+    }
+}
+
+/// returns the leaf indexes to be sampled, derived from the seed.
+/// the first one is always the first leaf, and the last one is always the last leaf.
+pub fn sample_indexes(seed: &Seed, leaf_count: u32, sample_count: u32) -> Result<Vec<u32>, Error> {
+    if sample_count == 0 {
+        return Err(Error::NoSamples);
+    }
+    if leaf_count == 0 {
+        return Err(Error::NoHeaders);
+    }
+    let mut indexes = Vec::with_capacity(sample_count as usize + 2);
+    indexes.push(0);
+    for i in 0..sample_count {
+        let mut engine = sha256d::Hash::engine();
+        engine.input(&seed[..]);
+        engine.input(&leaf_count.to_le_bytes());
+        engine.input(&i.to_le_bytes());
+        let hash = sha256d::Hash::from_engine(engine);
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash[..8]);
+        indexes.push((u64::from_le_bytes(bytes) % leaf_count as u64) as u32);
+    }
+    indexes.push(leaf_count - 1);
+    Ok(indexes)
+}
+
+fn leaf(block_hash: &BlockHash) -> TxMerkleNode {
+    TxMerkleNode::from_hash(block_hash.as_hash())
+}
+
+fn leaf_index(proof: &MerkleProof) -> u32 {
+    proof
+        .prefix
+        .iter()
+        .enumerate()
+        .fold(0, |index, (i, prefix)| index | ((*prefix as u32) << i))
+}
+
+fn tree_depth(leaf_count: u32) -> usize {
+    let mut depth = 0;
+    while (1u64 << depth) < leaf_count as u64 {
+        depth += 1;
+    }
+    depth
+}
+
+fn header_tree(headers: &[BlockHeader]) -> Result<Vec<Vec<TxMerkleNode>>, Error> {
+    if headers.is_empty() {
+        return Err(Error::NoHeaders);
+    }
+    Ok(merkle_tree(
+        headers
+            .iter()
+            .map(|header| leaf(&header.block_hash()))
+            .collect(),
+    ))
+}
+
+/// levels of merkle tree from the leaves to the root.
+/// the last node of odd length level is paired with itself, same as the merkle root of a block.
+fn merkle_tree(leaves: Vec<TxMerkleNode>) -> Vec<Vec<TxMerkleNode>> {
+    let mut tree = vec![leaves];
+    while tree.last().unwrap().len() > 1 {
+        let level = tree.last().unwrap();
+        let next = level
+            .chunks(2)
+            .map(|pair| {
+                let mut engine = TxMerkleNode::engine();
+                engine.input(&pair[0][..]);
+                engine.input(&pair[pair.len() - 1][..]);
+                TxMerkleNode::from_engine(engine)
+            })
+            .collect();
+        tree.push(next);
+    }
+    tree
+}
+
+fn merkle_proof(tree: &[Vec<TxMerkleNode>], index: u32) -> MerkleProof {
+    let mut prefix = vec![];
+    let mut siblings = vec![tree[0][index as usize]];
+    let mut index = index as usize;
+    for level in tree[..tree.len() - 1].iter() {
+        prefix.push(index % 2 == 1);
+        siblings.push(level[(index ^ 1).min(level.len() - 1)]);
+        index /= 2;
+    }
+    MerkleProof { prefix, siblings }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::Network;
+
+    const SAMPLE_COUNT: u32 = 8;
+    const SEED: Seed = [1; 32];
+
+    fn regtest_headers(prev_hash: BlockHash, len: usize) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = Vec::with_capacity(len);
+        let genesis = genesis_block(Network::Regtest).header;
+        for i in 0..len {
+            let mut header = BlockHeader {
+                prev_blockhash: headers
+                    .last()
+                    .map(|header| header.block_hash())
+                    .unwrap_or(prev_hash),
+                time: genesis.time + i as u32 * 600,
+                ..genesis
+            };
+            while !is_valid_proof_of_work_hash(&header.target(), &header.block_hash()) {
+                header.nonce += 1;
+            }
+            headers.push(header);
+        }
+        headers
+    }
+
+    #[test]
+    fn test_tree_depth() {
+        for (leaf_count, depth) in [(1, 0), (2, 1), (3, 2), (4, 2), (5, 3), (2016, 11)] {
+            assert_eq!(tree_depth(leaf_count), depth);
+        }
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        for leaf_count in 1..10 {
+            let leaves: Vec<TxMerkleNode> = (0..leaf_count)
+                .map(|i| TxMerkleNode::hash(&[i as u8]))
+                .collect();
+            let tree = merkle_tree(leaves.clone());
+            let root = tree.last().unwrap()[0];
+            // same as the merkle root of a block
+            assert_eq!(
+                root,
+                TxMerkleNode::from_hash(bitcoin::util::hash::bitcoin_merkle_root(
+                    leaves.iter().map(|leaf| leaf.as_hash())
+                ))
+            );
+            for index in 0..leaf_count {
+                let proof = merkle_proof(&tree, index);
+                assert_eq!(proof.merkle_root().unwrap(), root);
+                assert_eq!(proof.depth(), tree_depth(leaf_count));
+                assert_eq!(leaf_index(&proof), index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_indexes() {
+        let seed = [1; 32];
+        let indexes = sample_indexes(&seed, 100, SAMPLE_COUNT).unwrap();
+        assert_eq!(indexes.len(), SAMPLE_COUNT as usize + 2);
+        assert_eq!(indexes[0], 0);
+        assert_eq!(*indexes.last().unwrap(), 99);
+        assert!(indexes.iter().all(|index| *index < 100));
+        // deterministic
+        assert_eq!(indexes, sample_indexes(&seed, 100, SAMPLE_COUNT).unwrap());
+        // depends on the seed
+        assert_ne!(
+            indexes,
+            sample_indexes(&[2; 32], 100, SAMPLE_COUNT).unwrap()
+        );
+        assert_eq!(sample_indexes(&seed, 100, 0), Err(Error::NoSamples));
+    }
+
+    #[test]
+    fn test_verify_sanity() {
+        let prev_hash = genesis_block(Network::Regtest).header.block_hash();
+        let headers = regtest_headers(prev_hash, 100);
+        let proof =
+            CompactPowProof::build(&headers, &sample_indexes(&SEED, 100, SAMPLE_COUNT).unwrap())
+                .unwrap();
+        assert_eq!(proof.tip(), headers.last());
+        assert_eq!(proof.commitment, commit(&headers).unwrap());
+        let max_target = headers[0].target();
+        let work = proof
+            .verify(&prev_hash, 100, &SEED, SAMPLE_COUNT, &max_target)
+            .unwrap();
+        // all the headers have the same work
+        assert_eq!(work, headers[0].work().mul_u32(100));
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        let prev_hash = genesis_block(Network::Regtest).header.block_hash();
+        let headers = regtest_headers(prev_hash, 100);
+        let max_target = headers[0].target();
+        let proof =
+            CompactPowProof::build(&headers, &sample_indexes(&SEED, 100, SAMPLE_COUNT).unwrap())
+                .unwrap();
+
+        // different seed
+        assert_eq!(
+            proof.verify(&prev_hash, 100, &[0; 32], SAMPLE_COUNT, &max_target),
+            Err(Error::UnmatchedLeafIndex)
+        );
+        // different chain tip
+        assert_eq!(
+            proof.verify(
+                &BlockHash::hash(&[0]),
+                100,
+                &SEED,
+                SAMPLE_COUNT,
+                &max_target
+            ),
+            Err(Error::UnconnectedHeader)
+        );
+        // different number of headers
+        assert_eq!(
+            proof.verify(&prev_hash, 99, &SEED, SAMPLE_COUNT, &max_target),
+            Err(Error::UnmatchedLeafIndex)
+        );
+        // different sample count
+        assert_eq!(
+            proof.verify(&prev_hash, 100, &SEED, SAMPLE_COUNT + 1, &max_target),
+            Err(Error::UnmatchedSampleCount)
+        );
+        // target floor
+        assert_eq!(
+            proof.verify(&prev_hash, 100, &SEED, SAMPLE_COUNT, &(max_target >> 1)),
+            Err(Error::TargetAboveFloor)
+        );
+
+        // headers are not connected
+        let mut unconnected_headers = headers.clone();
+        unconnected_headers.remove(0);
+        unconnected_headers.push(headers[0]);
+        let proof = CompactPowProof::build(
+            &unconnected_headers,
+            &sample_indexes(&SEED, 100, 100).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(&prev_hash, 100, &SEED, 100, &max_target),
+            Err(Error::UnconnectedHeader)
+        );
+
+        // headers without proof of work
+        let mut bad_headers: Vec<BlockHeader> = Vec::with_capacity(100);
+        for header in headers.iter() {
+            bad_headers.push(BlockHeader {
+                prev_blockhash: bad_headers
+                    .last()
+                    .map(|header| header.block_hash())
+                    .unwrap_or(prev_hash),
+                bits: BlockHeader::compact_target_from_u256(&(max_target >> 64)),
+                ..*header
+            });
+        }
+        let proof = CompactPowProof::build(
+            &bad_headers,
+            &sample_indexes(&SEED, 100, SAMPLE_COUNT).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(&prev_hash, 100, &SEED, SAMPLE_COUNT, &max_target),
+            Err(Error::BadProofOfWork)
+        );
+    }
+}
@@ -1,5 +1,9 @@
 pub mod chaindb;
+use crate::compact_pow::{self, CompactPowProof};
+use crate::signet::{self, SignetBlockHeader};
 use bitcoin::blockdata::constants::genesis_block;
+use bitcoin::blockdata::script::Script;
+use bitcoin::consensus::params::Params;
 pub use bitcoin::hash_types::BlockHash;
 use bitcoin::hashes::Hash;
 use bitcoin::util::uint::Uint256;
@@ -13,6 +17,14 @@ use std::fmt;
 pub const UNTRUSTED_LENGTH: u32 = 5;
 pub const MAX_FUTURE_BLOCK_TIME: u32 = 2 * 60 * 60;
 pub const DIFFCHANGE_TIMESPAN: u32 = 14 * 24 * 3600;
+/// maximum seconds the first block of difficulty adjustment period can go back from the previous block (BIP94).
+pub const MAX_TIMEWARP: u32 = 600;
+/// minimum number of the latest headers kept on pruning.
+/// it covers median time past of replaced untrusted headers.
+pub const MIN_RETAINED_LENGTH: u32 = 11 + UNTRUSTED_LENGTH;
+/// minimum number of the latest headers kept on pruning on testnet.
+/// testnet difficulty scans back headers to the difficulty adjustment height.
+pub const TESTNET_MIN_RETAINED_LENGTH: u32 = 2016;
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -33,6 +45,14 @@ pub enum Error {
     InvalidTarget,
     MustInitializeWithDiffchangeHeight,
     NoParentHeader,
+    Pruned,
+    RetainedLengthTooShort,
+    TimewarpAttack,
+    SignetSolutionRequired,
+    NotSignet,
+    CheckpointBehindTip,
+    Signet(signet::Error),
+    CompactPow(compact_pow::Error),
     ChainDB(chaindb::Error),
 }
 
@@ -63,6 +83,16 @@ impl fmt::Display for Error {
             Error::NoParentHeader => {
                 write!(f, "no paretn header")
             }
+            Error::Pruned => write!(f, "header pruned"),
+            Error::RetainedLengthTooShort => write!(f, "retained length too short"),
+            Error::TimewarpAttack => {
+                write!(f, "timestamp is too early on difficulty adjustment block")
+            }
+            Error::SignetSolutionRequired => write!(f, "signet solution required"),
+            Error::NotSignet => write!(f, "not signet"),
+            Error::CheckpointBehindTip => write!(f, "checkpoint must be ahead of the tip"),
+            Error::Signet(e) => write!(f, "signet error {}", e),
+            Error::CompactPow(e) => write!(f, "compact pow error {}", e),
             Error::ChainDB(string) => {
                 write!(f, "chain db error {}", string)
             }
@@ -76,6 +106,18 @@ impl From<chaindb::Error> for Error {
     }
 }
 
+impl From<signet::Error> for Error {
+    fn from(e: signet::Error) -> Error {
+        Error::Signet(e)
+    }
+}
+
+impl From<compact_pow::Error> for Error {
+    fn from(e: compact_pow::Error) -> Error {
+        Error::CompactPow(e)
+    }
+}
+
 /// A header enriched with information about its position on the blockchain
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StoredBlockHeader {
@@ -83,6 +125,16 @@ pub struct StoredBlockHeader {
     pub work: Uint256,
 }
 
+/// Result of storing headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredHeaders {
+    /// the stored headers with their work in the order of height.
+    pub headers: Vec<StoredBlockHeader>,
+    /// number of the headers newly stored on the best chain.
+    /// headers already stored at the same height are not counted.
+    pub accepted: u32,
+}
+
 // validate that work is greater than target written in the block.
 fn validate_work(block_header: &BlockHeader) -> Result<(), Error> {
     let target = block_header.target();
@@ -130,8 +182,45 @@ impl<C: ReadonlyChainDB> HeaderChain<C> {
         }
     }
 
+    /// returns the header at the height.
+    /// headers below the oldest height are not provable, even if they are kept in the db:
+    /// the ones at difficulty adjustment height are kept for the retargets on pruning,
+    /// and the ones skipped by a checkpoint may be on an abandoned fork.
     pub fn header_at(&mut self, height: u32) -> Result<Option<StoredBlockHeader>, Error> {
-        Ok(self.db.header_at(height)?)
+        match self.db.oldest_height()? {
+            Some(oldest_height) if height < oldest_height => Err(Error::Pruned),
+            _ => Ok(self.db.header_at(height)?),
+        }
+    }
+
+    /// returns the height of the block in the header chain.
+    pub fn height_of(&mut self, block_hash: &BlockHash) -> Result<Option<u32>, Error> {
+        if let Some(height) = self.db.height_of(block_hash)? {
+            if self.db.oldest_height()? > Some(height) {
+                return Ok(None);
+            }
+            if let Some(header) = self.db.header_at(height)? {
+                if header.header.block_hash() == *block_hash {
+                    return Ok(Some(height));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// returns the lowest height from which all the headers are stored.
+    pub fn oldest_height(&mut self) -> Result<Option<u32>, Error> {
+        Ok(self.db.oldest_height()?)
+    }
+}
+
+/// returns minimum number of the latest headers which must be kept on pruning.
+pub fn min_retained_length(network: Network) -> u32 {
+    let params = Params::new(network);
+    if params.allow_min_difficulty_blocks && !params.no_pow_retargeting {
+        TESTNET_MIN_RETAINED_LENGTH
+    } else {
+        MIN_RETAINED_LENGTH
     }
 }
 
@@ -159,9 +248,124 @@ impl<H: ChainDB> HeaderChain<H> {
         }
         validate_work(&header)?;
         let work = header.work();
-        Ok(self
+        self.db
+            .store_header(height, StoredBlockHeader { header, work })?;
+        Ok(self.db.flush()?)
+    }
+
+    /// jumps the chain forward to a trusted header at difficulty adjustment height.
+    /// headers following the checkpoint are validated with the retarget rules from it,
+    /// and headers below it are no longer provable: they are left in the db,
+    /// but `header_at` rejects them as below the oldest height.
+    /// the checkpoint target must be reachable by retargeting from the current tip epoch,
+    /// and the work of the skipped blocks is estimated with the checkpoint target,
+    /// so that the cumulative work never goes below the current chain.
+    ///
+    /// the skipped blocks are not verified, and the estimated work is not their actual chainwork.
+    /// the caller is trusted that the checkpoint is on the most-work chain:
+    /// a checkpoint on a fork is accepted as long as its own proof of work and target are valid,
+    /// and headers following it are compared with the estimated work.
+    pub fn add_checkpoint(
+        &mut self,
+        height: u32,
+        header: BlockHeader,
+        now: u32,
+    ) -> Result<(), Error> {
+        let tip_height = self.db.tip_height()?.ok_or(Error::NoTip)?;
+        let skipped_work = header.work().mul_u32(height.saturating_sub(tip_height));
+        self.jump_to(height, header, skipped_work, now)
+    }
+
+    /// jumps the chain forward to the header at difficulty adjustment height
+    /// with compact proof of the skipped headers. see `compact_pow` for the trade-offs.
+    /// the seed must have been drawn after the commitment of the proof.
+    /// the work of the skipped blocks is estimated from the sampled headers.
+    pub fn store_compact_pow_proof(
+        &mut self,
+        height: u32,
+        proof: &CompactPowProof,
+        seed: &compact_pow::Seed,
+        sample_count: u32,
+        max_target: &Uint256,
+        now: u32,
+    ) -> Result<(), Error> {
+        // signet headers are valid only with the block solution
+        if self.network == Network::Signet {
+            return Err(Error::SignetSolutionRequired);
+        }
+        let tip_height = self.db.tip_height()?.ok_or(Error::NoTip)?;
+        if height <= tip_height {
+            return Err(Error::CheckpointBehindTip);
+        }
+        let tip = self.header_at(tip_height)?.ok_or(Error::NoTip)?;
+        let skipped_work = proof.verify(
+            &tip.header.block_hash(),
+            height - tip_height,
+            seed,
+            sample_count,
+            max_target,
+        )?;
+        let header = *proof.tip().unwrap();
+        self.jump_to(height, header, skipped_work, now)
+    }
+
+    fn jump_to(
+        &mut self,
+        height: u32,
+        header: BlockHeader,
+        skipped_work: Uint256,
+        now: u32,
+    ) -> Result<(), Error> {
+        if height % 2016 != 0 {
+            return Err(Error::MustInitializeWithDiffchangeHeight);
+        }
+        let tip_height = self.db.tip_height()?.ok_or(Error::NoTip)?;
+        if height <= tip_height {
+            return Err(Error::CheckpointBehindTip);
+        }
+        let tip = self.header_at(tip_height)?.ok_or(Error::NoTip)?;
+        // the epoch start is kept on pruning, even below the oldest height.
+        let epoch_start = self
             .db
-            .store_header(height, StoredBlockHeader { header, work })?)
+            .header_at(tip_height - tip_height % 2016)?
+            .ok_or(Error::UnconnectedHeader)?;
+        if header.target() > self.max_checkpoint_target(&epoch_start.header, tip_height, height) {
+            return Err(Error::InvalidTarget);
+        }
+        validate_work(&header)?;
+        if self.db.mpt_at(tip_height + 1)?.unwrap_or_default() > header.time {
+            return Err(Error::MedianPastTime);
+        }
+        if header.time > max_future(now) {
+            return Err(Error::MaxFutureTime);
+        }
+        let work = tip.work + skipped_work;
+        self.db
+            .store_header(height, StoredBlockHeader { header, work })?;
+        self.db.store_oldest_height(height)?;
+        Ok(self.db.flush()?)
+    }
+
+    /// returns the easiest target reachable by the retargets between the tip and the checkpoint.
+    fn max_checkpoint_target(
+        &self,
+        epoch_start: &BlockHeader,
+        tip_height: u32,
+        checkpoint_height: u32,
+    ) -> Uint256 {
+        let params = Params::new(self.network);
+        if params.no_pow_retargeting {
+            return epoch_start.target();
+        }
+        let max = max_target(self.network);
+        let mut target = epoch_start.target();
+        for _ in 0..(checkpoint_height / 2016 - tip_height / 2016) {
+            target = target.mul_u32(4);
+            if target > max {
+                return max;
+            }
+        }
+        target
     }
 
     pub fn store_headers(
@@ -169,7 +373,39 @@ impl<H: ChainDB> HeaderChain<H> {
         tip_height: u32,
         headers: Vec<BlockHeader>,
         now: u32,
-    ) -> Result<Vec<StoredBlockHeader>, Error> {
+    ) -> Result<StoredHeaders, Error> {
+        // signet headers are valid only with the block solution
+        if self.network == Network::Signet {
+            return Err(Error::SignetSolutionRequired);
+        }
+        self.store_valid_headers(tip_height, headers, now)
+    }
+
+    /// stores signet headers after validating the block solution of each header against the challenge.
+    pub fn store_signet_headers(
+        &mut self,
+        tip_height: u32,
+        headers: Vec<SignetBlockHeader>,
+        challenge: &Script,
+        now: u32,
+    ) -> Result<StoredHeaders, Error> {
+        if self.network != Network::Signet {
+            return Err(Error::NotSignet);
+        }
+        for header in headers.iter() {
+            signet::validate_block_solution(challenge, header)?;
+        }
+        let headers = headers.into_iter().map(|header| header.header).collect();
+        self.store_valid_headers(tip_height, headers, now)
+    }
+
+    fn store_valid_headers(
+        &mut self,
+        tip_height: u32,
+        headers: Vec<BlockHeader>,
+        now: u32,
+    ) -> Result<StoredHeaders, Error> {
+        let params = Params::new(self.network);
         let max_future = max_future(now);
         let current_tip_height = self.db.tip_height()?.ok_or(Error::NoTip)?;
         let current_tip = self.header_at(current_tip_height)?.unwrap();
@@ -188,6 +424,7 @@ impl<H: ChainDB> HeaderChain<H> {
         let mut prev_header = self
             .header_at(start_height - 1)?
             .expect("Start header must be exist.");
+        let mut accepted = 0;
         let stored_headers = {
             let mut stored_headers = Vec::with_capacity(headers_len as usize);
             let mut work = prev_header.work;
@@ -195,6 +432,7 @@ impl<H: ChainDB> HeaderChain<H> {
             for header in headers.into_iter() {
                 let stored_block_header = {
                     if let Some(stored_header) = self
+                        .db
                         .header_at(height)?
                         .filter(|stored_header| stored_header.header == header)
                     {
@@ -202,6 +440,7 @@ impl<H: ChainDB> HeaderChain<H> {
                         stored_header
                     } else {
                         work = work + header.work();
+                        accepted += 1;
                         StoredBlockHeader { header, work }
                     }
                 };
@@ -223,6 +462,12 @@ impl<H: ChainDB> HeaderChain<H> {
             {
                 return Err(Error::InvalidTarget);
             }
+            if params.enforce_bip94
+                && height % 2016 == 0
+                && header.header.time < prev_header.header.time.saturating_sub(MAX_TIMEWARP)
+            {
+                return Err(Error::TimewarpAttack);
+            }
             validate_work(&header.header)?;
             if self.db.mpt_at(height)?.unwrap_or_default() > header.header.time {
                 return Err(Error::MedianPastTime);
@@ -234,7 +479,31 @@ impl<H: ChainDB> HeaderChain<H> {
             height += 1;
             prev_header = header
         }
-        Ok(stored_headers)
+        self.db.flush()?;
+        Ok(StoredHeaders {
+            headers: stored_headers,
+            accepted,
+        })
+    }
+
+    /// removes headers except the latest `retained_length` headers and the ones at difficulty adjustment height.
+    pub fn prune_headers(&mut self, retained_length: u32) -> Result<(), Error> {
+        if retained_length < min_retained_length(self.network) {
+            return Err(Error::RetainedLengthTooShort);
+        }
+        let tip_height = self.db.tip_height()?.ok_or(Error::NoTip)?;
+        let oldest_height = self.db.oldest_height()?.ok_or(Error::NoTip)?;
+        let new_oldest_height = (tip_height + 1).saturating_sub(retained_length);
+        if new_oldest_height <= oldest_height {
+            return Ok(());
+        }
+        for height in oldest_height..new_oldest_height {
+            // headers at difficulty adjustment height are needed by required_target.
+            if height % 2016 != 0 {
+                self.db.remove_header(height)?;
+            }
+        }
+        Ok(self.db.store_oldest_height(new_oldest_height)?)
     }
 
     pub fn required_target(
@@ -243,49 +512,60 @@ impl<H: ChainDB> HeaderChain<H> {
         prev_header: &BlockHeader,
         next_header: &BlockHeader,
     ) -> Result<Uint256, Error> {
-        Ok(
-            if (prev_height + 1) % 2016 == 0 && self.network != Network::Regtest {
-                let timespan = {
-                    let start = self
-                        .header_at(prev_height - 2015)?
-                        .ok_or(Error::UnconnectedHeader)?
-                        .header
-                        .time;
-                    let end = prev_header.time;
-                    match end - start {
-                        n if n < DIFFCHANGE_TIMESPAN / 4 => DIFFCHANGE_TIMESPAN / 4,
-                        n if n > DIFFCHANGE_TIMESPAN * 4 => DIFFCHANGE_TIMESPAN * 4,
-                        n => n,
-                    }
-                };
-                let mut target = prev_header.target().mul_u32(timespan)
-                    / Uint256::from_u64(DIFFCHANGE_TIMESPAN as u64).unwrap();
-                let max = max_target();
-                if target > max {
-                    target = max;
+        let params = Params::new(self.network);
+        Ok(if params.no_pow_retargeting {
+            prev_header.target()
+        } else if (prev_height + 1) % 2016 == 0 {
+            let timespan = {
+                let start = self
+                    .db
+                    .epoch_start_time_at(prev_height)?
+                    .ok_or(Error::UnconnectedHeader)?;
+                let end = prev_header.time;
+                match end - start {
+                    n if n < DIFFCHANGE_TIMESPAN / 4 => DIFFCHANGE_TIMESPAN / 4,
+                    n if n > DIFFCHANGE_TIMESPAN * 4 => DIFFCHANGE_TIMESPAN * 4,
+                    n => n,
                 }
-                satoshi_the_precision(target)
-            } else if self.network == Network::Testnet
-                && next_header.time > prev_header.time + 2 * 600
-            {
-                // Reset Difficulty
-                max_target()
-            } else if self.network == Network::Testnet {
-                let max = max_target();
-                let mut height = prev_height;
-                let mut scan = prev_header.clone();
-                while height % 2016 != 0 && scan.target() == max {
-                    height -= 1;
-                    scan = self
-                        .header_at(height)?
-                        .ok_or(Error::UnconnectedHeader)?
-                        .header;
-                }
-                scan.target()
+            };
+            // BIP94 retargets from the first block of the period,
+            // which can not be a min difficulty block.
+            let base_target = if params.enforce_bip94 {
+                self.db
+                    .header_at(prev_height + 1 - 2016)?
+                    .ok_or(Error::UnconnectedHeader)?
+                    .header
+                    .target()
             } else {
                 prev_header.target()
-            },
-        )
+            };
+            let mut target = base_target.mul_u32(timespan)
+                / Uint256::from_u64(DIFFCHANGE_TIMESPAN as u64).unwrap();
+            let max = max_target(self.network);
+            if target > max {
+                target = max;
+            }
+            satoshi_the_precision(target)
+        } else if params.allow_min_difficulty_blocks
+            && next_header.time > prev_header.time + 2 * 600
+        {
+            // Reset Difficulty
+            max_target(self.network)
+        } else if params.allow_min_difficulty_blocks {
+            let max = max_target(self.network);
+            let mut height = prev_height;
+            let mut scan = prev_header.clone();
+            while height % 2016 != 0 && scan.target() == max {
+                height -= 1;
+                scan = self
+                    .header_at(height)?
+                    .ok_or(Error::UnconnectedHeader)?
+                    .header;
+            }
+            scan.target()
+        } else {
+            prev_header.target()
+        })
     }
 }
 
@@ -307,8 +587,9 @@ fn satoshi_the_precision(n: Uint256) -> Uint256 {
     ret << bits
 }
 
-fn max_target() -> Uint256 {
-    Uint256::from_u64(0xFFFF).unwrap() << 208
+/// returns the lowest difficulty target of the network.
+fn max_target(network: Network) -> Uint256 {
+    Params::new(network).pow_limit
 }
 fn max_future(now: u32) -> u32 {
     now.checked_add(MAX_FUTURE_BLOCK_TIME).unwrap()
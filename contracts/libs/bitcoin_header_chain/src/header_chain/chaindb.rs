@@ -1,4 +1,6 @@
-use crate::header_chain::StoredBlockHeader;
+pub mod file;
+pub mod memory;
+use crate::header_chain::{BlockHash, StoredBlockHeader};
 
 pub type Error = String;
 
@@ -7,6 +9,12 @@ pub type ChainDBResult<T> = Result<T, Error>;
 pub trait ReadonlyChainDB {
     fn header_at(&mut self, height: u32) -> ChainDBResult<Option<StoredBlockHeader>>;
     fn tip_height(&mut self) -> ChainDBResult<Option<u32>>;
+    // gets the lowest height from which all the headers are stored.
+    // headers below it are pruned except the ones at difficulty adjustment height.
+    fn oldest_height(&mut self) -> ChainDBResult<Option<u32>>;
+    // gets the height of the header stored with the block hash.
+    // the header at the height may have been replaced by another one.
+    fn height_of(&mut self, block_hash: &BlockHash) -> ChainDBResult<Option<u32>>;
 
     // gets last 12 timestamps.
     // if there is less than 12 blocks in store, return the existing timestamps only.
@@ -26,6 +34,13 @@ pub trait ReadonlyChainDB {
     fn mpt_at(&mut self, height: u32) -> ChainDBResult<Option<u32>> {
         Ok(self.last_timestamps_at(height)?.mid())
     }
+
+    // gets timestamp of the first block of the difficulty adjustment epoch which includes the height.
+    fn epoch_start_time_at(&mut self, height: u32) -> ChainDBResult<Option<u32>> {
+        Ok(self
+            .header_at(height - height % 2016)?
+            .map(|stored_header| stored_header.header.time))
+    }
 }
 
 #[derive(Default)]
@@ -57,6 +72,12 @@ impl Timestamps {
 
 pub trait ChainDB: ReadonlyChainDB {
     fn store_header(&mut self, height: u32, block_header: StoredBlockHeader) -> ChainDBResult<()>;
+    fn remove_header(&mut self, height: u32) -> ChainDBResult<()>;
+    fn store_oldest_height(&mut self, height: u32) -> ChainDBResult<()>;
+    // writes the state buffered while storing a batch of headers.
+    fn flush(&mut self) -> ChainDBResult<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,178 @@
+use crate::header_chain::chaindb::memory::MemoryChainDB;
+use crate::header_chain::chaindb::{ChainDB, ChainDBResult, ReadonlyChainDB};
+use crate::header_chain::{BlockHash, StoredBlockHeader};
+use bitcoin::consensus::encode::{self, Decodable, Encodable};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+
+const RECORD_STORE_HEADER: u8 = 0;
+const RECORD_REMOVE_HEADER: u8 = 1;
+const RECORD_STORE_OLDEST_HEIGHT: u8 = 2;
+
+/// FileChainDB stores bitcoin chain db on a file.
+/// The file is an append-only log of the writes, which is replayed on open.
+/// It keeps all the headers on memory too.
+#[derive(Debug)]
+pub struct FileChainDB {
+    memory: MemoryChainDB,
+    writer: BufWriter<File>,
+}
+
+impl FileChainDB {
+    /// opens the file, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> ChainDBResult<Self> {
+        let path = path.as_ref();
+        let mut memory = MemoryChainDB::new();
+        if path.exists() {
+            let mut reader = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+            while let Some(tag) = read_tag(&mut reader)? {
+                let height = decode::<u32, _>(&mut reader)?;
+                match tag {
+                    RECORD_STORE_HEADER => {
+                        let header = StoredBlockHeader {
+                            header: decode(&mut reader)?,
+                            work: decode(&mut reader)?,
+                        };
+                        memory.store_header(height, header)?
+                    }
+                    RECORD_REMOVE_HEADER => memory.remove_header(height)?,
+                    RECORD_STORE_OLDEST_HEIGHT => memory.store_oldest_height(height)?,
+                    _ => return Err(format!("unknown record {}", tag)),
+                }
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            memory,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// writes buffered records to the file.
+    pub fn flush(&mut self) -> ChainDBResult<()> {
+        self.writer.flush().map_err(|e| e.to_string())
+    }
+
+    fn write_record(
+        &mut self,
+        tag: u8,
+        height: u32,
+        header: Option<&StoredBlockHeader>,
+    ) -> ChainDBResult<()> {
+        let mut record = vec![tag];
+        height
+            .consensus_encode(&mut record)
+            .map_err(|e| e.to_string())?;
+        if let Some(header) = header {
+            header
+                .header
+                .consensus_encode(&mut record)
+                .map_err(|e| e.to_string())?;
+            header
+                .work
+                .consensus_encode(&mut record)
+                .map_err(|e| e.to_string())?;
+        }
+        self.writer.write_all(&record).map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for FileChainDB {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+fn read_tag<R: std::io::Read>(reader: &mut R) -> ChainDBResult<Option<u8>> {
+    let mut tag = [0u8; 1];
+    match reader.read_exact(&mut tag) {
+        Ok(()) => Ok(Some(tag[0])),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn decode<T: Decodable, R: std::io::Read>(reader: &mut R) -> ChainDBResult<T> {
+    T::consensus_decode(reader).map_err(|e: encode::Error| e.to_string())
+}
+
+impl ReadonlyChainDB for FileChainDB {
+    fn header_at(&mut self, height: u32) -> ChainDBResult<Option<StoredBlockHeader>> {
+        self.memory.header_at(height)
+    }
+
+    fn tip_height(&mut self) -> ChainDBResult<Option<u32>> {
+        self.memory.tip_height()
+    }
+
+    fn oldest_height(&mut self) -> ChainDBResult<Option<u32>> {
+        self.memory.oldest_height()
+    }
+
+    fn height_of(&mut self, block_hash: &BlockHash) -> ChainDBResult<Option<u32>> {
+        self.memory.height_of(block_hash)
+    }
+}
+
+impl ChainDB for FileChainDB {
+    fn store_header(&mut self, height: u32, block_header: StoredBlockHeader) -> ChainDBResult<()> {
+        self.write_record(RECORD_STORE_HEADER, height, Some(&block_header))?;
+        self.memory.store_header(height, block_header)
+    }
+
+    fn remove_header(&mut self, height: u32) -> ChainDBResult<()> {
+        self.write_record(RECORD_REMOVE_HEADER, height, None)?;
+        self.memory.remove_header(height)
+    }
+
+    fn store_oldest_height(&mut self, height: u32) -> ChainDBResult<()> {
+        self.write_record(RECORD_STORE_OLDEST_HEIGHT, height, None)?;
+        self.memory.store_oldest_height(height)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::header_chain::HeaderChain;
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::Network;
+
+    #[test]
+    fn test_file_chaindb_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "bitcoin_header_chain_test_{}.chaindb",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let genesis = genesis_block(Network::Bitcoin).header;
+        {
+            let mut header_chain =
+                HeaderChain::new(FileChainDB::open(&path).unwrap(), Network::Bitcoin);
+            header_chain.init_to_genesis().unwrap();
+        }
+        let mut db = FileChainDB::open(&path).unwrap();
+        assert_eq!(db.tip_height().unwrap(), Some(0));
+        assert_eq!(db.oldest_height().unwrap(), Some(0));
+        assert_eq!(
+            db.header_at(0).unwrap(),
+            Some(StoredBlockHeader {
+                header: genesis,
+                work: genesis.work()
+            })
+        );
+        db.remove_header(0).unwrap();
+        db.store_oldest_height(1).unwrap();
+        db.flush().unwrap();
+        let mut db = FileChainDB::open(&path).unwrap();
+        assert_eq!(db.header_at(0).unwrap(), None);
+        assert_eq!(db.height_of(&genesis.block_hash()).unwrap(), None);
+        assert_eq!(db.oldest_height().unwrap(), Some(1));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
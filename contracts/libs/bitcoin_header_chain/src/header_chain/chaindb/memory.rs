@@ -0,0 +1,85 @@
+use crate::header_chain::chaindb::{ChainDB, ChainDBResult, ReadonlyChainDB};
+use crate::header_chain::{BlockHash, StoredBlockHeader};
+use std::collections::HashMap;
+
+/// MemoryChainDB stores bitcoin chain db on memory.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MemoryChainDB {
+    headers: HashMap<u32, StoredBlockHeader>,
+    heights: HashMap<BlockHash, u32>,
+    tip_height: Option<u32>,
+    oldest_height: Option<u32>,
+}
+
+impl MemoryChainDB {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ReadonlyChainDB for MemoryChainDB {
+    fn header_at(&mut self, height: u32) -> ChainDBResult<Option<StoredBlockHeader>> {
+        Ok(self.headers.get(&height).cloned())
+    }
+
+    fn tip_height(&mut self) -> ChainDBResult<Option<u32>> {
+        Ok(self.tip_height)
+    }
+
+    fn oldest_height(&mut self) -> ChainDBResult<Option<u32>> {
+        Ok(self.oldest_height)
+    }
+
+    fn height_of(&mut self, block_hash: &BlockHash) -> ChainDBResult<Option<u32>> {
+        Ok(self.heights.get(block_hash).cloned())
+    }
+}
+
+impl ChainDB for MemoryChainDB {
+    fn store_header(&mut self, height: u32, block_header: StoredBlockHeader) -> ChainDBResult<()> {
+        if self.tip_height.unwrap_or_default() <= height {
+            self.tip_height = Some(height);
+        }
+        if self.oldest_height.is_none() {
+            self.oldest_height = Some(height);
+        }
+        self.heights
+            .insert(block_header.header.block_hash(), height);
+        self.headers.insert(height, block_header);
+        Ok(())
+    }
+
+    fn remove_header(&mut self, height: u32) -> ChainDBResult<()> {
+        if let Some(stored_header) = self.headers.remove(&height) {
+            self.heights.remove(&stored_header.header.block_hash());
+        }
+        Ok(())
+    }
+
+    fn store_oldest_height(&mut self, height: u32) -> ChainDBResult<()> {
+        self.oldest_height = Some(height);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::header_chain::HeaderChain;
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::Network;
+
+    #[test]
+    fn test_memory_chaindb() {
+        let mut header_chain = HeaderChain::new(MemoryChainDB::new(), Network::Bitcoin);
+        header_chain.init_to_genesis().unwrap();
+        let genesis = genesis_block(Network::Bitcoin).header;
+        assert_eq!(header_chain.tip_height().unwrap(), Some(0));
+        assert_eq!(header_chain.oldest_height().unwrap(), Some(0));
+        assert_eq!(header_chain.header_at(0).unwrap().unwrap().header, genesis);
+        assert_eq!(
+            header_chain.height_of(&genesis.block_hash()).unwrap(),
+            Some(0)
+        );
+    }
+}
@@ -9,7 +9,13 @@ use std::convert::TryInto;
 use std::str::FromStr;
 use std::time;
 
-const NETWORKS: [Network; 3] = [Network::Bitcoin, Network::Testnet, Network::Regtest];
+const NETWORKS: [Network; 5] = [
+    Network::Bitcoin,
+    Network::Testnet,
+    Network::Signet,
+    Network::Testnet4,
+    Network::Regtest,
+];
 
 fn block_header(
     version: i32,
@@ -36,6 +42,7 @@ mod mock_chain_db {
     pub struct MockChainDB {
         pub map: HashMap<u32, StoredBlockHeader>,
         pub tip_height: Option<u32>,
+        pub oldest_height: Option<u32>,
     }
 
     impl MockChainDB {
@@ -43,6 +50,7 @@ mod mock_chain_db {
             Self {
                 map: HashMap::default(),
                 tip_height: None,
+                oldest_height: None,
             }
         }
     }
@@ -55,6 +63,18 @@ mod mock_chain_db {
         fn tip_height(&mut self) -> ChainDBResult<Option<u32>> {
             Ok(self.tip_height)
         }
+
+        fn oldest_height(&mut self) -> ChainDBResult<Option<u32>> {
+            Ok(self.oldest_height)
+        }
+
+        fn height_of(&mut self, block_hash: &BlockHash) -> ChainDBResult<Option<u32>> {
+            Ok(self
+                .map
+                .iter()
+                .find(|(_, stored_header)| stored_header.header.block_hash() == *block_hash)
+                .map(|(height, _)| *height))
+        }
     }
 
     impl ChainDB for MockChainDB {
@@ -66,9 +86,22 @@ mod mock_chain_db {
             if self.tip_height.is_none() || self.tip_height()?.unwrap() < height {
                 self.tip_height = Some(height);
             }
+            if self.oldest_height.is_none() {
+                self.oldest_height = Some(height);
+            }
             self.map.insert(height, block_header);
             Ok(())
         }
+
+        fn remove_header(&mut self, height: u32) -> ChainDBResult<()> {
+            self.map.remove(&height);
+            Ok(())
+        }
+
+        fn store_oldest_height(&mut self, height: u32) -> ChainDBResult<()> {
+            self.oldest_height = Some(height);
+            Ok(())
+        }
     }
 }
 
@@ -372,12 +405,12 @@ fn test_required_target_testnet_reset_difficulty() {
     let target = header_chain
         .required_target(0, &prev_header, &next_header)
         .unwrap();
-    assert_eq!(target, max_target());
+    assert_eq!(target, max_target(Network::Testnet));
 }
 
 #[test]
 fn test_required_target_testnet_scan_back() {
-    let max_target = max_target();
+    let max_target = max_target(Network::Testnet);
     let scan_backed_header = block_header(
         1,
         "0000000000000000000000000000000000000000000000000000000000000000",
@@ -433,7 +466,7 @@ fn test_required_target_testnet_scan_back() {
 
 #[test]
 fn test_required_target_testnet_scan_back_max_target() {
-    let max_target = max_target();
+    let max_target = max_target(Network::Testnet);
     let older_header = block_header(
         1,
         "0000000000000000000000000000000000000000000000000000000000000000",
@@ -570,10 +603,12 @@ fn store_headers_repeating() {
         bits: 0x1d00ffff,
         nonce: 2573394689,
     };
-    for _ in 1..100 {
-        header_chain
+    for i in 1..100 {
+        let stored = header_chain
             .store_headers(1, vec![next_block_header.clone()], next_block_header.time)
             .unwrap();
+        // only the first one is newly stored
+        assert_eq!(stored.accepted, if i == 1 { 1 } else { 0 });
         let header_tip = header_chain.tip().unwrap().unwrap();
         assert_eq!(header_tip.header, next_block_header);
         assert_eq!(header_tip.work, Uint256::from_u64(0x200020002).unwrap());
@@ -760,6 +795,142 @@ fn test_extend_tip_bulk() {
     );
 }
 
+#[test]
+fn test_height_of() {
+    use random_chain::*;
+
+    const BLOCK_TIMESPAN: u32 = 600;
+    let genesis = random_header(BlockHash::default(), 1231469665, HIGH_TARGET);
+    let mut headers = vec![];
+    let mut prev_header = genesis.clone();
+    for _ in 0..10 {
+        let header = random_header(
+            prev_header.block_hash(),
+            prev_header.time + BLOCK_TIMESPAN,
+            HIGH_TARGET,
+        );
+        headers.push(header.clone());
+        prev_header = header;
+    }
+
+    let db = MockChainDB::new();
+    let mut header_chain = HeaderChain::new(db, Network::Regtest);
+    header_chain
+        .init_to_header(0, genesis, genesis.time)
+        .unwrap();
+    header_chain
+        .store_headers(10, headers.clone(), headers[9].time)
+        .unwrap();
+
+    assert_eq!(
+        header_chain.height_of(&genesis.block_hash()).unwrap(),
+        Some(0)
+    );
+    for (i, header) in headers.iter().enumerate() {
+        assert_eq!(
+            header_chain.height_of(&header.block_hash()).unwrap(),
+            Some(i as u32 + 1)
+        );
+    }
+    // assert unknown block
+    let unknown = random_header(genesis.block_hash(), genesis.time + 1, HIGH_TARGET);
+    assert!(header_chain
+        .height_of(&unknown.block_hash())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_prune_headers() {
+    use random_chain::*;
+
+    // Create Random Header Chain
+    const BLOCK_TIMESPAN: u32 = 600;
+    let genesis = random_header(BlockHash::default(), 1231469665, HIGH_TARGET);
+    let mut headers = vec![];
+    let mut prev_header = genesis.clone();
+    for _ in 0..2100 {
+        let header = random_header(
+            prev_header.block_hash(),
+            prev_header.time + BLOCK_TIMESPAN,
+            HIGH_TARGET,
+        );
+        headers.push(header.clone());
+        prev_header = header;
+    }
+
+    let db = MockChainDB::new();
+    let mut header_chain = HeaderChain::new(db, Network::Regtest);
+    header_chain
+        .init_to_header(0, genesis, genesis.time)
+        .unwrap();
+    header_chain
+        .store_headers(2000, headers[..2000].to_vec(), headers[1999].time)
+        .unwrap();
+    assert_eq!(header_chain.oldest_height().unwrap().unwrap(), 0);
+
+    // prune headers
+    header_chain.prune_headers(50).unwrap();
+    assert_eq!(header_chain.oldest_height().unwrap().unwrap(), 1951);
+    assert_eq!(header_chain.header_at(1950).unwrap_err(), Error::Pruned);
+    assert_eq!(header_chain.header_at(1).unwrap_err(), Error::Pruned);
+    assert!(header_chain.header_at(1951).unwrap().is_some());
+    // assert header at difficulty adjustment height retained for the retargets,
+    // but not provable below the oldest height
+    assert_eq!(header_chain.header_at(0).unwrap_err(), Error::Pruned);
+    assert_eq!(
+        header_chain.db.header_at(0).unwrap().unwrap().header,
+        genesis
+    );
+
+    // extend tip over the difficulty adjustment height and prune headers again
+    header_chain
+        .store_headers(2100, headers[2000..].to_vec(), headers[2099].time)
+        .unwrap();
+    header_chain.prune_headers(50).unwrap();
+    assert_eq!(header_chain.oldest_height().unwrap().unwrap(), 2051);
+    assert_eq!(header_chain.header_at(2050).unwrap_err(), Error::Pruned);
+    assert_eq!(header_chain.header_at(2015).unwrap_err(), Error::Pruned);
+    assert_eq!(header_chain.header_at(1951).unwrap_err(), Error::Pruned);
+    assert_eq!(header_chain.header_at(2016).unwrap_err(), Error::Pruned);
+    assert_eq!(
+        header_chain.db.header_at(2016).unwrap().unwrap().header,
+        headers[2015]
+    );
+    assert_eq!(
+        header_chain.db.header_at(0).unwrap().unwrap().header,
+        genesis
+    );
+    for height in 2051..2101 {
+        assert_eq!(
+            header_chain.header_at(height).unwrap().unwrap().header,
+            headers[height as usize - 1]
+        );
+    }
+    // assert heights above the tip are not pruned
+    assert!(header_chain.header_at(2101).unwrap().is_none());
+
+    // pruning with the same retained length does nothing
+    header_chain.prune_headers(50).unwrap();
+    assert_eq!(header_chain.oldest_height().unwrap().unwrap(), 2051);
+}
+
+#[test]
+fn test_prune_headers_retained_length_too_short() {
+    for network in NETWORKS.iter() {
+        let db = MockChainDB::new();
+        let mut header_chain = HeaderChain::new(db, *network);
+        header_chain.init_to_genesis().unwrap();
+        let min = min_retained_length(*network);
+        assert_eq!(
+            header_chain.prune_headers(min - 1).unwrap_err(),
+            Error::RetainedLengthTooShort
+        );
+        header_chain.prune_headers(min).unwrap();
+        assert_eq!(header_chain.oldest_height().unwrap().unwrap(), 0);
+    }
+}
+
 #[test]
 fn test_replace_with_larger_work_chain() {
     use random_chain::*;
@@ -984,3 +1155,468 @@ fn test_testnet_blockchain() {
     let tip_height = 2104707;
     header_chain.store_headers(tip_height, blocks, now).unwrap();
 }
+
+#[test]
+fn test_required_target_testnet4_retarget_from_first_block() {
+    // first block of the period at 0
+    let start_header = block_header(
+        1,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        0,
+        404399040,
+        0,
+    );
+    // min difficulty block at 2015
+    let end_header = block_header(
+        1,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        start_header.time + DIFFCHANGE_TIMESPAN / 2,
+        BlockHeader::compact_target_from_u256(&max_target(Network::Testnet4)),
+        0,
+    );
+    let next_header = block_header(
+        1,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        end_header.time + 600,
+        0,
+        0,
+    );
+
+    for (network, expected) in [
+        // retarget from the target of the first block
+        (
+            Network::Testnet4,
+            satoshi_the_precision(start_header.target() >> 1),
+        ),
+        // retarget from the target of the last block, which is min difficulty
+        (
+            Network::Testnet,
+            satoshi_the_precision(max_target(Network::Testnet) >> 1),
+        ),
+    ] {
+        let mut db = MockChainDB::new();
+        db.store_header(
+            0,
+            StoredBlockHeader {
+                header: start_header,
+                work: Uint256::default(),
+            },
+        )
+        .unwrap();
+        let mut header_chain = HeaderChain::new(db, network);
+        let target = header_chain
+            .required_target(2015, &end_header, &next_header)
+            .unwrap();
+        assert_eq!(target, expected);
+    }
+}
+
+#[test]
+fn test_store_headers_testnet4_timewarp() {
+    let start_header = genesis_block(Network::Testnet4).header;
+    let end_header = block_header(
+        1,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        start_header.time + DIFFCHANGE_TIMESPAN,
+        start_header.bits,
+        0,
+    );
+    for (network, time, expected) in [
+        // the first block of the period goes back more than MAX_TIMEWARP
+        (
+            Network::Testnet4,
+            end_header.time - MAX_TIMEWARP - 1,
+            Error::TimewarpAttack,
+        ),
+        // passes timewarp rule and fails at proof of work
+        (
+            Network::Testnet4,
+            end_header.time - MAX_TIMEWARP,
+            Error::BadProofOfWork,
+        ),
+        // timewarp rule is not enforced on testnet
+        (
+            Network::Testnet,
+            end_header.time - MAX_TIMEWARP - 1,
+            Error::BadProofOfWork,
+        ),
+    ] {
+        let mut db = MockChainDB::new();
+        for (height, header) in [(0, start_header), (2015, end_header)] {
+            db.store_header(
+                height,
+                StoredBlockHeader {
+                    header,
+                    work: Uint256::default(),
+                },
+            )
+            .unwrap();
+        }
+        let mut header_chain = HeaderChain::new(db, network);
+        let mut next_header = block_header(
+            1,
+            &end_header.block_hash().to_string(),
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            time,
+            0,
+            0,
+        );
+        next_header.bits = BlockHeader::compact_target_from_u256(
+            &header_chain
+                .required_target(2015, &end_header, &next_header)
+                .unwrap(),
+        );
+        assert_eq!(
+            header_chain
+                .store_headers(2016, vec![next_header], time)
+                .unwrap_err(),
+            expected
+        );
+    }
+}
+
+// headers of testnet4 fetched by tests/testdata/fetch.sh,
+// from the height on the first line, which is at difficulty adjustment height.
+fn testnet4_headers() -> (u32, Vec<BlockHeader>) {
+    let text = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/testdata/testnet4/headers.txt"
+    ))
+    .expect("fetch the fixture with tests/testdata/fetch.sh");
+    let mut lines = text.lines();
+    let start_height = lines.next().unwrap().trim().parse().unwrap();
+    let headers = lines
+        .map(|line| {
+            bitcoin::consensus::encode::deserialize(&hex::decode(line.trim()).unwrap()).unwrap()
+        })
+        .collect();
+    (start_height, headers)
+}
+
+#[test]
+#[ignore = "needs the testnet4 headers fetched by tests/testdata/fetch.sh"]
+fn test_store_headers_testnet4_fixture() {
+    let network = Network::Testnet4;
+    let (start_height, headers) = testnet4_headers();
+    let now = headers.last().unwrap().time;
+    assert_eq!(start_height % 2016, 0);
+    // the headers cross a retarget
+    assert!(headers.len() > 2016);
+    // and have a 20-minute reset in the period above the minimum difficulty
+    assert_ne!(headers[0].target(), max_target(network));
+    let reset = (1..2016)
+        .find(|i| {
+            headers[*i].time > headers[i - 1].time + 1200
+                && headers[*i].target() == max_target(network)
+        })
+        .expect("no 20-minute reset in the fixture");
+
+    let mut header_chain = HeaderChain::new(MockChainDB::new(), network);
+    header_chain
+        .init_to_header(start_height, headers[0], now)
+        .unwrap();
+    let tip_height = start_height + headers.len() as u32 - 1;
+    header_chain
+        .store_headers(tip_height, headers[1..].to_vec(), now)
+        .unwrap();
+    assert_eq!(
+        header_chain.tip().unwrap().unwrap().header,
+        *headers.last().unwrap()
+    );
+
+    // tampered copies are rejected
+    let store_tampered = |index: usize, header: BlockHeader| {
+        let mut header_chain = HeaderChain::new(MockChainDB::new(), network);
+        header_chain
+            .init_to_header(start_height, headers[0], now)
+            .unwrap();
+        let mut tampered = headers[1..=index].to_vec();
+        tampered[index - 1] = header;
+        header_chain
+            .store_headers(start_height + index as u32, tampered, now)
+            .unwrap_err()
+    };
+    // the reset needs more than 20 minutes from the previous block
+    let mut header = headers[reset];
+    header.time = headers[reset - 1].time + 1200;
+    assert_eq!(store_tampered(reset, header), Error::InvalidTarget);
+    // the retarget follows the timespan of the period
+    let mut header = headers[2016];
+    header.bits = BlockHeader::compact_target_from_u256(&(header.target() >> 1));
+    assert_eq!(store_tampered(2016, header), Error::InvalidTarget);
+    // the first block of the period can not go back more than MAX_TIMEWARP
+    let mut header = headers[2016];
+    header.time = headers[2015].time - MAX_TIMEWARP - 1;
+    assert_eq!(store_tampered(2016, header), Error::TimewarpAttack);
+}
+
+#[test]
+fn test_store_headers_signet() {
+    let db = MockChainDB::new();
+    let mut header_chain = HeaderChain::new(db, Network::Signet);
+    header_chain.init_to_genesis().unwrap();
+    let genesis = genesis_block(Network::Signet);
+    let header = block_header(
+        1,
+        &genesis.header.block_hash().to_string(),
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        genesis.header.time + 600,
+        genesis.header.bits,
+        0,
+    );
+
+    // CASE: header without block solution
+    assert_eq!(
+        header_chain
+            .store_headers(1, vec![header], header.time)
+            .unwrap_err(),
+        Error::SignetSolutionRequired
+    );
+
+    // CASE: coinbase tx is not in the block
+    let signet_header = SignetBlockHeader {
+        header,
+        coinbase_tx: genesis.txdata[0].clone(),
+        coinbase_proof: crate::merkle_proof::MerkleProof {
+            prefix: vec![],
+            siblings: vec![genesis.header.merkle_root],
+        },
+    };
+    assert_eq!(
+        header_chain
+            .store_signet_headers(
+                1,
+                vec![signet_header.clone()],
+                &signet::default_signet_challenge(),
+                header.time
+            )
+            .unwrap_err(),
+        Error::Signet(signet::Error::InvalidCoinbaseProof)
+    );
+
+    // CASE: not signet
+    let mut header_chain = HeaderChain::new(MockChainDB::new(), Network::Testnet);
+    header_chain.init_to_genesis().unwrap();
+    assert_eq!(
+        header_chain
+            .store_signet_headers(
+                1,
+                vec![signet_header],
+                &signet::default_signet_challenge(),
+                header.time
+            )
+            .unwrap_err(),
+        Error::NotSignet
+    );
+}
+
+#[test]
+fn test_add_checkpoint() {
+    let network = Network::Regtest;
+    let mut header_chain = HeaderChain::new(MockChainDB::new(), network);
+    header_chain.init_to_genesis().unwrap();
+    let genesis = header_chain.tip().unwrap().unwrap();
+    let target = genesis.header.target();
+    let abandoned = random_chain::random_header(
+        genesis.header.block_hash(),
+        genesis.header.time + 600,
+        target,
+    );
+    header_chain
+        .store_headers(1, vec![abandoned], abandoned.time)
+        .unwrap();
+    let tip_work = header_chain.tip().unwrap().unwrap().work;
+    let time = genesis.header.time + 4032 * 600;
+    let checkpoint = random_chain::random_header(BlockHash::default(), time, target);
+    assert_eq!(
+        header_chain
+            .add_checkpoint(4033, checkpoint, time)
+            .unwrap_err(),
+        Error::MustInitializeWithDiffchangeHeight
+    );
+    header_chain.add_checkpoint(4032, checkpoint, time).unwrap();
+    let tip = header_chain.tip().unwrap().unwrap();
+    assert_eq!(tip.header, checkpoint);
+    assert_eq!(tip.work, tip_work + checkpoint.work().mul_u32(4031));
+    assert_eq!(header_chain.oldest_height().unwrap(), Some(4032));
+    assert_eq!(header_chain.header_at(2016).unwrap_err(), Error::Pruned);
+    // the headers below the checkpoint are no longer provable
+    assert_eq!(header_chain.header_at(1).unwrap_err(), Error::Pruned);
+    assert_eq!(header_chain.header_at(0).unwrap_err(), Error::Pruned);
+    assert_eq!(
+        header_chain.height_of(&abandoned.block_hash()).unwrap(),
+        None
+    );
+
+    // the chain never goes back
+    assert_eq!(
+        header_chain
+            .add_checkpoint(4032, checkpoint, time)
+            .unwrap_err(),
+        Error::CheckpointBehindTip
+    );
+    assert_eq!(
+        header_chain
+            .add_checkpoint(2016, checkpoint, time)
+            .unwrap_err(),
+        Error::CheckpointBehindTip
+    );
+
+    // the checkpoint can not be replaced
+    let fork = random_chain::random_header(BlockHash::default(), time, target);
+    assert_eq!(
+        header_chain
+            .store_headers(4032, vec![fork], time)
+            .unwrap_err(),
+        Error::Pruned
+    );
+
+    // headers are extended from the checkpoint
+    let next_header = random_chain::random_header(checkpoint.block_hash(), time + 600, target);
+    header_chain
+        .store_headers(4033, vec![next_header], time + 600)
+        .unwrap();
+    assert_eq!(
+        header_chain.tip().unwrap().unwrap(),
+        StoredBlockHeader {
+            header: next_header,
+            work: tip.work + next_header.work()
+        }
+    );
+}
+
+#[test]
+fn test_add_checkpoint_target_out_of_retarget_range() {
+    let max = max_target(Network::Bitcoin);
+    let target = max >> 8;
+    let start_header = block_header(
+        1,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        1000000,
+        BlockHeader::compact_target_from_u256(&target),
+        0,
+    );
+    // target check comes before proof of work check
+    for (height, checkpoint_target, expected) in [
+        // 4 times easier at most on a retarget
+        (2016, target.mul_u32(4), Error::BadProofOfWork),
+        (2016, target.mul_u32(8), Error::InvalidTarget),
+        (4032, target.mul_u32(16), Error::BadProofOfWork),
+        // never easier than the max target
+        (2016 * 10, max, Error::BadProofOfWork),
+        (2016 * 10, max.mul_u32(2), Error::InvalidTarget),
+    ] {
+        let mut db = MockChainDB::new();
+        db.store_header(
+            0,
+            StoredBlockHeader {
+                header: start_header,
+                work: start_header.work(),
+            },
+        )
+        .unwrap();
+        let mut header_chain = HeaderChain::new(db, Network::Bitcoin);
+        let time = start_header.time + height * 600;
+        let checkpoint = block_header(
+            1,
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            time,
+            BlockHeader::compact_target_from_u256(&checkpoint_target),
+            0,
+        );
+        assert_eq!(
+            header_chain
+                .add_checkpoint(height, checkpoint, time)
+                .unwrap_err(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_store_compact_pow_proof() {
+    let network = Network::Regtest;
+    let mut header_chain = HeaderChain::new(MockChainDB::new(), network);
+    header_chain.init_to_genesis().unwrap();
+    let genesis = header_chain.tip().unwrap().unwrap();
+    let target = genesis.header.target();
+    let mut headers = Vec::with_capacity(2016);
+    let mut prev_header = genesis.header;
+    for _ in 0..2016 {
+        let header =
+            random_chain::random_header(prev_header.block_hash(), prev_header.time + 600, target);
+        headers.push(header);
+        prev_header = header;
+    }
+    let now = prev_header.time;
+    let seed = [1; 32];
+    let proof = CompactPowProof::build(
+        &headers,
+        &compact_pow::sample_indexes(&seed, 2016, 16).unwrap(),
+    )
+    .unwrap();
+
+    // sample count is different from the proof
+    assert_eq!(
+        header_chain
+            .store_compact_pow_proof(2016, &proof, &seed, 8, &target, now)
+            .unwrap_err(),
+        Error::CompactPow(compact_pow::Error::UnmatchedSampleCount)
+    );
+    header_chain
+        .store_compact_pow_proof(2016, &proof, &seed, 16, &target, now)
+        .unwrap();
+    assert_eq!(
+        header_chain.tip().unwrap().unwrap(),
+        StoredBlockHeader {
+            header: prev_header,
+            work: genesis.work + prev_header.work().mul_u32(2016),
+        }
+    );
+    assert_eq!(
+        header_chain
+            .store_compact_pow_proof(2016, &proof, &seed, 16, &target, now)
+            .unwrap_err(),
+        Error::CheckpointBehindTip
+    );
+}
+
+#[test]
+fn test_reorg_regtest_chain() {
+    use crate::regtest::RegtestChain;
+    let mut header_chain = HeaderChain::new(MockChainDB::new(), Network::Regtest);
+    header_chain.init_to_genesis().unwrap();
+    let mut chain = RegtestChain::new();
+    chain.mine_empty_blocks(6);
+    let now = chain.tip().header.time;
+    let stored = header_chain
+        .store_headers(6, chain.headers_from(1), now)
+        .unwrap();
+    assert_eq!(stored.accepted, 6);
+    assert_eq!(
+        header_chain.tip().unwrap().unwrap().header,
+        chain.tip().header
+    );
+
+    let mut fork = chain.fork_at(1);
+    fork.mine_empty_blocks(7);
+    let now = fork.tip().header.time;
+    let stored = header_chain
+        .store_headers(8, fork.headers_from(2), now)
+        .unwrap();
+    assert_eq!(stored.accepted, 7);
+    assert_eq!(
+        header_chain.tip().unwrap().unwrap().header,
+        fork.tip().header
+    );
+    assert_eq!(
+        header_chain.header_at(2).unwrap().unwrap().header,
+        fork.header_at(2)
+    );
+}
@@ -1,2 +1,8 @@
+pub mod coinbase_proof;
+pub mod compact_pow;
 pub mod header_chain;
 pub mod merkle_proof;
+#[cfg(any(test, feature = "test-support"))]
+pub mod regtest;
+pub mod signet;
+pub mod witness_proof;
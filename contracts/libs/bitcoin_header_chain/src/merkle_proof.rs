@@ -1,9 +1,13 @@
 use bitcoin::consensus::Encodable;
 use bitcoin::hash_types::TxMerkleNode;
 use bitcoin::hashes::Hash;
+use bitcoin::Transaction;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt;
 
+/// serialized length of tx without witness, which can not be distinguished from an inner node of merkle tree.
+pub const AMBIGUOUS_TX_LENGTH: usize = 64;
+
 #[derive(Debug)]
 pub enum Error {
     /// Std Io error
@@ -12,6 +16,18 @@ pub enum Error {
     NoSibling,
     // Invalid MerkleProof
     InvalidMerkleProof,
+    /// tx length is same as an inner node of merkle tree
+    AmbiguousTxLength,
+    /// coinbase proof is not for the first leaf of merkle tree
+    NotCoinbaseProof,
+    /// tx given as the coinbase is not a coinbase tx
+    NotCoinbaseTx,
+    /// leaf of coinbase proof is not the txid of the coinbase tx
+    UnmatchedCoinbaseTx,
+    /// depth of merkle proof is different from the coinbase proof
+    UnmatchedDepth,
+    /// merkle root of merkle proof is different from the coinbase proof
+    UnmatchedMerkleRoot,
 }
 
 impl fmt::Display for Error {
@@ -20,6 +36,12 @@ impl fmt::Display for Error {
             Error::NoSibling => write!(f, "no sibling"),
             Error::InvalidMerkleProof => write!(f, "invalid merkle path"),
             Error::StdIoError(ref e) => write!(f, "std io error {}", e),
+            Error::AmbiguousTxLength => write!(f, "ambiguous tx length"),
+            Error::NotCoinbaseProof => write!(f, "not coinbase proof"),
+            Error::NotCoinbaseTx => write!(f, "not coinbase tx"),
+            Error::UnmatchedCoinbaseTx => write!(f, "unmatched coinbase tx with coinbase proof"),
+            Error::UnmatchedDepth => write!(f, "unmatched depth with coinbase proof"),
+            Error::UnmatchedMerkleRoot => write!(f, "unmatched merkle root with coinbase proof"),
         }
     }
 }
@@ -60,6 +82,59 @@ impl MerkleProof {
         }
         Ok(current)
     }
+
+    /// depth of the leaf in merkle tree.
+    pub fn depth(&self) -> usize {
+        self.prefix.len()
+    }
+
+    /// returns true if the leaf is the first one of merkle tree, which is the coinbase tx.
+    pub fn is_coinbase_proof(&self) -> bool {
+        self.prefix.iter().all(|prefix| !prefix)
+    }
+
+    /// verifies that the leaf is at the same depth as the coinbase proof of the same merkle tree.
+    ///
+    /// All the leaves of bitcoin merkle tree are at the same depth.
+    /// The coinbase txid can not be an inner node unless the coinbase tx is 64 bytes,
+    /// so the coinbase proof fixes the depth of the tree.
+    /// It prevents to prove an inner node as a leaf, or a leaf as an inner node.
+    /// The leaf of the coinbase proof must be the txid of the coinbase tx,
+    /// otherwise any node at the left edge of the tree would pass as the coinbase.
+    pub fn verify_depth(
+        &self,
+        coinbase_tx: &Transaction,
+        coinbase_proof: &MerkleProof,
+    ) -> Result<(), Error> {
+        if !coinbase_tx.is_coin_base() {
+            return Err(Error::NotCoinbaseTx);
+        }
+        validate_tx_length(coinbase_tx)?;
+        if coinbase_proof.leaf()?.as_hash() != coinbase_tx.txid().as_hash() {
+            return Err(Error::UnmatchedCoinbaseTx);
+        }
+        if !coinbase_proof.is_coinbase_proof() {
+            return Err(Error::NotCoinbaseProof);
+        }
+        if self.depth() != coinbase_proof.depth() {
+            return Err(Error::UnmatchedDepth);
+        }
+        if self.merkle_root()? != coinbase_proof.merkle_root()? {
+            return Err(Error::UnmatchedMerkleRoot);
+        }
+        Ok(())
+    }
+}
+
+/// validates that the tx can not be confused with an inner node of merkle tree.
+///
+/// Inner node of merkle tree is the hash of 64 bytes.
+/// If the serialized tx without witness is 64 bytes, its txid may be an inner node.
+pub fn validate_tx_length(tx: &Transaction) -> Result<(), Error> {
+    if tx.get_strippedsize() == AMBIGUOUS_TX_LENGTH {
+        return Err(Error::AmbiguousTxLength);
+    }
+    Ok(())
 }
 
 fn step(left: &TxMerkleNode, right: &TxMerkleNode) -> Result<TxMerkleNode, Error> {
@@ -336,6 +411,166 @@ mod test {
         }
     }
 
+    #[test]
+    fn coinbase_proof() {
+        use crate::regtest::RegtestChain;
+        use bitcoin::blockdata::transaction::TxOut;
+        let mut chain = RegtestChain::new();
+        for tx_count in 1..7 {
+            let txs = (0..tx_count - 1)
+                .map(|value| Transaction {
+                    version: 1,
+                    lock_time: 0,
+                    input: vec![],
+                    output: vec![TxOut {
+                        value,
+                        script_pubkey: Default::default(),
+                    }],
+                })
+                .collect();
+            let block = chain.mine_block(txs).clone();
+            let coinbase_tx = &block.txdata[0];
+            let coinbase_proof = crate::regtest::merkle_proof(&block, 0);
+            assert!(coinbase_proof.is_coinbase_proof());
+            for index in 0..tx_count as usize {
+                let merkle_proof = crate::regtest::merkle_proof(&block, index);
+                merkle_proof
+                    .verify_depth(coinbase_tx, &coinbase_proof)
+                    .unwrap();
+                if index == 0 {
+                    continue;
+                }
+                assert!(!merkle_proof.is_coinbase_proof());
+                // not a coinbase tx
+                assert!(matches!(
+                    coinbase_proof
+                        .verify_depth(&block.txdata[index], &merkle_proof)
+                        .unwrap_err(),
+                    Error::NotCoinbaseTx
+                ));
+                // the coinbase tx of the other block
+                let other_coinbase_tx = &chain.block_at(1).txdata[0];
+                assert!(matches!(
+                    merkle_proof
+                        .verify_depth(other_coinbase_tx, &coinbase_proof)
+                        .unwrap_err(),
+                    Error::UnmatchedCoinbaseTx
+                ));
+                // the coinbase txid at the other position
+                let mut not_coinbase_proof = merkle_proof.clone();
+                not_coinbase_proof.siblings[0] = coinbase_proof.siblings[0];
+                assert!(matches!(
+                    merkle_proof
+                        .verify_depth(coinbase_tx, &not_coinbase_proof)
+                        .unwrap_err(),
+                    Error::NotCoinbaseProof
+                ));
+            }
+        }
+    }
+
+    // 64 bytes tx, whose serialization is two 32 bytes hashes.
+    fn ambiguous_tx() -> Transaction {
+        use bitcoin::blockdata::script::Script;
+        use bitcoin::blockdata::transaction::{OutPoint, TxIn, TxOut};
+        Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::default(),
+                script_sig: Script::new(),
+                sequence: 0xffffffff,
+                witness: vec![],
+            }],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: Script::from(vec![0x51, 0x51, 0x51, 0x51]),
+            }],
+        }
+    }
+
+    #[test]
+    fn validate_ambiguous_tx_length() {
+        let mut tx = ambiguous_tx();
+        assert_eq!(
+            bitcoin::consensus::encode::serialize(&tx).len(),
+            AMBIGUOUS_TX_LENGTH
+        );
+        assert!(matches!(
+            validate_tx_length(&tx).unwrap_err(),
+            Error::AmbiguousTxLength
+        ));
+        // witness is not counted
+        tx.input[0].witness = vec![vec![0; 10]];
+        assert!(matches!(
+            validate_tx_length(&tx).unwrap_err(),
+            Error::AmbiguousTxLength
+        ));
+        tx.output[0].script_pubkey = bitcoin::blockdata::script::Script::from(vec![0x51; 5]);
+        validate_tx_length(&tx).unwrap();
+    }
+
+    #[test]
+    fn inner_node_as_tx_attack() {
+        use crate::regtest::coinbase_tx;
+        // the tx bytes are the children of an inner node.
+        let tx = ambiguous_tx();
+        let bytes = bitcoin::consensus::encode::serialize(&tx);
+        let left = TxMerkleNode::from_slice(&bytes[..32]).unwrap();
+        let right = TxMerkleNode::from_slice(&bytes[32..]).unwrap();
+        // merkle tree of 4 leaves. the last 2 leaves are the children.
+        let coinbase_tx = coinbase_tx(1, 0);
+        let leaves = [
+            TxMerkleNode::from_hash(coinbase_tx.txid().as_hash()),
+            tx_merkle_node("7af61424f4f131892d6e972bec2d599b84affeafd958a7e7a8530aa0f1004790"),
+            left,
+            right,
+        ];
+        let inner_left = step(&leaves[0], &leaves[1]).unwrap();
+        let inner_right = step(&leaves[2], &leaves[3]).unwrap();
+        let merkle_root = step(&inner_left, &inner_right).unwrap();
+        assert_eq!(inner_right.as_hash(), tx.txid().as_hash());
+
+        // the inner node is proven as the tx without the checks.
+        let attack_proof = MerkleProof {
+            prefix: vec![true],
+            siblings: vec![TxMerkleNode::from_hash(tx.txid().as_hash()), inner_left],
+        };
+        assert_eq!(attack_proof.merkle_root().unwrap(), merkle_root);
+
+        // the tx length is rejected.
+        assert!(matches!(
+            validate_tx_length(&tx).unwrap_err(),
+            Error::AmbiguousTxLength
+        ));
+        // the depth is rejected by the coinbase proof.
+        let coinbase_proof = MerkleProof {
+            prefix: vec![false, false],
+            siblings: vec![leaves[0], leaves[1], inner_right],
+        };
+        assert_eq!(coinbase_proof.merkle_root().unwrap(), merkle_root);
+        assert!(matches!(
+            attack_proof
+                .verify_depth(&coinbase_tx, &coinbase_proof)
+                .unwrap_err(),
+            Error::UnmatchedDepth
+        ));
+        // the inner node at the left edge passes as the coinbase proof of the same depth,
+        // unless it is checked against the coinbase tx.
+        let inner_coinbase_proof = MerkleProof {
+            prefix: vec![false],
+            siblings: vec![inner_left, inner_right],
+        };
+        assert!(inner_coinbase_proof.is_coinbase_proof());
+        assert_eq!(inner_coinbase_proof.depth(), attack_proof.depth());
+        assert!(matches!(
+            attack_proof
+                .verify_depth(&coinbase_tx, &inner_coinbase_proof)
+                .unwrap_err(),
+            Error::UnmatchedCoinbaseTx
+        ));
+    }
+
     #[test]
     fn no_sibling_path() {
         let merkle_proof = MerkleProof {
@@ -0,0 +1,226 @@
+//! Regtest block factory for tests.
+//!
+//! It mines valid regtest blocks in-process by grinding the nonce at the minimum difficulty,
+//! and builds forks at chosen heights.
+use crate::header_chain::is_valid_proof_of_work_hash;
+use crate::merkle_proof::MerkleProof;
+use bitcoin::blockdata::constants::genesis_block;
+use bitcoin::blockdata::opcodes;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::hashes::{Hash, HashEngine};
+use bitcoin::{Block, BlockHeader, Network, OutPoint, Transaction, TxIn, TxOut};
+
+/// seconds between mined blocks.
+pub const BLOCK_INTERVAL: u32 = 600;
+/// block subsidy of regtest blocks under 150 height.
+pub const SUBSIDY: u64 = 50 * 100_000_000;
+
+/// grinds the nonce until the header has valid proof of work.
+pub fn mine(mut header: BlockHeader) -> BlockHeader {
+    let target = header.target();
+    while !is_valid_proof_of_work_hash(&target, &header.block_hash()) {
+        header.nonce += 1;
+    }
+    header
+}
+
+/// coinbase tx committing to the height (BIP34), pushed the same way as bitcoind.
+/// `tag` is put in the script sig to distinguish the coinbase of forks.
+pub fn coinbase_tx(height: u32, tag: u32) -> Transaction {
+    Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: Builder::new()
+                .push_int(height as i64)
+                .push_slice(&tag.to_le_bytes())
+                .into_script(),
+            sequence: 0xffffffff,
+            witness: vec![],
+        }],
+        output: vec![TxOut {
+            value: SUBSIDY,
+            script_pubkey: Builder::new().push_opcode(opcodes::OP_TRUE).into_script(),
+        }],
+    }
+}
+
+/// merkle proof of the tx at the index in the block.
+pub fn merkle_proof(block: &Block, index: usize) -> MerkleProof {
+    let mut level: Vec<TxMerkleNode> = block
+        .txdata
+        .iter()
+        .map(|tx| TxMerkleNode::from_hash(tx.txid().as_hash()))
+        .collect();
+    let mut prefix = vec![];
+    let mut siblings = vec![level[index]];
+    let mut index = index;
+    while level.len() > 1 {
+        prefix.push(index % 2 == 1);
+        siblings.push(level[(index ^ 1).min(level.len() - 1)]);
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut engine = TxMerkleNode::engine();
+                engine.input(&pair[0][..]);
+                engine.input(&pair[pair.len() - 1][..]);
+                TxMerkleNode::from_engine(engine)
+            })
+            .collect();
+        index /= 2;
+    }
+    MerkleProof { prefix, siblings }
+}
+
+/// chain of regtest blocks from the genesis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegtestChain {
+    /// blocks indexed by height.
+    pub blocks: Vec<Block>,
+    tag: u32,
+}
+
+impl Default for RegtestChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegtestChain {
+    pub fn new() -> Self {
+        Self {
+            blocks: vec![genesis_block(Network::Regtest)],
+            tag: 0,
+        }
+    }
+
+    pub fn tip_height(&self) -> u32 {
+        self.blocks.len() as u32 - 1
+    }
+
+    pub fn tip(&self) -> &Block {
+        self.blocks.last().unwrap()
+    }
+
+    pub fn block_at(&self, height: u32) -> &Block {
+        &self.blocks[height as usize]
+    }
+
+    pub fn header_at(&self, height: u32) -> BlockHeader {
+        self.blocks[height as usize].header
+    }
+
+    /// headers from the height to the tip.
+    pub fn headers_from(&self, height: u32) -> Vec<BlockHeader> {
+        self.blocks[height as usize..]
+            .iter()
+            .map(|block| block.header)
+            .collect()
+    }
+
+    /// mines a block with the txs following the coinbase tx.
+    pub fn mine_block(&mut self, txs: Vec<Transaction>) -> &Block {
+        let time = self.tip().header.time + BLOCK_INTERVAL;
+        self.mine_block_with_time(txs, time)
+    }
+
+    /// mines a block with the txs and the timestamp.
+    pub fn mine_block_with_time(&mut self, txs: Vec<Transaction>, time: u32) -> &Block {
+        let prev = self.tip().header;
+        let mut txdata = vec![coinbase_tx(self.tip_height() + 1, self.tag)];
+        txdata.extend(txs);
+        let mut block = Block {
+            header: BlockHeader {
+                version: 0x20000000,
+                prev_blockhash: prev.block_hash(),
+                merkle_root: Default::default(),
+                time,
+                bits: prev.bits,
+                nonce: 0,
+            },
+            txdata,
+        };
+        block.header.merkle_root = block.merkle_root();
+        block.header = mine(block.header);
+        self.blocks.push(block);
+        self.tip()
+    }
+
+    /// mines empty blocks.
+    pub fn mine_empty_blocks(&mut self, count: u32) {
+        for _ in 0..count {
+            self.mine_block(vec![]);
+        }
+    }
+
+    /// returns a fork of the chain, whose tip is the block at the height.
+    /// blocks mined on the fork are different from the ones of the original chain.
+    pub fn fork_at(&self, height: u32) -> Self {
+        Self {
+            blocks: self.blocks[..=height as usize].to_vec(),
+            tag: self.tag + 1,
+        }
+    }
+
+    /// merkle proof of the tx at the index in the block at the height.
+    pub fn merkle_proof(&self, height: u32, index: usize) -> MerkleProof {
+        merkle_proof(self.block_at(height), index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin::blockdata::script::Script;
+
+    #[test]
+    fn test_mine_block() {
+        let mut chain = RegtestChain::new();
+        let tx = Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: Script::new(),
+            }],
+        };
+        for _ in 0..3 {
+            chain.mine_block(vec![tx.clone(), tx.clone()]);
+        }
+        assert_eq!(chain.tip_height(), 3);
+        for height in 1..=3 {
+            let block = chain.block_at(height);
+            assert_eq!(
+                block.header.prev_blockhash,
+                chain.header_at(height - 1).block_hash()
+            );
+            assert!(block.check_merkle_root());
+            for index in 0..3 {
+                let proof = chain.merkle_proof(height, index);
+                assert_eq!(proof.merkle_root().unwrap(), block.header.merkle_root);
+                assert_eq!(
+                    proof.leaf().unwrap().as_hash(),
+                    block.txdata[index].txid().as_hash()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fork_at() {
+        let mut chain = RegtestChain::new();
+        chain.mine_empty_blocks(3);
+        let mut fork = chain.fork_at(1);
+        fork.mine_empty_blocks(3);
+        assert_eq!(fork.tip_height(), 4);
+        assert_eq!(fork.header_at(1), chain.header_at(1));
+        assert_ne!(fork.header_at(2), chain.header_at(2));
+        assert_eq!(
+            fork.header_at(2).prev_blockhash,
+            chain.header_at(1).block_hash()
+        );
+    }
+}
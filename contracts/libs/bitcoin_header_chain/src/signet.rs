@@ -0,0 +1,590 @@
+use crate::merkle_proof::MerkleProof;
+use crate::witness_proof::witness_commitment_index;
+use bitcoin::blockdata::opcodes;
+use bitcoin::blockdata::script::{Builder, Instruction, Script};
+use bitcoin::consensus::encode::{deserialize, deserialize_partial, serialize};
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1;
+use bitcoin::{BlockHeader, OutPoint, Transaction, TxIn, TxOut};
+use std::fmt;
+
+/// header of the signet solution pushed in the witness commitment output (BIP325).
+pub const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+
+/// challenge of the default signet, 1-of-2 bare multisig.
+pub const DEFAULT_SIGNET_CHALLENGE: &str = "512103ad5e0edad18cb1f0fc0d28a3d4f1f3e445640337489abb10404f2d1e086be430210359ef5021964fe22d6f8e05b2463c9540ce96883fe3b278760f048f5189f2e6c452ae";
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// coinbase tx is not coinbase
+    NotCoinbaseTx,
+    /// coinbase proof is not the proof of the coinbase tx in the block
+    InvalidCoinbaseProof,
+    /// coinbase tx has no witness commitment output
+    NoWitnessCommitment,
+    /// signet solution can not be decoded
+    InvalidSolution,
+    /// challenge is not OP_TRUE nor bare multisig
+    UnsupportedChallenge,
+    /// solution does not satisfy the challenge
+    InvalidSignature,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NotCoinbaseTx => write!(f, "not coinbase tx"),
+            Error::InvalidCoinbaseProof => write!(f, "invalid coinbase proof"),
+            Error::NoWitnessCommitment => write!(f, "no witness commitment"),
+            Error::InvalidSolution => write!(f, "invalid signet solution"),
+            Error::UnsupportedChallenge => write!(f, "unsupported signet challenge"),
+            Error::InvalidSignature => write!(f, "invalid signet block signature"),
+        }
+    }
+}
+
+/// signet block header with the coinbase tx carrying the block solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignetBlockHeader {
+    pub header: BlockHeader,
+    pub coinbase_tx: Transaction,
+    /// merkle proof of the coinbase tx against the merkle root of the header.
+    pub coinbase_proof: MerkleProof,
+}
+
+/// returns the challenge of the default signet.
+pub fn default_signet_challenge() -> Script {
+    Script::from(Vec::from_hex(DEFAULT_SIGNET_CHALLENGE).unwrap())
+}
+
+/// validates the block solution of the signet header against the challenge, following BIP325.
+///
+/// Only OP_TRUE and bare multisig challenges are supported, which covers the default signet
+/// and the signets created with the default tooling of bitcoin core.
+/// The solution must be push only, and the dummy element of multisig must be empty.
+pub fn validate_block_solution(challenge: &Script, block: &SignetBlockHeader) -> Result<(), Error> {
+    let coinbase_tx = &block.coinbase_tx;
+    if !coinbase_tx.is_coin_base() {
+        return Err(Error::NotCoinbaseTx);
+    }
+    let coinbase_proof = &block.coinbase_proof;
+    match (coinbase_proof.leaf(), coinbase_proof.merkle_root()) {
+        (Ok(leaf), Ok(merkle_root))
+            if coinbase_proof.is_coinbase_proof()
+                && leaf.as_hash() == coinbase_tx.txid().as_hash()
+                && merkle_root == block.header.merkle_root => {}
+        _ => return Err(Error::InvalidCoinbaseProof),
+    }
+
+    // remove the solution from the coinbase tx
+    let index = witness_commitment_index(coinbase_tx).ok_or(Error::NoWitnessCommitment)?;
+    let mut modified_coinbase_tx = coinbase_tx.clone();
+    let (script_pubkey, solution) =
+        fetch_and_clear_solution(&coinbase_tx.output[index].script_pubkey);
+    modified_coinbase_tx.output[index].script_pubkey = script_pubkey;
+    let (script_sig, witness) = match solution {
+        Some(solution) => decode_solution(&solution)?,
+        // no solution, which is allowed for OP_TRUE challenge
+        None => (Script::new(), vec![]),
+    };
+
+    // merkle root of the block with the modified coinbase tx
+    let mut modified_proof = coinbase_proof.clone();
+    modified_proof.siblings[0] = TxMerkleNode::from_hash(modified_coinbase_tx.txid().as_hash());
+    let signet_merkle_root = modified_proof
+        .merkle_root()
+        .map_err(|_| Error::InvalidCoinbaseProof)?;
+
+    let to_spend = to_spend_tx(challenge, &block.header, &signet_merkle_root);
+    let spending = Transaction {
+        version: 0,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: to_spend.txid(),
+                vout: 0,
+            },
+            script_sig,
+            sequence: 0,
+            witness,
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: Builder::new()
+                .push_opcode(opcodes::all::OP_RETURN)
+                .into_script(),
+        }],
+    };
+    verify_challenge(challenge, &spending)
+}
+
+fn to_spend_tx(
+    challenge: &Script,
+    header: &BlockHeader,
+    signet_merkle_root: &TxMerkleNode,
+) -> Transaction {
+    let mut block_data = serialize(&header.version);
+    block_data.extend(serialize(&header.prev_blockhash));
+    block_data.extend(serialize(signet_merkle_root));
+    block_data.extend(serialize(&header.time));
+    Transaction {
+        version: 0,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: Builder::new()
+                .push_opcode(opcodes::all::OP_PUSHBYTES_0)
+                .push_slice(&block_data)
+                .into_script(),
+            sequence: 0,
+            witness: vec![],
+        }],
+        output: vec![TxOut {
+            value: 0,
+            script_pubkey: challenge.clone(),
+        }],
+    }
+}
+
+/// returns the witness commitment script without the solution, and the solution.
+/// the push of the solution is replaced with the push of the signet header.
+fn fetch_and_clear_solution(witness_commitment: &Script) -> (Script, Option<Vec<u8>>) {
+    let mut replacement = Builder::new();
+    let mut solution = None;
+    for instruction in witness_commitment.instructions() {
+        match instruction {
+            Ok(Instruction::PushBytes(data)) => {
+                if solution.is_none()
+                    && data.len() > SIGNET_HEADER.len()
+                    && data[..SIGNET_HEADER.len()] == SIGNET_HEADER
+                {
+                    solution = Some(data[SIGNET_HEADER.len()..].to_vec());
+                    replacement = replacement.push_slice(&SIGNET_HEADER);
+                } else {
+                    replacement = replacement.push_slice(data);
+                }
+            }
+            Ok(Instruction::Op(op)) => replacement = replacement.push_opcode(op),
+            // decoding of the script stops at the broken push
+            Err(_) => break,
+        }
+    }
+    match solution {
+        Some(solution) => (replacement.into_script(), Some(solution)),
+        None => (witness_commitment.clone(), None),
+    }
+}
+
+fn decode_solution(solution: &[u8]) -> Result<(Script, Vec<Vec<u8>>), Error> {
+    let (script_sig, consumed) =
+        deserialize_partial::<Script>(solution).map_err(|_| Error::InvalidSolution)?;
+    let witness =
+        deserialize::<Vec<Vec<u8>>>(&solution[consumed..]).map_err(|_| Error::InvalidSolution)?;
+    Ok((script_sig, witness))
+}
+
+fn verify_challenge(challenge: &Script, spending: &Transaction) -> Result<(), Error> {
+    let input = &spending.input[0];
+    if *challenge == Builder::new().push_opcode(opcodes::OP_TRUE).into_script() {
+        return if input.witness.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature)
+        };
+    }
+    let (required, pubkeys) = parse_multisig(challenge).ok_or(Error::UnsupportedChallenge)?;
+    // bare multisig does not take witness
+    if !input.witness.is_empty() {
+        return Err(Error::InvalidSignature);
+    }
+    let mut stack = vec![];
+    for instruction in input.script_sig.instructions() {
+        match instruction {
+            Ok(Instruction::PushBytes(data)) => stack.push(data),
+            _ => return Err(Error::InvalidSolution),
+        }
+    }
+    // dummy element and signatures are on top of the stack
+    if stack.len() < required + 1 {
+        return Err(Error::InvalidSignature);
+    }
+    let signatures = &stack[stack.len() - required..];
+    if !stack[stack.len() - required - 1].is_empty() {
+        return Err(Error::InvalidSignature);
+    }
+
+    // signatures must be in the same order as the public keys.
+    let mut signatures = signatures.iter().rev().peekable();
+    let mut pubkeys = pubkeys.iter().rev();
+    while let Some(signature) = signatures.peek() {
+        let pubkey = pubkeys.next().ok_or(Error::InvalidSignature)?;
+        if check_signature(challenge, spending, signature, pubkey)? {
+            signatures.next();
+        }
+    }
+    Ok(())
+}
+
+/// returns the number of required signatures and the public keys of bare multisig script.
+fn parse_multisig(script: &Script) -> Option<(usize, Vec<&[u8]>)> {
+    let instructions: Result<Vec<Instruction>, _> = script.instructions().collect();
+    let instructions = instructions.ok()?;
+    if instructions.len() < 4 {
+        return None;
+    }
+    let required = pushnum(&instructions[0])?;
+    let total = pushnum(&instructions[instructions.len() - 2])?;
+    if instructions[instructions.len() - 1] != Instruction::Op(opcodes::all::OP_CHECKMULTISIG) {
+        return None;
+    }
+    let pubkeys: Option<Vec<&[u8]>> = instructions[1..instructions.len() - 2]
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::PushBytes(data) => Some(*data),
+            _ => None,
+        })
+        .collect();
+    let pubkeys = pubkeys?;
+    if pubkeys.len() != total || required > total {
+        return None;
+    }
+    Some((required, pubkeys))
+}
+
+fn pushnum(instruction: &Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::Op(op) => {
+            let code = op.into_u8();
+            if code >= opcodes::all::OP_PUSHNUM_1.into_u8()
+                && code <= opcodes::all::OP_PUSHNUM_16.into_u8()
+            {
+                Some((code - opcodes::all::OP_PUSHNUM_1.into_u8() + 1) as usize)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// checks the signature in the same way as OP_CHECKMULTISIG with DERSIG rule.
+fn check_signature(
+    challenge: &Script,
+    spending: &Transaction,
+    signature: &[u8],
+    pubkey: &[u8],
+) -> Result<bool, Error> {
+    if signature.is_empty() {
+        return Ok(false);
+    }
+    let (der, sighash_type) = signature.split_at(signature.len() - 1);
+    let mut signature =
+        secp256k1::Signature::parse_der(der).map_err(|_| Error::InvalidSignature)?;
+    signature.normalize_s();
+    let pubkey = match secp256k1::PublicKey::parse_slice(pubkey, None) {
+        Ok(pubkey) => pubkey,
+        Err(_) => return Ok(false),
+    };
+    let sighash = spending.signature_hash(0, challenge, sighash_type[0] as u32);
+    let message = secp256k1::Message::parse(&sighash.into_inner());
+    Ok(secp256k1::verify(&message, &signature, &pubkey))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::witness_proof::WITNESS_COMMITMENT_HEADER;
+    use bitcoin::consensus::encode::Encodable;
+
+    fn secret_key(seed: u8) -> secp256k1::SecretKey {
+        secp256k1::SecretKey::parse(&[seed; 32]).unwrap()
+    }
+
+    fn multisig_challenge(required: i64, keys: &[secp256k1::SecretKey]) -> Script {
+        let mut builder = Builder::new().push_int(required);
+        for key in keys {
+            builder = builder
+                .push_slice(&secp256k1::PublicKey::from_secret_key(key).serialize_compressed());
+        }
+        builder
+            .push_int(keys.len() as i64)
+            .push_opcode(opcodes::all::OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    fn coinbase_tx(commitment_script: Script) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::from(vec![0x01, 0x64]),
+                sequence: 0xffffffff,
+                witness: vec![vec![0; 32]],
+            }],
+            output: vec![
+                TxOut {
+                    value: 5000000000,
+                    script_pubkey: Script::from(vec![0x51]),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: commitment_script,
+                },
+            ],
+        }
+    }
+
+    fn commitment_script(solution: Option<&[u8]>) -> Script {
+        let builder = Builder::new()
+            .push_opcode(opcodes::all::OP_RETURN)
+            .push_slice(&[WITNESS_COMMITMENT_HEADER[2..].to_vec(), vec![0x11; 32]].concat());
+        match solution {
+            Some(solution) => builder
+                .push_slice(&[SIGNET_HEADER.to_vec(), solution.to_vec()].concat())
+                .into_script(),
+            None => builder.into_script(),
+        }
+    }
+
+    fn encode_solution(script_sig: &Script, witness: &Vec<Vec<u8>>) -> Vec<u8> {
+        let mut solution = vec![];
+        script_sig.consensus_encode(&mut solution).unwrap();
+        witness.consensus_encode(&mut solution).unwrap();
+        solution
+    }
+
+    fn step(left: &TxMerkleNode, right: &TxMerkleNode) -> TxMerkleNode {
+        let mut data = left.into_inner().to_vec();
+        data.extend_from_slice(&right.into_inner());
+        TxMerkleNode::hash(&data)
+    }
+
+    // generates signet block header whose coinbase tx has the signatures of the keys.
+    fn gen_signet_block(challenge: &Script, keys: &[secp256k1::SecretKey]) -> SignetBlockHeader {
+        let sibling = TxMerkleNode::hash(&[0x22]);
+        let mut header = BlockHeader {
+            version: 0x20000000,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time: 1600000000,
+            bits: 0x1e0377ae,
+            nonce: 0,
+        };
+
+        // the block data to sign commits to the coinbase tx whose solution is cleared
+        let cleared_coinbase_tx = coinbase_tx(commitment_script(Some(&[])));
+        let signet_merkle_root = step(
+            &TxMerkleNode::from_hash(cleared_coinbase_tx.txid().as_hash()),
+            &sibling,
+        );
+        let to_spend = to_spend_tx(challenge, &header, &signet_merkle_root);
+        let mut spending = Transaction {
+            version: 0,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: to_spend.txid(),
+                    vout: 0,
+                },
+                script_sig: Script::new(),
+                sequence: 0,
+                witness: vec![],
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: Builder::new()
+                    .push_opcode(opcodes::all::OP_RETURN)
+                    .into_script(),
+            }],
+        };
+        let sighash = spending.signature_hash(0, challenge, 1);
+        let message = secp256k1::Message::parse(&sighash.into_inner());
+        let mut builder = Builder::new().push_opcode(opcodes::all::OP_PUSHBYTES_0);
+        for key in keys {
+            let (signature, _) = secp256k1::sign(&message, key);
+            let mut signature = signature.serialize_der().as_ref().to_vec();
+            signature.push(1);
+            builder = builder.push_slice(&signature);
+        }
+        spending.input[0].script_sig = builder.into_script();
+
+        let coinbase_tx = coinbase_tx(commitment_script(Some(&encode_solution(
+            &spending.input[0].script_sig,
+            &vec![],
+        ))));
+        let coinbase_txid = TxMerkleNode::from_hash(coinbase_tx.txid().as_hash());
+        header.merkle_root = step(&coinbase_txid, &sibling);
+        SignetBlockHeader {
+            header,
+            coinbase_tx,
+            coinbase_proof: MerkleProof {
+                prefix: vec![false],
+                siblings: vec![coinbase_txid, sibling],
+            },
+        }
+    }
+
+    #[test]
+    fn test_default_signet_challenge() {
+        let challenge = default_signet_challenge();
+        let (required, pubkeys) = parse_multisig(&challenge).unwrap();
+        assert_eq!(required, 1);
+        assert_eq!(pubkeys.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_block_solution() {
+        let keys = [secret_key(1), secret_key(2), secret_key(3)];
+        let challenge = multisig_challenge(2, &keys);
+        // CASE: first and second keys
+        let block = gen_signet_block(&challenge, &keys[..2]);
+        validate_block_solution(&challenge, &block).unwrap();
+        // CASE: first and third keys
+        let block = gen_signet_block(&challenge, &[keys[0], keys[2]]);
+        validate_block_solution(&challenge, &block).unwrap();
+    }
+
+    #[test]
+    fn test_validate_block_solution_invalid_signature() {
+        let keys = [secret_key(1), secret_key(2), secret_key(3)];
+        let challenge = multisig_challenge(2, &keys);
+        // CASE: not enough signatures
+        let block = gen_signet_block(&challenge, &keys[..1]);
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::InvalidSignature
+        );
+        // CASE: signatures in wrong order
+        let block = gen_signet_block(&challenge, &[keys[1], keys[0]]);
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::InvalidSignature
+        );
+        // CASE: signature of the other key
+        let block = gen_signet_block(&challenge, &[keys[0], secret_key(4)]);
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::InvalidSignature
+        );
+        // CASE: header is changed after signing
+        let mut block = gen_signet_block(&challenge, &keys[..2]);
+        block.header.time += 1;
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn test_validate_block_solution_invalid_coinbase() {
+        let keys = [secret_key(1)];
+        let challenge = multisig_challenge(1, &keys);
+        // CASE: coinbase proof does not match with the header
+        let mut block = gen_signet_block(&challenge, &keys);
+        block.header.merkle_root = TxMerkleNode::hash(&[0x33]);
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::InvalidCoinbaseProof
+        );
+        // CASE: no witness commitment
+        let mut block = gen_signet_block(&challenge, &keys);
+        block.coinbase_tx.output.pop();
+        let coinbase_txid = TxMerkleNode::from_hash(block.coinbase_tx.txid().as_hash());
+        block.coinbase_proof.siblings[0] = coinbase_txid;
+        block.header.merkle_root = block.coinbase_proof.merkle_root().unwrap();
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::NoWitnessCommitment
+        );
+    }
+
+    #[test]
+    fn test_validate_block_solution_op_true() {
+        let challenge = Builder::new().push_opcode(opcodes::OP_TRUE).into_script();
+        let mut block = gen_signet_block(&challenge, &[]);
+        block.coinbase_tx.output[1].script_pubkey = commitment_script(None);
+        let coinbase_txid = TxMerkleNode::from_hash(block.coinbase_tx.txid().as_hash());
+        block.coinbase_proof.siblings[0] = coinbase_txid;
+        block.header.merkle_root = block.coinbase_proof.merkle_root().unwrap();
+        validate_block_solution(&challenge, &block).unwrap();
+    }
+
+    #[test]
+    fn test_validate_block_solution_unsupported_challenge() {
+        let challenge = Builder::new()
+            .push_opcode(opcodes::all::OP_PUSHBYTES_0)
+            .push_slice(&[0x11; 20])
+            .into_script();
+        let block = gen_signet_block(&challenge, &[]);
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::UnsupportedChallenge
+        );
+    }
+
+    // block of the default signet fetched by tests/testdata/fetch.sh.
+    fn default_signet_block() -> SignetBlockHeader {
+        let hex = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/testdata/signet/block.hex"
+        ))
+        .expect("fetch the fixture with tests/testdata/fetch.sh");
+        let block: bitcoin::Block = deserialize(&Vec::from_hex(hex.trim()).unwrap()).unwrap();
+        SignetBlockHeader {
+            header: block.header,
+            coinbase_tx: block.txdata[0].clone(),
+            coinbase_proof: crate::regtest::merkle_proof(&block, 0),
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the default signet block fetched by tests/testdata/fetch.sh"]
+    fn test_validate_block_solution_default_signet() {
+        let challenge = default_signet_challenge();
+        let block = default_signet_block();
+        validate_block_solution(&challenge, &block).unwrap();
+
+        // the nonce is not signed, so that the block can be mined after signing
+        let mut tampered = block.clone();
+        tampered.header.nonce ^= 1;
+        validate_block_solution(&challenge, &tampered).unwrap();
+
+        // the version, the previous block hash and the time are signed
+        let mut tampered = block.clone();
+        tampered.header.version ^= 1;
+        assert_eq!(
+            validate_block_solution(&challenge, &tampered).unwrap_err(),
+            Error::InvalidSignature
+        );
+        let mut tampered = block.clone();
+        tampered.header.prev_blockhash = Default::default();
+        assert_eq!(
+            validate_block_solution(&challenge, &tampered).unwrap_err(),
+            Error::InvalidSignature
+        );
+        let mut tampered = block.clone();
+        tampered.header.time += 1;
+        assert_eq!(
+            validate_block_solution(&challenge, &tampered).unwrap_err(),
+            Error::InvalidSignature
+        );
+
+        // the coinbase tx is committed by the merkle root
+        let mut tampered = block.clone();
+        tampered.coinbase_tx.lock_time ^= 1;
+        assert_eq!(
+            validate_block_solution(&challenge, &tampered).unwrap_err(),
+            Error::InvalidCoinbaseProof
+        );
+
+        // the solution is signed by the keys of the challenge
+        let challenge = multisig_challenge(1, &[secret_key(1), secret_key(2)]);
+        assert_eq!(
+            validate_block_solution(&challenge, &block).unwrap_err(),
+            Error::InvalidSignature
+        );
+    }
+}
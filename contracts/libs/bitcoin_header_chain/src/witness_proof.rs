@@ -0,0 +1,333 @@
+use crate::merkle_proof::{self, MerkleProof};
+use bitcoin::hash_types::{TxMerkleNode, WitnessCommitment, WitnessMerkleNode, Wtxid};
+use bitcoin::hashes::Hash;
+use bitcoin::{Block, Transaction};
+use std::fmt;
+
+/// prefix of the script pubkey of the witness commitment output: OP_RETURN, push 36 bytes and the commitment header.
+pub const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+#[derive(Debug)]
+pub enum Error {
+    /// Merkle proof error
+    MerkleProof(merkle_proof::Error),
+    /// coinbase tx is not coinbase
+    NotCoinbaseTx,
+    /// coinbase proof is not the proof of the coinbase tx
+    NotCoinbaseProof,
+    /// coinbase tx has no witness commitment output
+    NoWitnessCommitment,
+    /// coinbase input witness is not a 32 bytes witness reserved value
+    InvalidWitnessReservedValue,
+    /// leaf of witness merkle proof is not the wtxid
+    UnmatchedWtxid,
+    /// depth of witness merkle proof is different from the coinbase proof
+    UnmatchedDepth,
+    /// witness commitment computed from witness merkle proof is different from the coinbase tx
+    UnmatchedWitnessCommitment,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MerkleProof(ref e) => write!(f, "merkle path error {}", e),
+            Error::NotCoinbaseTx => write!(f, "not coinbase tx"),
+            Error::NotCoinbaseProof => write!(f, "not coinbase proof"),
+            Error::NoWitnessCommitment => write!(f, "no witness commitment"),
+            Error::InvalidWitnessReservedValue => write!(f, "invalid witness reserved value"),
+            Error::UnmatchedWtxid => write!(f, "witness merkle path and wtxid does not match"),
+            Error::UnmatchedDepth => write!(f, "unmatched depth with coinbase proof"),
+            Error::UnmatchedWitnessCommitment => write!(f, "unmatched witness commitment"),
+        }
+    }
+}
+
+impl From<merkle_proof::Error> for Error {
+    fn from(e: merkle_proof::Error) -> Error {
+        Error::MerkleProof(e)
+    }
+}
+
+/// proof of wtxid inclusion in a block, following BIP141.
+///
+/// The coinbase tx is proven by txid against the merkle root of the block header,
+/// then the wtxid is proven against the witness commitment in the coinbase tx.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WitnessProof {
+    pub coinbase_tx: Transaction,
+    pub coinbase_proof: MerkleProof,
+    /// merkle proof of witness merkle tree, whose leaves are wtxids.
+    pub witness_proof: MerkleProof,
+}
+
+impl WitnessProof {
+    /// verifies that the wtxid is committed by the coinbase tx,
+    /// and returns the merkle root of the block which the coinbase tx belongs to.
+    ///
+    /// The returned merkle root must be checked against the block header by the caller.
+    pub fn verify(&self, wtxid: &Wtxid) -> Result<TxMerkleNode, Error> {
+        if !self.coinbase_tx.is_coin_base() {
+            return Err(Error::NotCoinbaseTx);
+        }
+        merkle_proof::validate_tx_length(&self.coinbase_tx)?;
+        if !self.coinbase_proof.is_coinbase_proof()
+            || self.coinbase_proof.leaf()?.as_hash() != self.coinbase_tx.txid().as_hash()
+        {
+            return Err(Error::NotCoinbaseProof);
+        }
+        if self.witness_proof.leaf()?.as_hash() != wtxid.as_hash() {
+            return Err(Error::UnmatchedWtxid);
+        }
+        // witness merkle tree has the same leaves count as the tx merkle tree.
+        if self.witness_proof.depth() != self.coinbase_proof.depth() {
+            return Err(Error::UnmatchedDepth);
+        }
+        let commitment = witness_commitment(&self.coinbase_tx)?;
+        let witness_reserved_value = witness_reserved_value(&self.coinbase_tx)?;
+        let witness_root =
+            WitnessMerkleNode::from_hash(self.witness_proof.merkle_root()?.as_hash());
+        if Block::compute_witness_commitment(&witness_root, witness_reserved_value) != commitment {
+            return Err(Error::UnmatchedWitnessCommitment);
+        }
+        Ok(self.coinbase_proof.merkle_root()?)
+    }
+}
+
+/// returns the witness commitment in the last output of the coinbase tx that starts with the commitment header.
+pub fn witness_commitment(coinbase_tx: &Transaction) -> Result<WitnessCommitment, Error> {
+    let index = witness_commitment_index(coinbase_tx).ok_or(Error::NoWitnessCommitment)?;
+    let script_pubkey = &coinbase_tx.output[index].script_pubkey;
+    Ok(WitnessCommitment::from_slice(&script_pubkey.as_bytes()[6..38]).unwrap())
+}
+
+/// returns the index of the last output of the coinbase tx that starts with the commitment header.
+pub fn witness_commitment_index(coinbase_tx: &Transaction) -> Option<usize> {
+    coinbase_tx.output.iter().rposition(|o| {
+        o.script_pubkey.len() >= 38 && o.script_pubkey[0..6] == WITNESS_COMMITMENT_HEADER[..]
+    })
+}
+
+fn witness_reserved_value(coinbase_tx: &Transaction) -> Result<&[u8], Error> {
+    let witness = &coinbase_tx.input[0].witness;
+    if witness.len() != 1 || witness[0].len() != 32 {
+        return Err(Error::InvalidWitnessReservedValue);
+    }
+    Ok(witness[0].as_slice())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin::blockdata::script::Script;
+    use bitcoin::hashes::HashEngine;
+    use bitcoin::{BlockHeader, OutPoint, TxIn, TxOut};
+
+    fn tx(witness: Vec<Vec<u8>>, value: u64) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Default::default(),
+                    vout: 0,
+                },
+                script_sig: Script::from(vec![0x01, 0x02]),
+                sequence: 0xffffffff,
+                witness,
+            }],
+            output: vec![TxOut {
+                value,
+                script_pubkey: Script::from(vec![0x00, 0x14, 0x01, 0x02, 0x03, 0x04]),
+            }],
+        }
+    }
+
+    fn step(left: &TxMerkleNode, right: &TxMerkleNode) -> TxMerkleNode {
+        let mut engine = TxMerkleNode::engine();
+        engine.input(&left[..]);
+        engine.input(&right[..]);
+        TxMerkleNode::from_engine(engine)
+    }
+
+    // generates merkle proof of the leaf at index.
+    fn gen_merkle_proof(leaves: Vec<TxMerkleNode>, index: usize) -> MerkleProof {
+        let mut prefix = vec![];
+        let mut siblings = vec![leaves[index]];
+        let mut level = leaves;
+        let mut index = index;
+        while level.len() > 1 {
+            if level.len() % 2 == 1 {
+                level.push(*level.last().unwrap());
+            }
+            let sibling = index ^ 1;
+            prefix.push(sibling < index);
+            siblings.push(level[sibling]);
+            level = level
+                .chunks(2)
+                .map(|pair| step(&pair[0], &pair[1]))
+                .collect();
+            index /= 2;
+        }
+        MerkleProof { prefix, siblings }
+    }
+
+    // generates block with coinbase tx committing witness of the txs.
+    fn gen_block(txs: Vec<Transaction>) -> Block {
+        let mut block = Block {
+            header: BlockHeader {
+                version: 1,
+                prev_blockhash: Default::default(),
+                merkle_root: Default::default(),
+                time: 0,
+                bits: 0,
+                nonce: 0,
+            },
+            txdata: vec![tx(vec![vec![0x11; 32]], 5000000000)],
+        };
+        block.txdata[0].input[0].previous_output = OutPoint::null();
+        block.txdata.extend(txs);
+        let commitment =
+            Block::compute_witness_commitment(&block.witness_root(), &[0x11; 32]).into_inner();
+        let mut script_pubkey = WITNESS_COMMITMENT_HEADER.to_vec();
+        script_pubkey.extend_from_slice(&commitment);
+        block.txdata[0].output.push(TxOut {
+            value: 0,
+            script_pubkey: Script::from(script_pubkey),
+        });
+        block.header.merkle_root = block.merkle_root();
+        assert!(block.check_witness_commitment());
+        block
+    }
+
+    fn gen_witness_proof(block: &Block, index: usize) -> WitnessProof {
+        let txids = block
+            .txdata
+            .iter()
+            .map(|tx| TxMerkleNode::from_hash(tx.txid().as_hash()))
+            .collect();
+        let wtxids = block
+            .txdata
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| {
+                if i == 0 {
+                    TxMerkleNode::default()
+                } else {
+                    TxMerkleNode::from_hash(tx.wtxid().as_hash())
+                }
+            })
+            .collect();
+        WitnessProof {
+            coinbase_tx: block.txdata[0].clone(),
+            coinbase_proof: gen_merkle_proof(txids, 0),
+            witness_proof: gen_merkle_proof(wtxids, index),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let block = gen_block(vec![
+            tx(vec![vec![0x01; 72], vec![0x02; 33]], 1),
+            tx(vec![], 2),
+            tx(vec![vec![0x03; 64]], 3),
+        ]);
+        for index in 1..block.txdata.len() {
+            let proof = gen_witness_proof(&block, index);
+            assert_eq!(
+                proof.verify(&block.txdata[index].wtxid()).unwrap(),
+                block.header.merkle_root
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_unmatched_wtxid() {
+        let block = gen_block(vec![
+            tx(vec![vec![0x01; 72]], 1),
+            tx(vec![vec![0x02; 72]], 2),
+        ]);
+        let proof = gen_witness_proof(&block, 1);
+        match proof.verify(&block.txdata[2].wtxid()).unwrap_err() {
+            Error::UnmatchedWtxid => {}
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn test_verify_unmatched_witness_commitment() {
+        let block = gen_block(vec![
+            tx(vec![vec![0x01; 72]], 1),
+            tx(vec![vec![0x02; 72]], 2),
+        ]);
+        // witness of the tx is replaced
+        let other_block = gen_block(vec![
+            tx(vec![vec![0x01; 72]], 1),
+            tx(vec![vec![0x03; 72]], 2),
+        ]);
+        let mut proof = gen_witness_proof(&other_block, 2);
+        proof.coinbase_tx = block.txdata[0].clone();
+        proof.coinbase_proof = gen_witness_proof(&block, 2).coinbase_proof;
+        match proof.verify(&other_block.txdata[2].wtxid()).unwrap_err() {
+            Error::UnmatchedWitnessCommitment => {}
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_coinbase() {
+        let block = gen_block(vec![
+            tx(vec![vec![0x01; 72]], 1),
+            tx(vec![vec![0x02; 72]], 2),
+        ]);
+        let wtxid = block.txdata[1].wtxid();
+
+        // not coinbase tx
+        let mut proof = gen_witness_proof(&block, 1);
+        proof.coinbase_tx = block.txdata[1].clone();
+        match proof.verify(&wtxid).unwrap_err() {
+            Error::NotCoinbaseTx => {}
+            e => panic!("unexpected error {}", e),
+        }
+
+        // coinbase proof of the other leaf
+        let mut proof = gen_witness_proof(&block, 1);
+        proof.coinbase_proof.prefix[0] = true;
+        match proof.verify(&wtxid).unwrap_err() {
+            Error::NotCoinbaseProof => {}
+            e => panic!("unexpected error {}", e),
+        }
+
+        // no witness commitment
+        let mut proof = gen_witness_proof(&block, 1);
+        proof.coinbase_tx.output.pop();
+        proof.coinbase_proof.siblings[0] =
+            TxMerkleNode::from_hash(proof.coinbase_tx.txid().as_hash());
+        match proof.verify(&wtxid).unwrap_err() {
+            Error::NoWitnessCommitment => {}
+            e => panic!("unexpected error {}", e),
+        }
+
+        // no witness reserved value
+        let mut proof = gen_witness_proof(&block, 1);
+        proof.coinbase_tx.input[0].witness = vec![];
+        match proof.verify(&wtxid).unwrap_err() {
+            Error::InvalidWitnessReservedValue => {}
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn test_verify_unmatched_depth() {
+        let block = gen_block(vec![
+            tx(vec![vec![0x01; 72]], 1),
+            tx(vec![vec![0x02; 72]], 2),
+        ]);
+        let mut proof = gen_witness_proof(&block, 1);
+        proof.witness_proof.prefix.pop();
+        proof.witness_proof.siblings.pop();
+        match proof.verify(&block.txdata[1].wtxid()).unwrap_err() {
+            Error::UnmatchedDepth => {}
+            e => panic!("unexpected error {}", e),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+#!/bin/sh
+# Fetches the signet and testnet4 fixtures of the header chain tests from bitcoin core nodes.
+# bitcoin-cli must reach a default signet node and a testnet4 node (bitcoin core 28 or later).
+#
+# usage: fetch.sh <signet height> <testnet4 start height>
+#
+# signet/block.hex: raw block of the default signet at <signet height>.
+# testnet4/headers.txt: the start height and the following 2100 raw headers of testnet4, one per line.
+# <testnet4 start height> must be at difficulty adjustment height, in a period above the minimum difficulty
+# which has 20-minute difficulty resets, so that the headers cross a retarget and the resets.
+set -eu
+cd "$(dirname "$0")"
+signet_height=$1
+testnet4_start=$2
+testnet4_count=2100
+
+mkdir -p signet testnet4
+bitcoin-cli -signet getblock "$(bitcoin-cli -signet getblockhash "$signet_height")" 0 >signet/block.hex
+{
+    echo "$testnet4_start"
+    height=$testnet4_start
+    while [ "$height" -lt $((testnet4_start + testnet4_count)) ]; do
+        bitcoin-cli -testnet4 getblockheader "$(bitcoin-cli -testnet4 getblockhash "$height")" false
+        height=$((height + 1))
+    done
+} >testnet4/headers.txt
@@ -0,0 +1,141 @@
+//! merkle proofs of bitcoin txs submitted to the bitcoin_spv contract and the gateway contract.
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::hash_types::{TxMerkleNode, Txid};
+use bitcoin::hashes::{Hash, HashEngine};
+use bitcoin::Block;
+use bitcoin_header_chain::merkle_proof::{
+    validate_tx_length, Error as MerkleProofError, MerkleProof,
+};
+use shared_types::bitcoin_spv::MerkleProofMsg;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    Encode(Box<bitcoin::consensus::encode::Error>),
+    MerkleProof(MerkleProofError),
+    TxNotFound(Txid),
+    IndexOutOfRange { index: usize, length: usize },
+    UnmatchedMerkleRoot,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Encode(e) => write!(f, "bitcoin encode error: {}", e),
+            Error::MerkleProof(e) => write!(f, "merkle proof error: {}", e),
+            Error::TxNotFound(txid) => write!(f, "tx {} not found in block", txid),
+            Error::IndexOutOfRange { index, length } => {
+                write!(f, "index out of range: index {}, length {}", index, length)
+            }
+            Error::UnmatchedMerkleRoot => f.write_str("unmatched merkle root with block header"),
+        }
+    }
+}
+
+impl From<bitcoin::consensus::encode::Error> for Error {
+    fn from(e: bitcoin::consensus::encode::Error) -> Self {
+        Error::Encode(Box::new(e))
+    }
+}
+
+impl From<MerkleProofError> for Error {
+    fn from(e: MerkleProofError) -> Self {
+        Error::MerkleProof(e)
+    }
+}
+
+/// tx with its merkle proof, the arguments of VerifyMintTx and ReleaseIncorrectAmountBTC of gateway
+/// and VerifyMerkleProof of bitcoin_spv.
+#[derive(Debug, Clone)]
+pub struct TxProof {
+    pub height: u32,
+    /// serialized tx.
+    pub tx: Vec<u8>,
+    pub merkle_proof: MerkleProofMsg,
+    /// serialized coinbase tx of the block.
+    pub coinbase_tx: Vec<u8>,
+    pub coinbase_merkle_proof: MerkleProofMsg,
+}
+
+/// decodes a raw block, e.g. the hex of `getblock <hash> 0` of bitcoind.
+pub fn parse_block(raw_block: &[u8]) -> Result<Block, Error> {
+    Ok(deserialize(raw_block)?)
+}
+
+/// builds the merkle proof of the tx at `index` in the block.
+///
+/// The last node of a level with odd nodes is paired with itself, same as bitcoin.
+/// The proof is verified against the merkle root of the block header.
+pub fn merkle_proof(block: &Block, index: usize) -> Result<MerkleProof, Error> {
+    if index >= block.txdata.len() {
+        return Err(Error::IndexOutOfRange {
+            index,
+            length: block.txdata.len(),
+        });
+    }
+    let mut level: Vec<TxMerkleNode> = block
+        .txdata
+        .iter()
+        .map(|tx| TxMerkleNode::from_hash(tx.txid().as_hash()))
+        .collect();
+    let mut prefix = vec![];
+    let mut siblings = vec![level[index]];
+    let mut index = index;
+    while level.len() > 1 {
+        prefix.push(index % 2 == 1);
+        siblings.push(level[(index ^ 1).min(level.len() - 1)]);
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut engine = TxMerkleNode::engine();
+                engine.input(&pair[0][..]);
+                engine.input(&pair[pair.len() - 1][..]);
+                TxMerkleNode::from_engine(engine)
+            })
+            .collect();
+        index /= 2;
+    }
+    let merkle_proof = MerkleProof { prefix, siblings };
+    if merkle_proof.merkle_root()? != block.header.merkle_root {
+        return Err(Error::UnmatchedMerkleRoot);
+    }
+    Ok(merkle_proof)
+}
+
+/// converts the merkle proof to the message of the contracts, whose siblings are the hex strings in the reversed byte order.
+pub fn merkle_proof_msg(merkle_proof: &MerkleProof) -> MerkleProofMsg {
+    MerkleProofMsg {
+        prefix: merkle_proof.prefix.clone(),
+        siblings: merkle_proof
+            .siblings
+            .iter()
+            .map(|sibling| sibling.to_string())
+            .collect(),
+    }
+}
+
+/// builds the proof of the tx in the block at `height`, with the proof of the coinbase tx of the block.
+///
+/// Txs whose serialized length without witness is 64 bytes are rejected,
+/// because the contracts can not distinguish them from the inner nodes of merkle tree.
+/// The depth of the coinbase proof is checked the same way as the contracts.
+pub fn tx_proof(block: &Block, height: u32, txid: &Txid) -> Result<TxProof, Error> {
+    let index = block
+        .txdata
+        .iter()
+        .position(|tx| tx.txid() == *txid)
+        .ok_or(Error::TxNotFound(*txid))?;
+    let tx = &block.txdata[index];
+    validate_tx_length(tx)?;
+    let tx_merkle_proof = merkle_proof(block, index)?;
+    let coinbase_tx = &block.txdata[0];
+    let coinbase_merkle_proof = merkle_proof(block, 0)?;
+    tx_merkle_proof.verify_depth(coinbase_tx, &coinbase_merkle_proof)?;
+    Ok(TxProof {
+        height,
+        tx: serialize(tx),
+        merkle_proof: merkle_proof_msg(&tx_merkle_proof),
+        coinbase_tx: serialize(coinbase_tx),
+        coinbase_merkle_proof: merkle_proof_msg(&coinbase_merkle_proof),
+    })
+}
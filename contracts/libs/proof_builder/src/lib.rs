@@ -0,0 +1,5 @@
+//! builds the proofs of the contracts from the Tendermint RPC and raw bitcoin blocks,
+//! without the TypeScript client.
+pub mod bitcoin_spv;
+pub mod rpc;
+pub mod sfps;
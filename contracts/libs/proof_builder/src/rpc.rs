@@ -0,0 +1,469 @@
+//! JSON results of the Tendermint RPC and their conversions to the proto types of sfps_lib.
+//!
+//! Int64 values are strings, hashes and addresses are upper hex and signatures and keys are base64,
+//! same as the amino JSON encoding of Tendermint v0.34 and CometBFT v0.38.
+use chrono::DateTime;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use sfps_lib::cosmos_proto::prost_types::Timestamp;
+use sfps_lib::cosmos_proto::tendermint::abci::{ExecTxResult, ResponseDeliverTx};
+use sfps_lib::cosmos_proto::tendermint::crypto::{public_key::Sum, ProofOp, ProofOps, PublicKey};
+use sfps_lib::cosmos_proto::tendermint::types;
+use sfps_lib::cosmos_proto::tendermint::version::Consensus;
+use std::fmt;
+use std::str::FromStr;
+
+const PUB_KEY_TYPE_ED25519: &str = "tendermint/PubKeyEd25519";
+const PUB_KEY_TYPE_SECP256K1: &str = "tendermint/PubKeySecp256k1";
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    Rpc { code: i64, message: String },
+    NoResult,
+    Hex(hex::FromHexError),
+    Base64(base64::DecodeError),
+    InvalidTime(chrono::ParseError),
+    UnsupportedPubKeyType(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Json(e) => write!(f, "json error: {}", e),
+            Error::Rpc { code, message } => write!(f, "rpc error {}: {}", code, message),
+            Error::NoResult => f.write_str("no result in rpc response"),
+            Error::Hex(e) => write!(f, "hex error: {}", e),
+            Error::Base64(e) => write!(f, "base64 error: {}", e),
+            Error::InvalidTime(e) => write!(f, "invalid time: {}", e),
+            Error::UnsupportedPubKeyType(t) => write!(f, "unsupported pub key type: {}", t),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(e: hex::FromHexError) -> Self {
+        Error::Hex(e)
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Error::Base64(e)
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(e: chrono::ParseError) -> Self {
+        Error::InvalidTime(e)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: String,
+}
+
+/// JSON-RPC 2.0 envelope of the responses.
+#[derive(Deserialize, Debug)]
+pub struct Response<R> {
+    pub result: Option<R>,
+    pub error: Option<RpcError>,
+}
+
+/// parses the whole JSON-RPC response and returns its result.
+pub fn parse_response<R: DeserializeOwned>(json: &str) -> Result<R, Error> {
+    let response: Response<R> = serde_json::from_str(json)?;
+    if let Some(error) = response.error {
+        let message = match error.data.is_empty() {
+            true => error.message,
+            false => format!("{} {}", error.message, error.data),
+        };
+        return Err(Error::Rpc {
+            code: error.code,
+            message,
+        });
+    }
+    response.result.ok_or(Error::NoResult)
+}
+
+/// result of `/header`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct HeaderResult {
+    pub header: Header,
+}
+
+/// result of `/block`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BlockResult {
+    pub block_id: BlockId,
+    pub block: Block,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Block {
+    pub header: Header,
+    /// commit of the previous block. None for the initial block.
+    pub last_commit: Option<Commit>,
+}
+
+/// result of `/commit`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommitResult {
+    pub signed_header: SignedHeader,
+    /// false if the commit is not yet canonical, that is seen by the node for the latest height.
+    pub canonical: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SignedHeader {
+    pub header: Header,
+    pub commit: Commit,
+}
+
+/// result of `/validators`.
+///
+/// It is paginated by 30 validators by default, so the validators of all the pages must be concatenated.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ValidatorsResult {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub block_height: i64,
+    pub validators: Vec<Validator>,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub count: i64,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub total: i64,
+}
+
+/// result of `/block_results`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BlockResultsResult {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub height: i64,
+    /// null if the block has no tx.
+    pub txs_results: Option<Vec<TxResult>>,
+}
+
+/// result of `/abci_query`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AbciQueryResult {
+    pub response: AbciQueryResponse,
+}
+
+/// response of `/abci_query` with `prove=true` of a store key, e.g. path `"/store/bank/key"`.
+///
+/// The state is of the block at `height`, which is committed to 'app_hash' of the next header.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AbciQueryResponse {
+    #[serde(default)]
+    pub code: u32,
+    /// null for the empty key.
+    pub key: Option<String>,
+    /// null if the key does not exist.
+    pub value: Option<String>,
+    /// null without `prove=true`.
+    #[serde(rename = "proofOps")]
+    pub proof_ops: Option<RpcProofOps>,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub height: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RpcProofOps {
+    pub ops: Vec<RpcProofOp>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RpcProofOp {
+    #[serde(rename = "type")]
+    pub op_type: String,
+    pub key: String,
+    pub data: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Header {
+    pub version: Version,
+    pub chain_id: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub height: i64,
+    pub time: String,
+    pub last_block_id: BlockId,
+    pub last_commit_hash: String,
+    pub data_hash: String,
+    pub validators_hash: String,
+    pub next_validators_hash: String,
+    pub consensus_hash: String,
+    pub app_hash: String,
+    pub last_results_hash: String,
+    pub evidence_hash: String,
+    pub proposer_address: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Version {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub block: u64,
+    #[serde(default, deserialize_with = "deserialize_from_str")]
+    pub app: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BlockId {
+    pub hash: String,
+    pub parts: PartSetHeader,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PartSetHeader {
+    pub total: u32,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Commit {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub height: i64,
+    pub round: i32,
+    pub block_id: BlockId,
+    pub signatures: Vec<CommitSig>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommitSig {
+    pub block_id_flag: i32,
+    pub validator_address: String,
+    pub timestamp: String,
+    /// null for the absent votes.
+    pub signature: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Validator {
+    pub address: String,
+    pub pub_key: PubKey,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub voting_power: i64,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub proposer_priority: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PubKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub value: String,
+}
+
+/// ResponseDeliverTx of Tendermint v0.34, or ExecTxResult of CometBFT v0.38 which has the same JSON.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TxResult {
+    #[serde(default)]
+    pub code: u32,
+    pub data: Option<String>,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub gas_wanted: i64,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub gas_used: i64,
+}
+
+/// deserializes integers encoded as strings.
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, Error> {
+    Ok(hex::decode(s)?)
+}
+
+fn decode_base64(s: &str) -> Result<Vec<u8>, Error> {
+    Ok(base64::decode(s)?)
+}
+
+/// parses RFC3339 time with nanoseconds.
+/// The zero time of go ('0001-01-01T00:00:00Z') of the absent votes is parsed as is.
+fn parse_time(s: &str) -> Result<Timestamp, Error> {
+    let time = DateTime::parse_from_rfc3339(s)?;
+    Ok(Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    })
+}
+
+impl Header {
+    pub fn to_proto(&self) -> Result<types::Header, Error> {
+        Ok(types::Header {
+            version: Some(Consensus {
+                block: self.version.block,
+                app: self.version.app,
+            }),
+            chain_id: self.chain_id.clone(),
+            height: self.height,
+            time: Some(parse_time(&self.time)?),
+            last_block_id: Some(self.last_block_id.to_proto()?),
+            last_commit_hash: decode_hex(&self.last_commit_hash)?,
+            data_hash: decode_hex(&self.data_hash)?,
+            validators_hash: decode_hex(&self.validators_hash)?,
+            next_validators_hash: decode_hex(&self.next_validators_hash)?,
+            consensus_hash: decode_hex(&self.consensus_hash)?,
+            app_hash: decode_hex(&self.app_hash)?,
+            last_results_hash: decode_hex(&self.last_results_hash)?,
+            evidence_hash: decode_hex(&self.evidence_hash)?,
+            proposer_address: decode_hex(&self.proposer_address)?,
+        })
+    }
+}
+
+impl BlockId {
+    pub fn to_proto(&self) -> Result<types::BlockId, Error> {
+        Ok(types::BlockId {
+            hash: decode_hex(&self.hash)?,
+            part_set_header: Some(types::PartSetHeader {
+                total: self.parts.total,
+                hash: decode_hex(&self.parts.hash)?,
+            }),
+        })
+    }
+}
+
+impl Commit {
+    pub fn to_proto(&self) -> Result<types::Commit, Error> {
+        Ok(types::Commit {
+            height: self.height,
+            round: self.round,
+            block_id: Some(self.block_id.to_proto()?),
+            signatures: self
+                .signatures
+                .iter()
+                .map(CommitSig::to_proto)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl CommitSig {
+    pub fn to_proto(&self) -> Result<types::CommitSig, Error> {
+        Ok(types::CommitSig {
+            block_id_flag: self.block_id_flag,
+            validator_address: decode_hex(&self.validator_address)?,
+            timestamp: Some(parse_time(&self.timestamp)?),
+            signature: match &self.signature {
+                Some(signature) => decode_base64(signature)?,
+                None => vec![],
+            },
+        })
+    }
+}
+
+impl SignedHeader {
+    pub fn to_proto(&self) -> Result<types::SignedHeader, Error> {
+        Ok(types::SignedHeader {
+            header: Some(self.header.to_proto()?),
+            commit: Some(self.commit.to_proto()?),
+        })
+    }
+}
+
+impl Validator {
+    pub fn to_proto(&self) -> Result<types::Validator, Error> {
+        Ok(types::Validator {
+            address: decode_hex(&self.address)?,
+            pub_key: Some(self.pub_key.to_proto()?),
+            voting_power: self.voting_power,
+            proposer_priority: self.proposer_priority,
+        })
+    }
+}
+
+impl PubKey {
+    pub fn to_proto(&self) -> Result<PublicKey, Error> {
+        let key = decode_base64(&self.value)?;
+        let sum = match self.key_type.as_str() {
+            PUB_KEY_TYPE_ED25519 => Sum::Ed25519(key),
+            PUB_KEY_TYPE_SECP256K1 => Sum::Secp256k1(key),
+            _ => return Err(Error::UnsupportedPubKeyType(self.key_type.clone())),
+        };
+        Ok(PublicKey { sum: Some(sum) })
+    }
+}
+
+impl AbciQueryResponse {
+    pub fn key(&self) -> Result<Vec<u8>, Error> {
+        match &self.key {
+            Some(key) => decode_base64(key),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// returns None if the key does not exist.
+    pub fn value(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.value.as_deref().map(decode_base64).transpose()
+    }
+}
+
+impl RpcProofOps {
+    pub fn to_proto(&self) -> Result<ProofOps, Error> {
+        Ok(ProofOps {
+            ops: self
+                .ops
+                .iter()
+                .map(RpcProofOp::to_proto)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl RpcProofOp {
+    pub fn to_proto(&self) -> Result<ProofOp, Error> {
+        Ok(ProofOp {
+            r#type: self.op_type.clone(),
+            key: decode_base64(&self.key)?,
+            data: decode_base64(&self.data)?,
+        })
+    }
+}
+
+impl TxResult {
+    /// converts to ResponseDeliverTx with only the fields committed to 'last_results_hash'.
+    pub fn to_proto(&self) -> Result<ResponseDeliverTx, Error> {
+        Ok(ResponseDeliverTx {
+            code: self.code,
+            data: match &self.data {
+                Some(data) => decode_base64(data)?,
+                None => vec![],
+            },
+            gas_wanted: self.gas_wanted,
+            gas_used: self.gas_used,
+            ..Default::default()
+        })
+    }
+
+    /// converts to ExecTxResult of CometBFT v0.38 with only the fields committed to 'last_results_hash'.
+    pub fn to_exec_tx_result(&self) -> Result<ExecTxResult, Error> {
+        Ok(ExecTxResult {
+            code: self.code,
+            data: match &self.data {
+                Some(data) => decode_base64(data)?,
+                None => vec![],
+            },
+            gas_wanted: self.gas_wanted,
+            gas_used: self.gas_used,
+            ..Default::default()
+        })
+    }
+}
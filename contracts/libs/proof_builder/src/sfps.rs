@@ -0,0 +1,390 @@
+//! proofs and light blocks submitted to the sfps contract and the contracts depending on it.
+use crate::rpc;
+use sfps_lib::cosmos_proto::tendermint::types::{Header, LightBlock, SignedHeader, ValidatorSet};
+use sfps_lib::exec_tx_result_proof::{encode_exec_tx_result_as_merkle_leaf, ExecTxResultProof};
+use sfps_lib::header::hash_header;
+use sfps_lib::light_block::{validate_light_block, Error as LightBlockError};
+use sfps_lib::merkle::{simple_hash_from_byte_vectors, MerkleProof};
+use sfps_lib::response_deliver_tx_proof::{
+    encode_response_deliver_tx_as_merkle_leaf, Error as ResponseDeliverTxProofError,
+    ResponseDeliverTxProof,
+};
+use sfps_lib::state_proof::{Error as StateProofError, StateProof};
+use sfps_lib::validator_set::total_voting_power;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    Rpc(rpc::Error),
+    LightBlock(LightBlockError),
+    ResponseDeliverTxProof(ResponseDeliverTxProofError),
+    StateProof(StateProofError),
+    QueryFailed { code: u32 },
+    NoProofOps,
+    IndexOutOfRange { index: usize, length: usize },
+    UnmatchedHeight { expected: i64, actual: i64 },
+    UnconnectedHeaders { height: i64 },
+    NoHeader,
+    NonIncreasingHeight { previous: i64, actual: i64 },
+    ExceedsInterval { max: u64, actual: u64 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Rpc(e) => write!(f, "rpc error: {}", e),
+            Error::LightBlock(e) => write!(f, "light block error: {}", e),
+            Error::ResponseDeliverTxProof(e) => {
+                write!(f, "response deliver tx proof error: {}", e)
+            }
+            Error::StateProof(e) => write!(f, "state proof error: {}", e),
+            Error::QueryFailed { code } => write!(f, "abci query failed with code {}", code),
+            Error::NoProofOps => f.write_str("no proof ops in abci query response"),
+            Error::IndexOutOfRange { index, length } => {
+                write!(f, "index out of range: index {}, length {}", index, length)
+            }
+            Error::UnmatchedHeight { expected, actual } => write!(
+                f,
+                "unmatched height: expected {}, actual {}",
+                expected, actual
+            ),
+            Error::UnconnectedHeaders { height } => {
+                write!(f, "header at {} is not connected to the previous", height)
+            }
+            Error::NoHeader => f.write_str("no header"),
+            Error::NonIncreasingHeight { previous, actual } => write!(
+                f,
+                "non increasing height: previous {}, actual {}",
+                previous, actual
+            ),
+            Error::ExceedsInterval { max, actual } => {
+                write!(f, "exceeds interval: max {}, actual {}", max, actual)
+            }
+        }
+    }
+}
+
+impl From<rpc::Error> for Error {
+    fn from(e: rpc::Error) -> Self {
+        Error::Rpc(e)
+    }
+}
+
+impl From<LightBlockError> for Error {
+    fn from(e: LightBlockError) -> Self {
+        Error::LightBlock(e)
+    }
+}
+
+impl From<ResponseDeliverTxProofError> for Error {
+    fn from(e: ResponseDeliverTxProofError) -> Self {
+        Error::ResponseDeliverTxProof(e)
+    }
+}
+
+impl From<StateProofError> for Error {
+    fn from(e: StateProofError) -> Self {
+        Error::StateProof(e)
+    }
+}
+
+/// builds the merkle proof of the leaf at `index` of the simple merkle tree of tendermint.
+pub fn merkle_proof(leaves: &[Vec<u8>], index: usize) -> Result<MerkleProof, Error> {
+    if index >= leaves.len() {
+        return Err(Error::IndexOutOfRange {
+            index,
+            length: leaves.len(),
+        });
+    }
+    Ok(MerkleProof {
+        total: leaves.len() as u64,
+        index: index as u64,
+        leaf: leaves[index].clone(),
+        aunts: aunts(leaves, index),
+    })
+}
+
+// aunts from the sibling of the leaf to the child of the root.
+fn aunts(leaves: &[Vec<u8>], index: usize) -> Vec<Vec<u8>> {
+    if leaves.len() <= 1 {
+        return vec![];
+    }
+    let split_point = leaves.len().next_power_of_two() / 2;
+    let (left, right) = leaves.split_at(split_point);
+    if index < split_point {
+        let mut aunts = aunts(left, index);
+        aunts.push(simple_hash_from_byte_vectors(right.to_vec()));
+        aunts
+    } else {
+        let mut aunts = aunts(right, index - split_point);
+        aunts.push(simple_hash_from_byte_vectors(left.to_vec()));
+        aunts
+    }
+}
+
+/// builds the merkle proof of the tx result at `index` in `/block_results`,
+/// against 'last_results_hash' of the next header.
+pub fn tx_result_merkle_proof(
+    block_results: &rpc::BlockResultsResult,
+    index: usize,
+) -> Result<MerkleProof, Error> {
+    tx_results_merkle_proof(block_results, index, |tx_result| {
+        Ok(encode_response_deliver_tx_as_merkle_leaf(
+            &tx_result.to_proto()?,
+        ))
+    })
+}
+
+/// builds the merkle proof of the tx result at `index` in `/block_results` of CometBFT v0.38,
+/// whose leaves are encoded as ExecTxResult.
+pub fn exec_tx_result_merkle_proof(
+    block_results: &rpc::BlockResultsResult,
+    index: usize,
+) -> Result<MerkleProof, Error> {
+    tx_results_merkle_proof(block_results, index, |tx_result| {
+        Ok(encode_exec_tx_result_as_merkle_leaf(
+            &tx_result.to_exec_tx_result()?,
+        ))
+    })
+}
+
+fn tx_results_merkle_proof(
+    block_results: &rpc::BlockResultsResult,
+    index: usize,
+    encode_leaf: impl Fn(&rpc::TxResult) -> Result<Vec<u8>, Error>,
+) -> Result<MerkleProof, Error> {
+    let leaves = block_results
+        .txs_results
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(encode_leaf)
+        .collect::<Result<Vec<_>, Error>>()?;
+    merkle_proof(&leaves, index)
+}
+
+/// converts the headers of consecutive heights and checks that each header contains the hash of the previous one.
+pub fn connected_headers(headers: &[rpc::Header]) -> Result<Vec<Header>, Error> {
+    let headers = headers
+        .iter()
+        .map(rpc::Header::to_proto)
+        .collect::<Result<Vec<_>, _>>()?;
+    for pair in headers.windows(2) {
+        if pair[1].height != pair[0].height + 1 {
+            return Err(Error::UnmatchedHeight {
+                expected: pair[0].height + 1,
+                actual: pair[1].height,
+            });
+        }
+        let connected = matches!(
+            &pair[1].last_block_id,
+            Some(block_id) if block_id.hash == hash_header(&pair[0])
+        );
+        if !connected {
+            return Err(Error::UnconnectedHeaders {
+                height: pair[1].height,
+            });
+        }
+    }
+    Ok(headers)
+}
+
+/// builds the proof of the tx result at `index` in `/block_results`.
+///
+/// `headers` must start from the next height of the tx and end at a header committed to sfps,
+/// whose index is given to the contract with the proof (e.g. `block_hash_index` of ClaimReleasedBtc).
+/// The proof is verified before returned.
+pub fn response_deliver_tx_proof(
+    block_results: &rpc::BlockResultsResult,
+    index: usize,
+    headers: &[rpc::Header],
+) -> Result<ResponseDeliverTxProof, Error> {
+    let merkle_proof = tx_result_merkle_proof(block_results, index)?;
+    let headers = headers_from_next_height(block_results, headers)?;
+    let proof = ResponseDeliverTxProof {
+        merkle_proof,
+        headers,
+    };
+    proof.verify()?;
+    Ok(proof)
+}
+
+/// builds the proof of the tx result at `index` in `/block_results` of CometBFT v0.38,
+/// given to the contracts as `ExecTxResultProof` of sfps.
+///
+/// `headers` are the same as `response_deliver_tx_proof`. The proof is verified before returned.
+pub fn exec_tx_result_proof(
+    block_results: &rpc::BlockResultsResult,
+    index: usize,
+    headers: &[rpc::Header],
+) -> Result<ExecTxResultProof, Error> {
+    let merkle_proof = exec_tx_result_merkle_proof(block_results, index)?;
+    let headers = headers_from_next_height(block_results, headers)?;
+    let proof = ExecTxResultProof {
+        merkle_proof,
+        headers,
+    };
+    proof.verify()?;
+    Ok(proof)
+}
+
+// converts the connected headers, which must start from the next height of the tx.
+fn headers_from_next_height(
+    block_results: &rpc::BlockResultsResult,
+    headers: &[rpc::Header],
+) -> Result<Vec<Header>, Error> {
+    let headers = connected_headers(headers)?;
+    if let Some(first) = headers.first() {
+        if first.height != block_results.height + 1 {
+            return Err(Error::UnmatchedHeight {
+                expected: block_results.height + 1,
+                actual: first.height,
+            });
+        }
+    }
+    Ok(headers)
+}
+
+/// builds the state proof of the key-value pair, or the non-existence of the key, of `/abci_query` with `prove=true`.
+///
+/// `store_key` is the store name of the query path (e.g. `bank` of `"/store/bank/key"`).
+/// `headers` must start from the next height of the query, whose 'app_hash' commits to the state,
+/// and end at a header committed to sfps. The proof is verified before returned.
+pub fn state_proof(
+    query: &rpc::AbciQueryResponse,
+    store_key: &[u8],
+    headers: &[rpc::Header],
+) -> Result<StateProof, Error> {
+    if query.code != 0 {
+        return Err(Error::QueryFailed { code: query.code });
+    }
+    let proof_ops = query
+        .proof_ops
+        .as_ref()
+        .ok_or(Error::NoProofOps)?
+        .to_proto()?;
+    let headers = connected_headers(headers)?;
+    if let Some(first) = headers.first() {
+        if first.height != query.height + 1 {
+            return Err(Error::UnmatchedHeight {
+                expected: query.height + 1,
+                actual: first.height,
+            });
+        }
+    }
+    let proof = StateProof { proof_ops, headers };
+    proof.verify(store_key, &query.key()?, query.value()?.as_deref())?;
+    Ok(proof)
+}
+
+/// builds the light block from the header, its commit and its validators.
+///
+/// The commit is either `signed_header.commit` of `/commit` at the height, or `block.last_commit` of `/block` at the next height.
+/// `validators` are the concatenated pages of `/validators` at the height.
+/// The total voting power is summed from the validators, and the proposer is not set as sfps does not use it.
+pub fn light_block(
+    header: &rpc::Header,
+    commit: &rpc::Commit,
+    validators: &[rpc::Validator],
+) -> Result<LightBlock, Error> {
+    if commit.height != header.height {
+        return Err(Error::UnmatchedHeight {
+            expected: header.height,
+            actual: commit.height,
+        });
+    }
+    let mut validator_set = ValidatorSet {
+        validators: validators
+            .iter()
+            .map(rpc::Validator::to_proto)
+            .collect::<Result<_, _>>()?,
+        proposer: None,
+        total_voting_power: 0,
+    };
+    validator_set.total_voting_power = total_voting_power(&validator_set);
+    let light_block = LightBlock {
+        signed_header: Some(SignedHeader {
+            header: Some(header.to_proto()?),
+            commit: Some(commit.to_proto()?),
+        }),
+        validator_set: Some(validator_set),
+    };
+    validate_light_block(&light_block)?;
+    Ok(light_block)
+}
+
+/// selects the light blocks whose hashes are committed to sfps by `verify_subsequent_light_blocks`.
+///
+/// A light block is committed when the interval from the last committed header
+/// would exceed `max_interval` at the next light block, and the last light block is always committed.
+/// Fewer committed hashes keep the hash list of sfps short,
+/// while tx result proofs need the headers up to the next committed one.
+pub fn commit_flags(
+    anchor_header: &Header,
+    light_blocks: &[LightBlock],
+    max_interval: u64,
+) -> Result<Vec<bool>, Error> {
+    let heights = light_blocks
+        .iter()
+        .map(|light_block| {
+            light_block
+                .signed_header
+                .as_ref()
+                .and_then(|signed_header| signed_header.header.as_ref())
+                .map(|header| header.height)
+                .ok_or(Error::NoHeader)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut previous = anchor_header.height;
+    for height in heights.iter() {
+        if *height <= previous {
+            return Err(Error::NonIncreasingHeight {
+                previous,
+                actual: *height,
+            });
+        }
+        previous = *height;
+    }
+    let mut last_committed = anchor_header.height;
+    let mut commit_flags = Vec::with_capacity(heights.len());
+    for (i, height) in heights.iter().enumerate() {
+        let interval = (height - last_committed) as u64;
+        if interval > max_interval {
+            return Err(Error::ExceedsInterval {
+                max: max_interval,
+                actual: interval,
+            });
+        }
+        let commit = match heights.get(i + 1) {
+            Some(next) => (next - last_committed) as u64 > max_interval,
+            None => true,
+        };
+        if commit {
+            last_committed = *height;
+        }
+        commit_flags.push(commit);
+    }
+    Ok(commit_flags)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merkle_proof() {
+        for total in 1..=20 {
+            let leaves: Vec<Vec<u8>> = (0..total).map(|i| vec![i as u8; i + 1]).collect();
+            let root = simple_hash_from_byte_vectors(leaves.clone());
+            for index in 0..total {
+                let proof = merkle_proof(&leaves, index).unwrap();
+                proof.verify(root.clone()).unwrap();
+                assert_eq!(proof.leaf, leaves[index]);
+            }
+            assert!(matches!(
+                merkle_proof(&leaves, total),
+                Err(Error::IndexOutOfRange { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use bitcoin::consensus::deserialize;
+use bitcoin::hash_types::{BlockHash, TxMerkleNode};
+use bitcoin::{Block, Transaction};
+use bitcoin_header_chain::merkle_proof::MerkleProof;
+use proof_builder::bitcoin_spv::{merkle_proof, parse_block, tx_proof, Error};
+use shared_types::bitcoin_spv::MerkleProofMsg;
+use std::str::FromStr;
+
+fn read_block(name: &str) -> Block {
+    let hex = std::fs::read_to_string(format!("tests/testdata/bitcoin/{}", name)).unwrap();
+    parse_block(&hex::decode(hex.trim()).unwrap()).unwrap()
+}
+
+// parses the message same as the contracts.
+fn parse_merkle_proof_msg(msg: &MerkleProofMsg) -> MerkleProof {
+    MerkleProof {
+        prefix: msg.prefix.clone(),
+        siblings: msg
+            .siblings
+            .iter()
+            .map(|sibling| TxMerkleNode::from_str(sibling).unwrap())
+            .collect(),
+    }
+}
+
+#[test]
+fn test_merkle_proof() {
+    for (name, hash) in [
+        (
+            "mainnet_block.hex",
+            "00000000b0c5a240b2a61d2e75692224efd4cbecdf6eaf4cc2cf477ca7c270e7",
+        ),
+        (
+            "testnet_segwit_block.hex",
+            "000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b",
+        ),
+    ]
+    .iter()
+    {
+        let block = read_block(name);
+        assert_eq!(block.block_hash(), BlockHash::from_str(hash).unwrap());
+        let coinbase_proof = merkle_proof(&block, 0).unwrap();
+        assert!(coinbase_proof.is_coinbase_proof());
+        for (index, tx) in block.txdata.iter().enumerate() {
+            let proof = merkle_proof(&block, index).unwrap();
+            assert_eq!(proof.merkle_root().unwrap(), block.header.merkle_root);
+            assert_eq!(proof.leaf().unwrap()[..], tx.txid()[..]);
+            proof
+                .verify_depth(&block.txdata[0], &coinbase_proof)
+                .unwrap();
+        }
+        assert!(matches!(
+            merkle_proof(&block, block.txdata.len()).unwrap_err(),
+            Error::IndexOutOfRange { .. }
+        ));
+    }
+}
+
+#[test]
+fn test_tx_proof() {
+    // the height is pushed in the coinbase (BIP34).
+    let height = 924634;
+    let block = read_block("testnet_segwit_block.hex");
+    assert!(block.txdata.len() > 2);
+    let tx = block.txdata.last().unwrap();
+    let proof = tx_proof(&block, height, &tx.txid()).unwrap();
+    assert_eq!(proof.height, height);
+    let decoded: Transaction = deserialize(&proof.tx).unwrap();
+    assert_eq!(decoded.txid(), tx.txid());
+    let merkle_proof = parse_merkle_proof_msg(&proof.merkle_proof);
+    assert_eq!(
+        merkle_proof.merkle_root().unwrap(),
+        block.header.merkle_root
+    );
+    assert_eq!(merkle_proof.leaf().unwrap()[..], tx.txid()[..]);
+    let coinbase_tx: Transaction = deserialize(&proof.coinbase_tx).unwrap();
+    assert!(coinbase_tx.is_coin_base());
+    let coinbase_merkle_proof = parse_merkle_proof_msg(&proof.coinbase_merkle_proof);
+    merkle_proof
+        .verify_depth(&coinbase_tx, &coinbase_merkle_proof)
+        .unwrap();
+
+    // tx of the other block
+    let other_tx = read_block("mainnet_block.hex").txdata[1].txid();
+    assert!(matches!(
+        tx_proof(&block, height, &other_tx).unwrap_err(),
+        Error::TxNotFound(txid) if txid == other_tx
+    ));
+}
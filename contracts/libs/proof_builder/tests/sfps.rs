@@ -0,0 +1,408 @@
+use proof_builder::rpc::{
+    parse_response, AbciQueryResult, BlockResult, BlockResultsResult, CommitResult,
+    Error as RpcError, HeaderResult, ValidatorsResult,
+};
+use proof_builder::sfps::{
+    commit_flags, connected_headers, exec_tx_result_proof, light_block, response_deliver_tx_proof,
+    state_proof, Error as SfpsError,
+};
+use sfps_lib::cosmos_proto::prost::Message;
+use sfps_lib::cosmos_proto::tendermint::abci::{ExecTxResult, ResponseDeliverTx};
+use sfps_lib::cosmos_proto::tendermint::types::{Header, LightBlock};
+use sfps_lib::light_block::verify_light_block;
+use sfps_lib::state_proof::Error as StateProofError;
+
+// RPC responses of secret-4 at the heights of sfps_lib/tests/testdata.json.
+const INITIAL_HEIGHT: i64 = 1000000;
+const HIGHEST_HEIGHT: i64 = 1000009;
+const TX_HEIGHT: i64 = 1000008;
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("tests/testdata/secret-4/{}", name)).unwrap()
+}
+
+fn initial_header() -> Header {
+    let result: HeaderResult = parse_response(&fixture("header_1000000.json")).unwrap();
+    result.header.to_proto().unwrap()
+}
+
+fn commit(height: i64) -> CommitResult {
+    parse_response(&fixture(&format!("commit_{}.json", height))).unwrap()
+}
+
+fn validators(height: i64) -> ValidatorsResult {
+    parse_response(&fixture(&format!("validators_{}.json", height))).unwrap()
+}
+
+fn light_blocks() -> Vec<LightBlock> {
+    (INITIAL_HEIGHT + 1..=HIGHEST_HEIGHT)
+        .map(|height| {
+            let commit = commit(height);
+            light_block(
+                &commit.signed_header.header,
+                &commit.signed_header.commit,
+                &validators(height).validators,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+fn header_of(light_block: &LightBlock) -> &Header {
+    light_block
+        .signed_header
+        .as_ref()
+        .unwrap()
+        .header
+        .as_ref()
+        .unwrap()
+}
+
+struct DalekEd25519Verifier();
+
+impl sfps_lib::light_block::Ed25519Verifier<String> for DalekEd25519Verifier {
+    fn verify_batch(
+        &mut self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<(), String> {
+        let mut sig = Vec::with_capacity(signatures.len());
+        for signature in signatures.iter() {
+            sig.push(ed25519_dalek::Signature::from_bytes(signature).unwrap())
+        }
+        let mut pubkeys = Vec::with_capacity(public_keys.len());
+        for public_key in public_keys.iter() {
+            pubkeys.push(ed25519_dalek::PublicKey::from_bytes(public_key).unwrap())
+        }
+        ed25519_dalek::verify_batch(messages, &sig, &pubkeys)
+            .map_err(|_| "ed25519 error".to_string())
+    }
+}
+
+impl sfps_lib::light_block::Secp256k1Verifier<String> for DalekEd25519Verifier {
+    fn verify(&mut self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), String> {
+        Err("secp256k1 is not supported".to_string())
+    }
+}
+
+#[test]
+fn test_light_blocks() {
+    let mut validators_hash = initial_header().next_validators_hash;
+    for light_block in light_blocks().iter() {
+        verify_light_block(light_block, &mut DalekEd25519Verifier {}).unwrap();
+        let header = header_of(light_block);
+        assert_eq!(header.validators_hash, validators_hash);
+        validators_hash = header.next_validators_hash.clone();
+        let validator_set = light_block.validator_set.as_ref().unwrap();
+        assert_eq!(validator_set.validators.len(), 60);
+        assert_eq!(validator_set.total_voting_power, 99636591);
+        assert!(validator_set.proposer.is_none());
+    }
+}
+
+#[test]
+fn test_light_block_from_next_block() {
+    let next_block: BlockResult = parse_response(&fixture("block_1000009.json")).unwrap();
+    let commit = commit(TX_HEIGHT);
+    let from_next_block = light_block(
+        &commit.signed_header.header,
+        next_block.block.last_commit.as_ref().unwrap(),
+        &validators(TX_HEIGHT).validators,
+    )
+    .unwrap();
+    assert_eq!(
+        from_next_block,
+        light_blocks()[(TX_HEIGHT - INITIAL_HEIGHT - 1) as usize]
+    );
+    assert_eq!(
+        next_block.block.header.to_proto().unwrap(),
+        *header_of(light_blocks().last().unwrap())
+    );
+
+    // the commit of the other height
+    let err = light_block(
+        &commit.signed_header.header,
+        &self::commit(HIGHEST_HEIGHT).signed_header.commit,
+        &validators(TX_HEIGHT).validators,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        SfpsError::UnmatchedHeight {
+            expected: TX_HEIGHT,
+            actual: HIGHEST_HEIGHT
+        }
+    ));
+
+    // a page of validators
+    let err = light_block(
+        &commit.signed_header.header,
+        &commit.signed_header.commit,
+        &validators(TX_HEIGHT).validators[..30],
+    )
+    .unwrap_err();
+    assert!(matches!(err, SfpsError::LightBlock(_)));
+}
+
+#[test]
+fn test_commit_flags() {
+    let anchor_header = initial_header();
+    let light_blocks = light_blocks();
+    assert_eq!(
+        commit_flags(&anchor_header, &light_blocks, 3).unwrap(),
+        vec![false, false, true, false, false, true, false, false, true]
+    );
+    assert_eq!(
+        commit_flags(&anchor_header, &light_blocks, 9).unwrap(),
+        vec![false, false, false, false, false, false, false, false, true]
+    );
+    assert_eq!(
+        commit_flags(&anchor_header, &light_blocks, 1).unwrap(),
+        vec![true; 9]
+    );
+
+    // skipped light blocks
+    let skipped = vec![light_blocks[0].clone(), light_blocks[4].clone()];
+    assert!(matches!(
+        commit_flags(&anchor_header, &skipped, 3).unwrap_err(),
+        SfpsError::ExceedsInterval { max: 3, actual: 4 }
+    ));
+    assert_eq!(
+        commit_flags(&anchor_header, &skipped, 4).unwrap(),
+        vec![true, true]
+    );
+    assert_eq!(
+        commit_flags(&anchor_header, &skipped, 5).unwrap(),
+        vec![false, true]
+    );
+
+    // unordered light blocks
+    let unordered = vec![light_blocks[1].clone(), light_blocks[0].clone()];
+    assert!(matches!(
+        commit_flags(&anchor_header, &unordered, 3).unwrap_err(),
+        SfpsError::NonIncreasingHeight {
+            previous: 1000002,
+            actual: 1000001
+        }
+    ));
+}
+
+#[test]
+fn test_response_deliver_tx_proof() {
+    let block_results: BlockResultsResult =
+        parse_response(&fixture("block_results_1000008.json")).unwrap();
+    let headers = vec![commit(HIGHEST_HEIGHT).signed_header.header];
+    let proof = response_deliver_tx_proof(&block_results, 0, &headers).unwrap();
+    assert_eq!(proof.merkle_proof.total, 1);
+    assert_eq!(proof.headers[0].height, TX_HEIGHT + 1);
+    assert_eq!(
+        proof.verify().unwrap(),
+        commit(HIGHEST_HEIGHT)
+            .signed_header
+            .commit
+            .to_proto()
+            .unwrap()
+            .block_id
+            .unwrap()
+            .hash
+    );
+    let response_deliver_tx =
+        ResponseDeliverTx::decode(proof.merkle_proof.leaf.as_slice()).unwrap();
+    assert_eq!(response_deliver_tx.code, 0);
+    assert_eq!(response_deliver_tx.gas_wanted, 50000);
+    assert_eq!(response_deliver_tx.gas_used, 23757);
+
+    // out of range
+    assert!(matches!(
+        response_deliver_tx_proof(&block_results, 1, &headers).unwrap_err(),
+        SfpsError::IndexOutOfRange {
+            index: 1,
+            length: 1
+        }
+    ));
+
+    // headers from the tx height
+    let headers_from_tx_height = vec![
+        commit(TX_HEIGHT).signed_header.header,
+        commit(HIGHEST_HEIGHT).signed_header.header,
+    ];
+    assert!(matches!(
+        response_deliver_tx_proof(&block_results, 0, &headers_from_tx_height).unwrap_err(),
+        SfpsError::UnmatchedHeight {
+            expected: 1000009,
+            actual: 1000008
+        }
+    ));
+
+    // unconnected headers
+    let mut unconnected_header = commit(TX_HEIGHT).signed_header.header;
+    unconnected_header.last_block_id = commit(TX_HEIGHT - 2).signed_header.commit.block_id;
+    let unconnected_headers = vec![
+        commit(TX_HEIGHT - 1).signed_header.header,
+        unconnected_header,
+    ];
+    assert!(matches!(
+        connected_headers(&unconnected_headers).unwrap_err(),
+        SfpsError::UnconnectedHeaders { height: TX_HEIGHT }
+    ));
+
+    // no headers
+    assert!(matches!(
+        response_deliver_tx_proof(&block_results, 0, &[]).unwrap_err(),
+        SfpsError::ResponseDeliverTxProof(_)
+    ));
+}
+
+#[test]
+fn test_exec_tx_result_proof() {
+    let block_results: BlockResultsResult =
+        parse_response(&fixture("block_results_1000008.json")).unwrap();
+    let headers = vec![commit(HIGHEST_HEIGHT).signed_header.header];
+    let proof = exec_tx_result_proof(&block_results, 0, &headers).unwrap();
+    // the committed fields of ExecTxResult have the same field numbers as ResponseDeliverTx,
+    // so the proof is the same as the one of Tendermint v0.34.
+    let response_deliver_tx_proof = response_deliver_tx_proof(&block_results, 0, &headers).unwrap();
+    assert_eq!(proof.merkle_proof, response_deliver_tx_proof.merkle_proof);
+    assert_eq!(proof.headers, response_deliver_tx_proof.headers);
+    assert_eq!(
+        proof.verify().unwrap(),
+        response_deliver_tx_proof.verify().unwrap()
+    );
+    let exec_tx_result = ExecTxResult::decode(proof.merkle_proof.leaf.as_slice()).unwrap();
+    assert_eq!(exec_tx_result, proof.leaf_exec_tx_result().unwrap());
+    assert_eq!(exec_tx_result.code, 0);
+    assert_eq!(exec_tx_result.gas_wanted, 50000);
+    assert_eq!(exec_tx_result.gas_used, 23757);
+
+    // out of range
+    assert!(matches!(
+        exec_tx_result_proof(&block_results, 1, &headers).unwrap_err(),
+        SfpsError::IndexOutOfRange {
+            index: 1,
+            length: 1
+        }
+    ));
+
+    // headers from the tx height
+    let headers_from_tx_height = vec![
+        commit(TX_HEIGHT).signed_header.header,
+        commit(HIGHEST_HEIGHT).signed_header.header,
+    ];
+    assert!(matches!(
+        exec_tx_result_proof(&block_results, 0, &headers_from_tx_height).unwrap_err(),
+        SfpsError::UnmatchedHeight {
+            expected: 1000009,
+            actual: 1000008
+        }
+    ));
+}
+
+#[test]
+#[ignore = "needs tests/testdata/secret-4/abci_query_1000008.json fetched by tests/testdata/secret-4/fetch_abci_query.sh"]
+fn test_state_proof() {
+    // the total supply of uscrt in the bank store, which exists in every block.
+    let query: AbciQueryResult = parse_response(&fixture("abci_query_1000008.json")).unwrap();
+    let query = query.response;
+    assert_eq!(query.height, TX_HEIGHT);
+    let key = query.key().unwrap();
+    let value = query.value().unwrap().unwrap();
+    let headers = vec![commit(HIGHEST_HEIGHT).signed_header.header];
+    let proof = state_proof(&query, b"bank", &headers).unwrap();
+    assert_eq!(proof.headers[0].height, TX_HEIGHT + 1);
+    assert_eq!(
+        proof.verify(b"bank", &key, Some(&value)).unwrap(),
+        commit(HIGHEST_HEIGHT)
+            .signed_header
+            .commit
+            .to_proto()
+            .unwrap()
+            .block_id
+            .unwrap()
+            .hash
+    );
+
+    // another value
+    assert!(matches!(
+        proof.verify(b"bank", &key, Some(b"1")).unwrap_err(),
+        StateProofError::Ics23(_)
+    ));
+    // another store
+    assert_eq!(
+        proof.verify(b"staking", &key, Some(&value)).unwrap_err(),
+        StateProofError::UnmatchedProofOpKey
+    );
+    // the key exists
+    assert!(matches!(
+        proof.verify(b"bank", &key, None).unwrap_err(),
+        StateProofError::Ics23(_)
+    ));
+
+    // tampered proof of the iavl store
+    let mut tampered = query.clone();
+    let proof_ops = tampered.proof_ops.as_mut().unwrap();
+    let mut data = base64::decode(&proof_ops.ops[0].data).unwrap();
+    let last = data.len() - 1;
+    data[last] ^= 1;
+    proof_ops.ops[0].data = base64::encode(&data);
+    assert!(state_proof(&tampered, b"bank", &headers).is_err());
+
+    // the header of the query height does not commit to the state of the query
+    let headers_from_query_height = vec![
+        commit(TX_HEIGHT).signed_header.header,
+        commit(HIGHEST_HEIGHT).signed_header.header,
+    ];
+    assert!(matches!(
+        state_proof(&query, b"bank", &headers_from_query_height).unwrap_err(),
+        SfpsError::UnmatchedHeight {
+            expected: 1000009,
+            actual: 1000008
+        }
+    ));
+    let mut state_proof_with_query_header = proof;
+    state_proof_with_query_header.headers =
+        vec![commit(TX_HEIGHT).signed_header.header.to_proto().unwrap()];
+    assert!(matches!(
+        state_proof_with_query_header
+            .verify(b"bank", &key, Some(&value))
+            .unwrap_err(),
+        StateProofError::Ics23(_)
+    ));
+}
+
+#[test]
+fn test_state_proof_without_proof() {
+    let json = r#"{"jsonrpc":"2.0","id":-1,"result":{"response":{"code":0,"log":"","info":"","index":"0","key":"AHVzY3J0","value":"MQ==","proofOps":null,"height":"1000008","codespace":""}}}"#;
+    let query = parse_response::<AbciQueryResult>(json).unwrap().response;
+    assert_eq!(query.key().unwrap(), b"\x00uscrt");
+    assert_eq!(query.value().unwrap().unwrap(), b"1");
+    let headers = vec![commit(HIGHEST_HEIGHT).signed_header.header];
+    assert!(matches!(
+        state_proof(&query, b"bank", &headers).unwrap_err(),
+        SfpsError::NoProofOps
+    ));
+
+    let json = r#"{"jsonrpc":"2.0","id":-1,"result":{"response":{"code":38,"log":"failed to load state at height 1000008","info":"","index":"0","key":null,"value":null,"proofOps":null,"height":"1000008","codespace":"sdk"}}}"#;
+    let query = parse_response::<AbciQueryResult>(json).unwrap().response;
+    assert!(query.key().unwrap().is_empty());
+    assert!(query.value().unwrap().is_none());
+    assert!(matches!(
+        state_proof(&query, b"bank", &headers).unwrap_err(),
+        SfpsError::QueryFailed { code: 38 }
+    ));
+}
+
+#[test]
+fn test_rpc_error() {
+    let json = r#"{"jsonrpc":"2.0","id":-1,"error":{"code":-32603,"message":"Internal error","data":"height 1 is not available, lowest height is 1000000"}}"#;
+    match parse_response::<CommitResult>(json).unwrap_err() {
+        RpcError::Rpc { code, message } => {
+            assert_eq!(code, -32603);
+            assert_eq!(
+                message,
+                "Internal error height 1 is not available, lowest height is 1000000"
+            );
+        }
+        e => panic!("unexpected error {}", e),
+    }
+}
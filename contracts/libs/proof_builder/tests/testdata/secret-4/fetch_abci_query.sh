@@ -0,0 +1,12 @@
+#!/bin/sh
+# Fetches abci_query_1000008.json, the proof of the total supply of uscrt in the bank store of secret-4 at 1000008.
+# The state is committed to 'app_hash' of header 1000009, which is in commit_1000009.json.
+# The rpc must be of an archive node which keeps the state at 1000008.
+#
+# usage: fetch_abci_query.sh <rpc url>
+set -eu
+cd "$(dirname "$0")"
+rpc=$1
+# SupplyKey prefix 0x00 of the bank module followed by the denom.
+key=00$(printf uscrt | od -An -tx1 | tr -d ' \n')
+curl -sf "$rpc/abci_query?path=%22/store/bank/key%22&data=0x$key&height=1000008&prove=true" >abci_query_1000008.json
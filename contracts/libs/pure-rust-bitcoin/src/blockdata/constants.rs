@@ -113,9 +113,48 @@ fn bitcoin_genesis_tx() -> Transaction {
     ret
 }
 
+/// Constructs and returns the coinbase (and only) transaction of the Testnet4 genesis block
+fn testnet4_genesis_tx() -> Transaction {
+    // Base
+    let mut ret = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![],
+    };
+
+    // Inputs
+    let in_script = script::Builder::new().push_scriptint(486604799)
+                                          .push_scriptint(4)
+                                          .push_slice(b"03/May/2024 000000000000000000001ebd58c244970b3aa9d783bb001011fbe8ea8e98e00e")
+                                          .into_script();
+    ret.input.push(TxIn {
+        previous_output: OutPoint::null(),
+        script_sig: in_script,
+        sequence: MAX_SEQUENCE,
+        witness: vec![],
+    });
+
+    // Outputs
+    let out_script = script::Builder::new()
+        .push_slice(&[0u8; 33])
+        .push_opcode(opcodes::all::OP_CHECKSIG)
+        .into_script();
+    ret.output.push(TxOut {
+        value: 50 * COIN_VALUE,
+        script_pubkey: out_script
+    });
+
+    // end
+    ret
+}
+
 /// Constructs and returns the genesis block
 pub fn genesis_block(network: Network) -> Block {
-    let txdata = vec![bitcoin_genesis_tx()];
+    let txdata = match network {
+        Network::Testnet4 => vec![testnet4_genesis_tx()],
+        _ => vec![bitcoin_genesis_tx()],
+    };
     let hash: sha256d::Hash = txdata[0].txid().into();
     let merkle_root = hash.into();
     match network {
@@ -171,6 +210,19 @@ pub fn genesis_block(network: Network) -> Block {
                 txdata: txdata
             }
         }
+        Network::Testnet4 => {
+            Block {
+                header: BlockHeader {
+                    version: 1,
+                    prev_blockhash: Default::default(),
+                    merkle_root,
+                    time: 1714777860,
+                    bits: 0x1d00ffff,
+                    nonce: 393743547
+                },
+                txdata: txdata
+            }
+        }
     }
 }
 
@@ -235,6 +287,20 @@ mod test {
                    "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943".to_string());
     }
 
+    #[test]
+    fn testnet4_genesis_full_block() {
+        let gen = genesis_block(Network::Testnet4);
+        assert_eq!(gen.header.version, 1);
+        assert_eq!(gen.header.prev_blockhash, Default::default());
+        assert_eq!(format!("{:x}", gen.header.merkle_root),
+                  "7aa0a7ae1e223414cb807e40cd57e667b718e42aaf9306db9102fe28912b7b4e".to_string());
+        assert_eq!(gen.header.time, 1714777860);
+        assert_eq!(gen.header.bits, 0x1d00ffff);
+        assert_eq!(gen.header.nonce, 393743547);
+        assert_eq!(format!("{:x}", gen.header.block_hash()),
+                   "00000000da84f2bafbbc53dee25a72ae507ff4914b867c565be350b0da8bf043".to_string());
+    }
+
     #[test]
     fn signet_genesis_full_block() {
         let gen = genesis_block(Network::Signet);
@@ -85,6 +85,8 @@ pub struct Params {
     pub allow_min_difficulty_blocks: bool,
     /// Determines whether retargeting is disabled for this network or not.
     pub no_pow_retargeting: bool,
+    /// Determines whether BIP94 rules (timewarp fix and retargeting from the first block of the period) are enforced or not.
+    pub enforce_bip94: bool,
 }
 
 impl Params {
@@ -104,6 +106,7 @@ impl Params {
                 pow_target_timespan: 14 * 24 * 60 * 60, // 2 weeks.
                 allow_min_difficulty_blocks: false,
                 no_pow_retargeting: false,
+                enforce_bip94: false,
             },
             Network::Testnet => Params {
                 network: Network::Testnet,
@@ -118,6 +121,7 @@ impl Params {
                 pow_target_timespan: 14 * 24 * 60 * 60, // 2 weeks.
                 allow_min_difficulty_blocks: true,
                 no_pow_retargeting: false,
+                enforce_bip94: false,
             },
             Network::Signet => Params {
                 network: Network::Signet,
@@ -132,6 +136,22 @@ impl Params {
                 pow_target_timespan: 14 * 24 * 60 * 60, // 2 weeks.
                 allow_min_difficulty_blocks: false,
                 no_pow_retargeting: false,
+                enforce_bip94: false,
+            },
+            Network::Testnet4 => Params {
+                network: Network::Testnet4,
+                bip16_time: 1333238400,                 // Apr 1 2012
+                bip34_height: 1,
+                bip65_height: 1,
+                bip66_height: 1,
+                rule_change_activation_threshold: 1512, // 75%
+                miner_confirmation_window: 2016,
+                pow_limit: MAX_BITS_TESTNET,
+                pow_target_spacing: 10 * 60,            // 10 minutes.
+                pow_target_timespan: 14 * 24 * 60 * 60, // 2 weeks.
+                allow_min_difficulty_blocks: true,
+                no_pow_retargeting: false,
+                enforce_bip94: true,
             },
             Network::Regtest => Params {
                 network: Network::Regtest,
@@ -146,6 +166,7 @@ impl Params {
                 pow_target_timespan: 14 * 24 * 60 * 60, // 2 weeks.
                 allow_min_difficulty_blocks: true,
                 no_pow_retargeting: true,
+                enforce_bip94: false,
             },
         }
     }
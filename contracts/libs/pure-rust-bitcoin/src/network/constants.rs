@@ -69,6 +69,8 @@ user_enum! {
         Testnet <-> "testnet",
         /// Bitcoin's signet
         Signet <-> "signet",
+        /// Bitcoin's testnet4
+        Testnet4 <-> "testnet4",
         /// Bitcoin's regtest
         Regtest <-> "regtest"
     }
@@ -91,6 +93,7 @@ impl Network {
             0xD9B4BEF9 => Some(Network::Bitcoin),
             0x0709110B => Some(Network::Testnet),
             0x40CF030A => Some(Network::Signet),
+            0x283F161C => Some(Network::Testnet4),
             0xDAB5BFFA => Some(Network::Regtest),
             _ => None
         }
@@ -113,6 +116,7 @@ impl Network {
             Network::Bitcoin => 0xD9B4BEF9,
             Network::Testnet => 0x0709110B,
             Network::Signet  => 0x40CF030A,
+            Network::Testnet4 => 0x283F161C,
             Network::Regtest => 0xDAB5BFFA,
         }
     }
@@ -308,6 +312,10 @@ mod tests {
             serialize(&Network::Signet.magic()),
             &[0x0a, 0x03, 0xcf, 0x40]
         );
+        assert_eq!(
+            serialize(&Network::Testnet4.magic()),
+            &[0x1c, 0x16, 0x3f, 0x28]
+        );
         assert_eq!(
             serialize(&Network::Regtest.magic()),
             &[0xfa, 0xbf, 0xb5, 0xda]
@@ -325,6 +333,10 @@ mod tests {
             deserialize(&[0x0a, 0x03, 0xcf, 0x40]).ok(),
             Some(Network::Signet.magic())
         );
+        assert_eq!(
+            deserialize(&[0x1c, 0x16, 0x3f, 0x28]).ok(),
+            Some(Network::Testnet4.magic())
+        );
         assert_eq!(
             deserialize(&[0xfa, 0xbf, 0xb5, 0xda]).ok(),
             Some(Network::Regtest.magic())
@@ -337,11 +349,13 @@ mod tests {
         assert_eq!(Network::Testnet.to_string(), "testnet");
         assert_eq!(Network::Regtest.to_string(), "regtest");
         assert_eq!(Network::Signet.to_string(), "signet");
+        assert_eq!(Network::Testnet4.to_string(), "testnet4");
 
         assert_eq!("bitcoin".parse::<Network>().unwrap(), Network::Bitcoin);
         assert_eq!("testnet".parse::<Network>().unwrap(), Network::Testnet);
         assert_eq!("regtest".parse::<Network>().unwrap(), Network::Regtest);
         assert_eq!("signet".parse::<Network>().unwrap(), Network::Signet);
+        assert_eq!("testnet4".parse::<Network>().unwrap(), Network::Testnet4);
         assert!("fakenet".parse::<Network>().is_err());
     }
 
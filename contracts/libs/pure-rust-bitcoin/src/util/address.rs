@@ -599,7 +599,7 @@ impl Address {
             (a, b) if a == b => true,
             (Network::Bitcoin, _) | (_, Network::Bitcoin) => false,
             (Network::Regtest, _) | (_, Network::Regtest) if !is_legacy => false,
-            (Network::Testnet, _) | (Network::Regtest, _) | (Network::Signet, _) => true
+            (Network::Testnet, _) | (Network::Regtest, _) | (Network::Signet, _) | (Network::Testnet4, _) => true
         }
     }
 }
@@ -613,7 +613,7 @@ impl fmt::Display for Address {
                 let mut prefixed = [0; 21];
                 prefixed[0] = match self.network {
                     Network::Bitcoin => PUBKEY_ADDRESS_PREFIX_MAIN,
-                    Network::Testnet | Network::Signet | Network::Regtest => PUBKEY_ADDRESS_PREFIX_TEST,
+                    Network::Testnet | Network::Signet | Network::Testnet4 | Network::Regtest => PUBKEY_ADDRESS_PREFIX_TEST,
                 };
                 prefixed[1..].copy_from_slice(&hash[..]);
                 base58::check_encode_slice_to_fmt(fmt, &prefixed[..])
@@ -622,7 +622,7 @@ impl fmt::Display for Address {
                 let mut prefixed = [0; 21];
                 prefixed[0] = match self.network {
                     Network::Bitcoin => SCRIPT_ADDRESS_PREFIX_MAIN,
-                    Network::Testnet | Network::Signet | Network::Regtest => SCRIPT_ADDRESS_PREFIX_TEST,
+                    Network::Testnet | Network::Signet | Network::Testnet4 | Network::Regtest => SCRIPT_ADDRESS_PREFIX_TEST,
                 };
                 prefixed[1..].copy_from_slice(&hash[..]);
                 base58::check_encode_slice_to_fmt(fmt, &prefixed[..])
@@ -633,7 +633,7 @@ impl fmt::Display for Address {
             } => {
                 let hrp = match self.network {
                     Network::Bitcoin => "bc",
-                    Network::Testnet | Network::Signet => "tb",
+                    Network::Testnet | Network::Signet | Network::Testnet4 => "tb",
                     Network::Regtest => "bcrt",
                 };
                 let mut upper_writer;
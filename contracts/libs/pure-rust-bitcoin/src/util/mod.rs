@@ -26,7 +26,7 @@ pub mod bip32;
 pub mod bip143;
 //pub mod contracthash;
 pub mod hash;
-//pub mod merkleblock;
+pub mod merkleblock;
 pub mod misc;
 pub mod psbt;
 pub mod taproot;
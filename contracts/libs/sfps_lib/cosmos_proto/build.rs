@@ -5,7 +5,10 @@ fn main() -> Result<()> {
             "proto/tendermint/types/types.proto",
             "proto/tendermint/types/canonical.proto",
             "proto/tendermint/abci/types.proto",
+            "proto/tendermint/abci/exec_tx_result.proto",
             "proto/cosmos/base/abci/v1beta1/abci.proto",
+            "proto/secret/compute/v1beta1/msg.proto",
+            "proto/confio/proofs.proto",
         ],
         &["proto"],
     )?;
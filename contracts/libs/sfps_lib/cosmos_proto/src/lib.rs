@@ -26,3 +26,15 @@ pub mod cosmos {
         }
     }
 }
+
+pub mod secret {
+    pub mod compute {
+        pub mod v1beta1 {
+            include!(concat!(env!("OUT_DIR"), "/secret.compute.v1beta1.rs"));
+        }
+    }
+}
+
+pub mod ics23 {
+    include!(concat!(env!("OUT_DIR"), "/ics23.rs"));
+}
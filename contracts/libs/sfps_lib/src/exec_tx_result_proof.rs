@@ -0,0 +1,182 @@
+use crate::merkle::MerkleProof;
+use crate::response_deliver_tx_proof::verify_headers_connected;
+pub use crate::response_deliver_tx_proof::Error;
+use cosmos_proto::cosmos::base::abci::v1beta1::TxMsgData;
+use cosmos_proto::prost::Message;
+use cosmos_proto::tendermint::abci::ExecTxResult;
+use cosmos_proto::tendermint::types::Header;
+
+pub fn tx_msg_data_of_exec_tx_result(exec_tx_result: &ExecTxResult) -> Result<TxMsgData, Error> {
+    TxMsgData::decode(exec_tx_result.data.as_slice()).map_err(Error::DecodeTxMsgData)
+}
+
+/// encodes the deterministic fields of ExecTxResult, which are committed to 'last_results_hash'.
+pub fn encode_exec_tx_result_as_merkle_leaf(exec_tx_result: &ExecTxResult) -> Vec<u8> {
+    ExecTxResult {
+        code: exec_tx_result.code,
+        data: exec_tx_result.data.clone(),
+        gas_wanted: exec_tx_result.gas_wanted,
+        gas_used: exec_tx_result.gas_used,
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+// Merkle Proof for ExecTxResult of CometBFT v0.38 ResponseFinalizeBlock.
+// This proof is valid when and only when all of 1-3 proposition are correct.
+// 1. (1 < a < length(headers)) header[a] contains hash of header[a-1] as it's last_block_id.hash'
+// 2. the first element of headers(= the lowest header of headers) contains merkle root of merkle proof as it's 'last_result_hash'
+// 3. merkle proof includes exec_tx_result as it's leaf
+//
+// The header layout is not changed from Tendermint v0.34, so headers are hashed by 'hash_header' as well.
+// Only code, data, gas_wanted and gas_used of ExecTxResult are committed to 'last_result_hash'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecTxResultProof {
+    pub merkle_proof: MerkleProof,
+    pub headers: Vec<Header>,
+}
+
+impl ExecTxResultProof {
+    // if tx result proof is correct, returns hash of the highest header of connected headers.
+    pub fn verify(&self) -> Result<Vec<u8>, Error> {
+        let highest_header_hash = verify_headers_connected(&self.headers)?;
+        let results_hash = self.headers.first().unwrap().last_results_hash.clone();
+        self.merkle_proof.verify(results_hash)?;
+        Ok(highest_header_hash)
+    }
+
+    pub fn leaf_exec_tx_result(&self) -> Result<ExecTxResult, Error> {
+        Ok(ExecTxResult::decode(self.merkle_proof.leaf.as_slice())?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::header::hash_header;
+    use crate::merkle::simple_hash_from_byte_vectors;
+    use crate::response_deliver_tx_proof::{
+        encode_response_deliver_tx_as_merkle_leaf, execute_contract_response_data,
+        MSG_EXECUTE_CONTRACT_RESPONSE_TYPE_URL,
+    };
+    use cosmos_proto::prost_types::{Any, Timestamp};
+    use cosmos_proto::secret::compute::v1beta1::MsgExecuteContractResponse;
+    use cosmos_proto::tendermint::abci::ResponseDeliverTx;
+    use cosmos_proto::tendermint::types::*;
+    use cosmos_proto::tendermint::version::*;
+
+    fn exec_tx_result() -> ExecTxResult {
+        ExecTxResult {
+            code: 0,
+            data: TxMsgData {
+                data: vec![],
+                msg_responses: vec![Any {
+                    type_url: MSG_EXECUTE_CONTRACT_RESPONSE_TYPE_URL.to_string(),
+                    value: MsgExecuteContractResponse {
+                        data: vec![1, 2, 3],
+                    }
+                    .encode_to_vec(),
+                }],
+            }
+            .encode_to_vec(),
+            log: "log".to_string(),
+            info: "info".to_string(),
+            gas_wanted: 50000,
+            gas_used: 23757,
+            events: vec![],
+            codespace: "".to_string(),
+        }
+    }
+
+    fn header(height: i64, last_block_hash: Vec<u8>, last_results_hash: Vec<u8>) -> Header {
+        Header {
+            version: Some(Consensus { app: 0, block: 11 }),
+            chain_id: "chain_id".to_string(),
+            height,
+            time: Some(Timestamp {
+                seconds: 0,
+                nanos: 0,
+            }),
+            last_block_id: Some(BlockId {
+                hash: last_block_hash,
+                part_set_header: Some(PartSetHeader {
+                    total: 0,
+                    hash: [0; 32].to_vec(),
+                }),
+            }),
+            last_commit_hash: [0; 32].to_vec(),
+            data_hash: [0; 32].to_vec(),
+            validators_hash: [0; 32].to_vec(),
+            next_validators_hash: [0; 32].to_vec(),
+            consensus_hash: [0; 32].to_vec(),
+            app_hash: [0; 32].to_vec(),
+            last_results_hash,
+            evidence_hash: [0; 32].to_vec(),
+            proposer_address: [0; 20].to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_exec_tx_result_encode_as_merkle_leaf() {
+        let exec_tx_result = exec_tx_result();
+        let encoded = encode_exec_tx_result_as_merkle_leaf(&exec_tx_result);
+        // the deterministic encoding is the same with the one of ResponseDeliverTx.
+        assert_eq!(
+            encoded,
+            encode_response_deliver_tx_as_merkle_leaf(&ResponseDeliverTx {
+                code: exec_tx_result.code,
+                data: exec_tx_result.data.clone(),
+                log: exec_tx_result.log.clone(),
+                info: exec_tx_result.info.clone(),
+                gas_wanted: exec_tx_result.gas_wanted,
+                gas_used: exec_tx_result.gas_used,
+                events: vec![],
+                codespace: exec_tx_result.codespace.clone(),
+            })
+        );
+        assert_eq!(
+            ExecTxResult::decode(encoded.as_slice()).unwrap(),
+            ExecTxResult {
+                log: "".to_string(),
+                info: "".to_string(),
+                ..exec_tx_result
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_exec_tx_result_proof() {
+        let other_leaf = encode_exec_tx_result_as_merkle_leaf(&ExecTxResult::default());
+        let leaf = encode_exec_tx_result_as_merkle_leaf(&exec_tx_result());
+        let results_hash = simple_hash_from_byte_vectors(vec![other_leaf.clone(), leaf.clone()]);
+        let first = header(10, [0; 32].to_vec(), results_hash);
+        let second = header(11, hash_header(&first), [0; 32].to_vec());
+        let proof = ExecTxResultProof {
+            merkle_proof: MerkleProof {
+                total: 2,
+                index: 1,
+                leaf,
+                aunts: vec![simple_hash_from_byte_vectors(vec![other_leaf])],
+            },
+            headers: vec![first, second.clone()],
+        };
+        assert_eq!(proof.verify().unwrap(), hash_header(&second));
+
+        let tx_msg_data =
+            tx_msg_data_of_exec_tx_result(&proof.leaf_exec_tx_result().unwrap()).unwrap();
+        assert_eq!(
+            execute_contract_response_data(tx_msg_data, 0).unwrap(),
+            vec![1, 2, 3]
+        );
+
+        let mut invalid_proof = proof;
+        invalid_proof.merkle_proof.leaf = encode_exec_tx_result_as_merkle_leaf(&ExecTxResult {
+            code: 1,
+            ..exec_tx_result()
+        });
+        assert!(matches!(
+            invalid_proof.verify().unwrap_err(),
+            Error::MerkleProof(_)
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::header::hash_header;
-use crate::light_block::Ed25519Verifier;
 use crate::light_block::Error as LightBlockError;
+use crate::light_block::SignatureVerifier;
 use crate::light_block::{validate_light_block, verify_light_block};
 use crate::response_deliver_tx_proof::{
     Error as ResponseDeliverTxProofError, ResponseDeliverTxProof,
@@ -99,11 +99,11 @@ impl<C: ChainDB> HeaderChain<C> {
             .map_err(|e| Error::ChainDB(format!("{}", e)))
     }
 
-    pub fn add_block_to_highest<S: ToString, E: Ed25519Verifier<S>>(
+    pub fn add_block_to_highest<S: ToString, E: SignatureVerifier<S>>(
         &mut self,
         current_highest_header: &Header,
         light_block: LightBlock,
-        ed25519_verifier: &mut E,
+        signature_verifier: &mut E,
     ) -> Result<(), Error> {
         validate_light_block(&light_block)?;
         {
@@ -134,16 +134,16 @@ impl<C: ChainDB> HeaderChain<C> {
         self.verify_block(
             &current_highest_header.next_validators_hash,
             &light_block,
-            ed25519_verifier,
+            signature_verifier,
         )?;
         self.append_header(light_block.signed_header.unwrap().header.unwrap())
     }
 
-    fn verify_block<S: ToString, E: Ed25519Verifier<S>>(
+    fn verify_block<S: ToString, E: SignatureVerifier<S>>(
         &self,
         validators_hash: &[u8],
         light_block: &LightBlock,
-        ed25519_verifier: &mut E,
+        signature_verifier: &mut E,
     ) -> Result<(), Error> {
         if validators_hash
             != light_block
@@ -157,7 +157,7 @@ impl<C: ChainDB> HeaderChain<C> {
         {
             return Err(Error::UnmatchedValidatorsHash);
         }
-        verify_light_block(light_block, ed25519_verifier)?;
+        verify_light_block(light_block, signature_verifier)?;
         Ok(())
     }
 
@@ -0,0 +1,695 @@
+//! Verifier of ICS23 commitment proofs.
+//! Supports the existence and non-existence proofs of IAVL stores and of the simple merkle tree of the multistore.
+use cosmos_proto::ics23::commitment_proof::Proof;
+use cosmos_proto::ics23::{
+    CommitmentProof, ExistenceProof, HashOp, InnerOp, InnerSpec, LeafOp, LengthOp,
+    NonExistenceProof, ProofSpec,
+};
+use cosmos_proto::prost::encoding::encode_varint;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    UnsupportedHashOp(i32),
+    UnsupportedLengthOp(i32),
+    InvalidLengthOpInput { expected: usize, actual: usize },
+    EmptyChild,
+    NoLeafOp,
+    NoInnerSpec,
+    UnmatchedLeafOp,
+    UnmatchedInnerOpHash,
+    InnerOpPrefixStartsWithLeafPrefix,
+    InvalidInnerOpPrefixLength(usize),
+    InvalidInnerOpSuffixLength(usize),
+    InvalidDepth { min: i32, max: i32, actual: usize },
+    UnmatchedKey,
+    UnmatchedValue,
+    UnmatchedRoot,
+    NoExistenceProof,
+    NoNonExistenceProof,
+    NoNeighbors,
+    LeftKeyNotSmaller,
+    RightKeyNotLarger,
+    NotLeftMost,
+    NotRightMost,
+    NotLeftNeighbor,
+    BranchNotFound,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnsupportedHashOp(op) => write!(f, "unsupported hash op: {}", op),
+            Error::UnsupportedLengthOp(op) => write!(f, "unsupported length op: {}", op),
+            Error::InvalidLengthOpInput { expected, actual } => write!(
+                f,
+                "invalid length op input: expected {}, actual {}",
+                expected, actual
+            ),
+            Error::EmptyChild => f.write_str("empty child"),
+            Error::NoLeafOp => f.write_str("no leaf op"),
+            Error::NoInnerSpec => f.write_str("no inner spec"),
+            Error::UnmatchedLeafOp => f.write_str("unmatched leaf op"),
+            Error::UnmatchedInnerOpHash => f.write_str("unmatched inner op hash"),
+            Error::InnerOpPrefixStartsWithLeafPrefix => {
+                f.write_str("inner op prefix starts with leaf prefix")
+            }
+            Error::InvalidInnerOpPrefixLength(length) => {
+                write!(f, "invalid inner op prefix length: {}", length)
+            }
+            Error::InvalidInnerOpSuffixLength(length) => {
+                write!(f, "invalid inner op suffix length: {}", length)
+            }
+            Error::InvalidDepth { min, max, actual } => write!(
+                f,
+                "invalid depth: min {}, max {}, actual {}",
+                min, max, actual
+            ),
+            Error::UnmatchedKey => f.write_str("unmatched key"),
+            Error::UnmatchedValue => f.write_str("unmatched value"),
+            Error::UnmatchedRoot => f.write_str("unmatched root"),
+            Error::NoExistenceProof => f.write_str("no existence proof"),
+            Error::NoNonExistenceProof => f.write_str("no non existence proof"),
+            Error::NoNeighbors => f.write_str("no neighbors"),
+            Error::LeftKeyNotSmaller => f.write_str("left key is not smaller than key"),
+            Error::RightKeyNotLarger => f.write_str("right key is not larger than key"),
+            Error::NotLeftMost => f.write_str("not left most"),
+            Error::NotRightMost => f.write_str("not right most"),
+            Error::NotLeftNeighbor => f.write_str("not left neighbor"),
+            Error::BranchNotFound => f.write_str("branch not found"),
+        }
+    }
+}
+
+/// ProofSpec of the IAVL stores of cosmos-sdk.
+pub fn iavl_spec() -> ProofSpec {
+    ProofSpec {
+        leaf_spec: Some(LeafOp {
+            hash: HashOp::Sha256 as i32,
+            prehash_key: HashOp::NoHash as i32,
+            prehash_value: HashOp::Sha256 as i32,
+            length: LengthOp::VarProto as i32,
+            prefix: vec![0],
+        }),
+        inner_spec: Some(InnerSpec {
+            child_order: vec![0, 1],
+            child_size: 33,
+            min_prefix_length: 4,
+            max_prefix_length: 12,
+            empty_child: vec![],
+            hash: HashOp::Sha256 as i32,
+        }),
+        max_depth: 0,
+        min_depth: 0,
+    }
+}
+
+/// ProofSpec of the simple merkle tree of Tendermint, which commits the store roots of the multistore to 'app_hash'.
+pub fn tendermint_spec() -> ProofSpec {
+    ProofSpec {
+        leaf_spec: Some(LeafOp {
+            hash: HashOp::Sha256 as i32,
+            prehash_key: HashOp::NoHash as i32,
+            prehash_value: HashOp::Sha256 as i32,
+            length: LengthOp::VarProto as i32,
+            prefix: vec![0],
+        }),
+        inner_spec: Some(InnerSpec {
+            child_order: vec![0, 1],
+            child_size: 32,
+            min_prefix_length: 1,
+            max_prefix_length: 1,
+            empty_child: vec![],
+            hash: HashOp::Sha256 as i32,
+        }),
+        max_depth: 0,
+        min_depth: 0,
+    }
+}
+
+/// verifies that the proof proves `key` is set to `value` in the tree of `root`.
+pub fn verify_membership(
+    proof: &CommitmentProof,
+    spec: &ProofSpec,
+    root: &[u8],
+    key: &[u8],
+    value: &[u8],
+) -> Result<(), Error> {
+    match &proof.proof {
+        Some(Proof::Exist(existence_proof)) => {
+            verify_existence(existence_proof, spec, root, key, value)
+        }
+        _ => Err(Error::NoExistenceProof),
+    }
+}
+
+/// verifies that the proof proves `key` is not set in the tree of `root`.
+pub fn verify_non_membership(
+    proof: &CommitmentProof,
+    spec: &ProofSpec,
+    root: &[u8],
+    key: &[u8],
+) -> Result<(), Error> {
+    match &proof.proof {
+        Some(Proof::Nonexist(non_existence_proof)) => {
+            verify_non_existence(non_existence_proof, spec, root, key)
+        }
+        _ => Err(Error::NoNonExistenceProof),
+    }
+}
+
+/// calculates the root hash of the tree which the proof is against.
+pub fn calculate_root(proof: &CommitmentProof) -> Result<Vec<u8>, Error> {
+    match &proof.proof {
+        Some(Proof::Exist(existence_proof)) => calculate_existence_root(existence_proof),
+        Some(Proof::Nonexist(non_existence_proof)) => {
+            match (&non_existence_proof.left, &non_existence_proof.right) {
+                (Some(left), _) => calculate_existence_root(left),
+                (None, Some(right)) => calculate_existence_root(right),
+                (None, None) => Err(Error::NoNeighbors),
+            }
+        }
+        _ => Err(Error::NoExistenceProof),
+    }
+}
+
+pub fn calculate_existence_root(proof: &ExistenceProof) -> Result<Vec<u8>, Error> {
+    let leaf = proof.leaf.as_ref().ok_or(Error::NoLeafOp)?;
+    let mut hash = apply_leaf(leaf, &proof.key, &proof.value)?;
+    for step in proof.path.iter() {
+        hash = apply_inner(step, &hash)?;
+    }
+    Ok(hash)
+}
+
+fn verify_existence(
+    proof: &ExistenceProof,
+    spec: &ProofSpec,
+    root: &[u8],
+    key: &[u8],
+    value: &[u8],
+) -> Result<(), Error> {
+    check_against_spec(proof, spec)?;
+    if proof.key != key {
+        return Err(Error::UnmatchedKey);
+    }
+    if proof.value != value {
+        return Err(Error::UnmatchedValue);
+    }
+    if calculate_existence_root(proof)? != root {
+        return Err(Error::UnmatchedRoot);
+    }
+    Ok(())
+}
+
+fn verify_non_existence(
+    proof: &NonExistenceProof,
+    spec: &ProofSpec,
+    root: &[u8],
+    key: &[u8],
+) -> Result<(), Error> {
+    if let Some(left) = &proof.left {
+        verify_existence(left, spec, root, &left.key, &left.value)?;
+        if left.key.as_slice() >= key {
+            return Err(Error::LeftKeyNotSmaller);
+        }
+    }
+    if let Some(right) = &proof.right {
+        verify_existence(right, spec, root, &right.key, &right.value)?;
+        if right.key.as_slice() <= key {
+            return Err(Error::RightKeyNotLarger);
+        }
+    }
+    let inner_spec = spec.inner_spec.as_ref().ok_or(Error::NoInnerSpec)?;
+    match (&proof.left, &proof.right) {
+        (None, None) => Err(Error::NoNeighbors),
+        (None, Some(right)) => ensure_left_most(inner_spec, &right.path),
+        (Some(left), None) => ensure_right_most(inner_spec, &left.path),
+        (Some(left), Some(right)) => ensure_left_neighbor(inner_spec, &left.path, &right.path),
+    }
+}
+
+fn check_against_spec(proof: &ExistenceProof, spec: &ProofSpec) -> Result<(), Error> {
+    let leaf = proof.leaf.as_ref().ok_or(Error::NoLeafOp)?;
+    let leaf_spec = spec.leaf_spec.as_ref().ok_or(Error::NoLeafOp)?;
+    let inner_spec = spec.inner_spec.as_ref().ok_or(Error::NoInnerSpec)?;
+    if leaf.hash != leaf_spec.hash
+        || leaf.prehash_key != leaf_spec.prehash_key
+        || leaf.prehash_value != leaf_spec.prehash_value
+        || leaf.length != leaf_spec.length
+        || !leaf.prefix.starts_with(&leaf_spec.prefix)
+    {
+        return Err(Error::UnmatchedLeafOp);
+    }
+    let depth = proof.path.len();
+    if (spec.min_depth > 0 && depth < spec.min_depth as usize)
+        || (spec.max_depth > 0 && depth > spec.max_depth as usize)
+    {
+        return Err(Error::InvalidDepth {
+            min: spec.min_depth,
+            max: spec.max_depth,
+            actual: depth,
+        });
+    }
+    for step in proof.path.iter() {
+        check_inner_op(step, &leaf_spec.prefix, inner_spec)?;
+    }
+    Ok(())
+}
+
+fn check_inner_op(inner: &InnerOp, leaf_prefix: &[u8], spec: &InnerSpec) -> Result<(), Error> {
+    if inner.hash != spec.hash {
+        return Err(Error::UnmatchedInnerOpHash);
+    }
+    if !leaf_prefix.is_empty() && inner.prefix.starts_with(leaf_prefix) {
+        return Err(Error::InnerOpPrefixStartsWithLeafPrefix);
+    }
+    let child_size = spec.child_size as usize;
+    let max_left_child_bytes = (spec.child_order.len().saturating_sub(1)) * child_size;
+    if inner.prefix.len() < spec.min_prefix_length as usize
+        || inner.prefix.len() > spec.max_prefix_length as usize + max_left_child_bytes
+    {
+        return Err(Error::InvalidInnerOpPrefixLength(inner.prefix.len()));
+    }
+    if child_size == 0 || inner.suffix.len() % child_size != 0 {
+        return Err(Error::InvalidInnerOpSuffixLength(inner.suffix.len()));
+    }
+    Ok(())
+}
+
+fn apply_leaf(leaf: &LeafOp, key: &[u8], value: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut data = leaf.prefix.clone();
+    data.extend(prepare_leaf_data(leaf.prehash_key, leaf.length, key)?);
+    data.extend(prepare_leaf_data(leaf.prehash_value, leaf.length, value)?);
+    do_hash(leaf.hash, &data)
+}
+
+fn apply_inner(inner: &InnerOp, child: &[u8]) -> Result<Vec<u8>, Error> {
+    if child.is_empty() {
+        return Err(Error::EmptyChild);
+    }
+    let mut data = inner.prefix.clone();
+    data.extend_from_slice(child);
+    data.extend_from_slice(&inner.suffix);
+    do_hash(inner.hash, &data)
+}
+
+fn prepare_leaf_data(prehash: i32, length: i32, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let hashed = match HashOp::from_i32(prehash) {
+        Some(HashOp::NoHash) => data.to_vec(),
+        _ => do_hash(prehash, data)?,
+    };
+    do_length(length, &hashed)
+}
+
+fn do_hash(hash_op: i32, data: &[u8]) -> Result<Vec<u8>, Error> {
+    match HashOp::from_i32(hash_op) {
+        Some(HashOp::Sha256) => Ok(Sha256::digest(data).to_vec()),
+        Some(HashOp::Sha512) => Ok(Sha512::digest(data).to_vec()),
+        _ => Err(Error::UnsupportedHashOp(hash_op)),
+    }
+}
+
+fn do_length(length_op: i32, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut result = vec![];
+    match LengthOp::from_i32(length_op) {
+        Some(LengthOp::NoPrefix) => {}
+        Some(LengthOp::VarProto) => encode_varint(data.len() as u64, &mut result),
+        Some(LengthOp::Fixed32Big) => result.extend((data.len() as u32).to_be_bytes()),
+        Some(LengthOp::Fixed32Little) => result.extend((data.len() as u32).to_le_bytes()),
+        Some(LengthOp::Fixed64Big) => result.extend((data.len() as u64).to_be_bytes()),
+        Some(LengthOp::Fixed64Little) => result.extend((data.len() as u64).to_le_bytes()),
+        Some(LengthOp::Require32Bytes) => check_length(32, data)?,
+        Some(LengthOp::Require64Bytes) => check_length(64, data)?,
+        _ => return Err(Error::UnsupportedLengthOp(length_op)),
+    }
+    result.extend_from_slice(data);
+    Ok(result)
+}
+
+fn check_length(expected: usize, data: &[u8]) -> Result<(), Error> {
+    if data.len() != expected {
+        return Err(Error::InvalidLengthOpInput {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+// Byte lengths of the sibling children which an InnerOp on the given branch includes.
+struct Padding {
+    min_prefix: usize,
+    max_prefix: usize,
+    suffix: usize,
+}
+
+fn get_position(spec: &InnerSpec, branch: usize) -> Result<usize, Error> {
+    spec.child_order
+        .iter()
+        .position(|order| *order as usize == branch)
+        .ok_or(Error::BranchNotFound)
+}
+
+fn get_padding(spec: &InnerSpec, branch: usize) -> Result<Padding, Error> {
+    let position = get_position(spec, branch)?;
+    let child_size = spec.child_size as usize;
+    let prefix = position * child_size;
+    Ok(Padding {
+        min_prefix: prefix + spec.min_prefix_length as usize,
+        max_prefix: prefix + spec.max_prefix_length as usize,
+        suffix: (spec.child_order.len() - 1 - position) * child_size,
+    })
+}
+
+fn has_padding(inner: &InnerOp, padding: &Padding) -> bool {
+    inner.prefix.len() >= padding.min_prefix
+        && inner.prefix.len() <= padding.max_prefix
+        && inner.suffix.len() == padding.suffix
+}
+
+// returns the branch of the child which the InnerOp is applied to.
+fn order_from_padding(spec: &InnerSpec, inner: &InnerOp) -> Result<usize, Error> {
+    for branch in 0..spec.child_order.len() {
+        if has_padding(inner, &get_padding(spec, branch)?) {
+            return Ok(branch);
+        }
+    }
+    Err(Error::BranchNotFound)
+}
+
+// returns true if all the children on the left of the InnerOp are empty.
+fn left_branches_are_empty(spec: &InnerSpec, inner: &InnerOp) -> Result<bool, Error> {
+    let position = get_position(spec, order_from_padding(spec, inner)?)?;
+    if position == 0 {
+        return Ok(false);
+    }
+    let child_size = spec.child_size as usize;
+    let left_children = match inner.prefix.len().checked_sub(position * child_size) {
+        Some(start) => &inner.prefix[start..],
+        None => return Ok(false),
+    };
+    Ok(left_children
+        .chunks(child_size)
+        .all(|child| child == spec.empty_child.as_slice()))
+}
+
+// returns true if all the children on the right of the InnerOp are empty.
+fn right_branches_are_empty(spec: &InnerSpec, inner: &InnerOp) -> Result<bool, Error> {
+    let position = get_position(spec, order_from_padding(spec, inner)?)?;
+    if position == spec.child_order.len() - 1 {
+        return Ok(false);
+    }
+    Ok(inner
+        .suffix
+        .chunks(spec.child_size as usize)
+        .all(|child| child == spec.empty_child.as_slice()))
+}
+
+fn ensure_left_most(spec: &InnerSpec, path: &[InnerOp]) -> Result<(), Error> {
+    let padding = get_padding(spec, 0)?;
+    for step in path {
+        if !has_padding(step, &padding) && !left_branches_are_empty(spec, step)? {
+            return Err(Error::NotLeftMost);
+        }
+    }
+    Ok(())
+}
+
+fn ensure_right_most(spec: &InnerSpec, path: &[InnerOp]) -> Result<(), Error> {
+    let padding = get_padding(spec, spec.child_order.len() - 1)?;
+    for step in path {
+        if !has_padding(step, &padding) && !right_branches_are_empty(spec, step)? {
+            return Err(Error::NotRightMost);
+        }
+    }
+    Ok(())
+}
+
+// ensures the leaves of the paths are adjacent.
+// the paths must share the top steps, then diverge to the adjacent branches,
+// and the rest of the left path must be right most and the rest of the right path must be left most.
+fn ensure_left_neighbor(
+    spec: &InnerSpec,
+    left_path: &[InnerOp],
+    right_path: &[InnerOp],
+) -> Result<(), Error> {
+    let mut left_path = left_path.to_vec();
+    let mut right_path = right_path.to_vec();
+    let mut top_left = left_path.pop().ok_or(Error::NotLeftNeighbor)?;
+    let mut top_right = right_path.pop().ok_or(Error::NotLeftNeighbor)?;
+    while top_left.prefix == top_right.prefix && top_left.suffix == top_right.suffix {
+        top_left = left_path.pop().ok_or(Error::NotLeftNeighbor)?;
+        top_right = right_path.pop().ok_or(Error::NotLeftNeighbor)?;
+    }
+    if order_from_padding(spec, &top_left)? + 1 != order_from_padding(spec, &top_right)? {
+        return Err(Error::NotLeftNeighbor);
+    }
+    ensure_right_most(spec, &left_path)?;
+    ensure_left_most(spec, &right_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::merkle::simple_hash_from_byte_vectors;
+
+    fn tendermint_leaf_op() -> LeafOp {
+        tendermint_spec().leaf_spec.unwrap()
+    }
+
+    fn tendermint_inner_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+        let mut data = vec![1];
+        data.extend_from_slice(left);
+        data.extend_from_slice(right);
+        Sha256::digest(&data).to_vec()
+    }
+
+    fn kv_pair_bytes(key: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![];
+        encode_varint(key.len() as u64, &mut bytes);
+        bytes.extend_from_slice(key);
+        let value_hash = Sha256::digest(value);
+        encode_varint(value_hash.len() as u64, &mut bytes);
+        bytes.extend_from_slice(&value_hash);
+        bytes
+    }
+
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    // 4 leaves tree which is the same as the multistore commits store roots.
+    fn tendermint_tree() -> (KeyValues, Vec<u8>) {
+        let kvs: KeyValues = vec![
+            (b"acc".to_vec(), vec![1; 32]),
+            (b"bank".to_vec(), vec![2; 32]),
+            (b"compute".to_vec(), vec![3; 32]),
+            (b"staking".to_vec(), vec![4; 32]),
+        ];
+        let root = simple_hash_from_byte_vectors(
+            kvs.iter()
+                .map(|(key, value)| kv_pair_bytes(key, value))
+                .collect(),
+        );
+        (kvs, root)
+    }
+
+    fn tendermint_existence_proof(kvs: &[(Vec<u8>, Vec<u8>)], index: usize) -> ExistenceProof {
+        let leaf_hashes: Vec<Vec<u8>> = kvs
+            .iter()
+            .map(|(key, value)| simple_hash_from_byte_vectors(vec![kv_pair_bytes(key, value)]))
+            .collect();
+        let sibling = leaf_hashes[index ^ 1].clone();
+        let other_pair = if index < 2 {
+            tendermint_inner_hash(&leaf_hashes[2], &leaf_hashes[3])
+        } else {
+            tendermint_inner_hash(&leaf_hashes[0], &leaf_hashes[1])
+        };
+        let step = |sibling: Vec<u8>, is_left: bool| {
+            if is_left {
+                InnerOp {
+                    hash: HashOp::Sha256 as i32,
+                    prefix: vec![1],
+                    suffix: sibling,
+                }
+            } else {
+                InnerOp {
+                    hash: HashOp::Sha256 as i32,
+                    prefix: [vec![1], sibling].concat(),
+                    suffix: vec![],
+                }
+            }
+        };
+        ExistenceProof {
+            key: kvs[index].0.clone(),
+            value: kvs[index].1.clone(),
+            leaf: Some(tendermint_leaf_op()),
+            path: vec![step(sibling, index % 2 == 0), step(other_pair, index < 2)],
+        }
+    }
+
+    #[test]
+    fn test_verify_membership_tendermint() {
+        let (kvs, root) = tendermint_tree();
+        let spec = tendermint_spec();
+        for index in 0..kvs.len() {
+            let proof = CommitmentProof {
+                proof: Some(Proof::Exist(tendermint_existence_proof(&kvs, index))),
+            };
+            assert_eq!(calculate_root(&proof).unwrap(), root);
+            verify_membership(&proof, &spec, &root, &kvs[index].0, &kvs[index].1).unwrap();
+            assert_eq!(
+                verify_membership(&proof, &spec, &root, &kvs[index].0, &[0; 32]).unwrap_err(),
+                Error::UnmatchedValue
+            );
+            assert_eq!(
+                verify_membership(&proof, &spec, &root, b"other", &kvs[index].1).unwrap_err(),
+                Error::UnmatchedKey
+            );
+            assert_eq!(
+                verify_membership(&proof, &spec, &[0; 32], &kvs[index].0, &kvs[index].1)
+                    .unwrap_err(),
+                Error::UnmatchedRoot
+            );
+        }
+        let proof = CommitmentProof {
+            proof: Some(Proof::Exist(tendermint_existence_proof(&kvs, 0))),
+        };
+        assert_eq!(
+            verify_membership(&proof, &iavl_spec(), &root, &kvs[0].0, &kvs[0].1).unwrap_err(),
+            Error::InvalidInnerOpPrefixLength(1)
+        );
+    }
+
+    #[test]
+    fn test_verify_non_membership_tendermint() {
+        let (kvs, root) = tendermint_tree();
+        let spec = tendermint_spec();
+        let non_existence_proof = |left: Option<usize>, right: Option<usize>| CommitmentProof {
+            proof: Some(Proof::Nonexist(NonExistenceProof {
+                key: vec![],
+                left: left.map(|index| tendermint_existence_proof(&kvs, index)),
+                right: right.map(|index| tendermint_existence_proof(&kvs, index)),
+            })),
+        };
+        // left most
+        verify_non_membership(&non_existence_proof(None, Some(0)), &spec, &root, b"a").unwrap();
+        // neighbors in the same subtree
+        verify_non_membership(&non_existence_proof(Some(0), Some(1)), &spec, &root, b"b").unwrap();
+        // neighbors in the different subtrees
+        verify_non_membership(&non_existence_proof(Some(1), Some(2)), &spec, &root, b"c").unwrap();
+        // right most
+        verify_non_membership(&non_existence_proof(Some(3), None), &spec, &root, b"z").unwrap();
+
+        assert_eq!(
+            verify_non_membership(&non_existence_proof(None, Some(1)), &spec, &root, b"b")
+                .unwrap_err(),
+            Error::NotLeftMost
+        );
+        assert_eq!(
+            verify_non_membership(&non_existence_proof(Some(2), None), &spec, &root, b"d")
+                .unwrap_err(),
+            Error::NotRightMost
+        );
+        assert_eq!(
+            verify_non_membership(&non_existence_proof(Some(0), Some(2)), &spec, &root, b"b")
+                .unwrap_err(),
+            Error::NotRightMost
+        );
+        assert_eq!(
+            verify_non_membership(&non_existence_proof(Some(0), Some(0)), &spec, &root, b"b")
+                .unwrap_err(),
+            Error::RightKeyNotLarger
+        );
+        assert_eq!(
+            verify_non_membership(
+                &non_existence_proof(Some(0), Some(1)),
+                &spec,
+                &root,
+                b"bank"
+            )
+            .unwrap_err(),
+            Error::RightKeyNotLarger
+        );
+        assert_eq!(
+            verify_non_membership(&non_existence_proof(Some(0), Some(1)), &spec, &root, b"acc")
+                .unwrap_err(),
+            Error::LeftKeyNotSmaller
+        );
+        assert_eq!(
+            verify_non_membership(&non_existence_proof(None, None), &spec, &root, b"b")
+                .unwrap_err(),
+            Error::NoNeighbors
+        );
+    }
+
+    fn iavl_leaf(key: &[u8], value: &[u8]) -> (LeafOp, Vec<u8>) {
+        // height 0, size 1, version 1 as zigzag varints
+        let leaf = LeafOp {
+            prefix: vec![0, 2, 2],
+            ..iavl_spec().leaf_spec.unwrap()
+        };
+        let hash = apply_leaf(&leaf, key, value).unwrap();
+        (leaf, hash)
+    }
+
+    #[test]
+    fn test_verify_iavl() {
+        // root (height 1, size 2, version 1) of leaves "a" and "c"
+        let (left_leaf, left_hash) = iavl_leaf(b"a", b"value_a");
+        let (right_leaf, right_hash) = iavl_leaf(b"c", b"value_c");
+        let left_step = InnerOp {
+            hash: HashOp::Sha256 as i32,
+            prefix: vec![2, 4, 2, 32],
+            suffix: [vec![32], right_hash.clone()].concat(),
+        };
+        let right_step = InnerOp {
+            hash: HashOp::Sha256 as i32,
+            prefix: [vec![2, 4, 2, 32], left_hash.clone(), vec![32]].concat(),
+            suffix: vec![],
+        };
+        let root = apply_inner(&left_step, &left_hash).unwrap();
+        assert_eq!(apply_inner(&right_step, &right_hash).unwrap(), root);
+        let left = ExistenceProof {
+            key: b"a".to_vec(),
+            value: b"value_a".to_vec(),
+            leaf: Some(left_leaf),
+            path: vec![left_step],
+        };
+        let right = ExistenceProof {
+            key: b"c".to_vec(),
+            value: b"value_c".to_vec(),
+            leaf: Some(right_leaf),
+            path: vec![right_step],
+        };
+        let spec = iavl_spec();
+        verify_membership(
+            &CommitmentProof {
+                proof: Some(Proof::Exist(right.clone())),
+            },
+            &spec,
+            &root,
+            b"c",
+            b"value_c",
+        )
+        .unwrap();
+        let non_existence_proof = CommitmentProof {
+            proof: Some(Proof::Nonexist(NonExistenceProof {
+                key: b"b".to_vec(),
+                left: Some(left),
+                right: Some(right),
+            })),
+        };
+        assert_eq!(calculate_root(&non_existence_proof).unwrap(), root);
+        verify_non_membership(&non_existence_proof, &spec, &root, b"b").unwrap();
+        assert_eq!(
+            verify_non_membership(&non_existence_proof, &spec, &root, b"d").unwrap_err(),
+            Error::RightKeyNotLarger
+        );
+        assert_eq!(
+            verify_membership(&non_existence_proof, &spec, &root, b"b", b"").unwrap_err(),
+            Error::NoExistenceProof
+        );
+    }
+}
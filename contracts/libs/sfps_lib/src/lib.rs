@@ -1,4 +1,6 @@
+pub mod exec_tx_result_proof;
 pub mod header_chain;
+pub mod ics23;
 pub mod light_block;
 pub mod light_client;
 pub mod merkle;
@@ -6,6 +8,7 @@ pub mod merkle;
 pub mod header;
 pub mod response_deliver_tx_proof;
 pub mod signed_header;
+pub mod state_proof;
 pub mod subsequent_hashes;
 pub mod validator_set;
 pub mod vote;
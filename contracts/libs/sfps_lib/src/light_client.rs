@@ -1,15 +1,22 @@
+use crate::exec_tx_result_proof::ExecTxResultProof;
 use crate::header::hash_header;
 use crate::light_block::validate_light_block;
 use crate::light_block::verify_light_block;
-use crate::light_block::Ed25519Verifier;
+use crate::light_block::verify_light_block_trusting;
 use crate::light_block::Error as LightBlockError;
+use crate::light_block::SignatureVerifier;
+use crate::light_block::TrustThreshold;
 use crate::response_deliver_tx_proof::{
     Error as ResponseDeliverTxProofError, ResponseDeliverTxProof,
 };
+use crate::state_proof::{Error as StateProofError, StateProof};
 use crate::subsequent_hashes::HeaderHashWithHeight;
 use crate::subsequent_hashes::{CommittedHashes, Error as SubsequentHashesError, Hashes};
+use crate::validator_set::hash_validator_set;
+use cosmos_proto::prost_types::Timestamp;
 use cosmos_proto::tendermint::types::Header;
 use cosmos_proto::tendermint::types::LightBlock;
+use cosmos_proto::tendermint::types::ValidatorSet;
 use std::convert::TryInto;
 use std::fmt;
 
@@ -25,9 +32,18 @@ pub enum Error {
     UnmatchedValidatorsHash,
     LightBlock(LightBlockError),
     ResponseDeliverTxProof(ResponseDeliverTxProofError),
+    StateProof(StateProofError),
     SubsequentHashes(SubsequentHashesError),
     AnchorHashNotFound,
     AnchorHeaderUnmatched,
+    NoHeaderTime,
+    TrustingPeriodExpired { expired_at: i64, now: i64 },
+    NonIncreasingHeight { trusted: i64, actual: i64 },
+    NonIncreasingTime,
+    UnmatchedMisbehaviourHeight { height_1: i64, height_2: i64 },
+    NoConflictingHeaders,
+    UnmatchedChainId { expected: String, actual: String },
+    HeaderFromFuture { time: i64, now: i64 },
 }
 
 impl std::fmt::Display for Error {
@@ -45,9 +61,38 @@ impl std::fmt::Display for Error {
             Error::UnmatchedValidatorsHash => f.write_str("unmatched validators hash"),
             Error::LightBlock(e) => write!(f, "light block error: {}", e),
             Error::ResponseDeliverTxProof(e) => write!(f, "tx result proof error: {}", e),
+            Error::StateProof(e) => write!(f, "state proof error: {}", e),
             Error::SubsequentHashes(e) => write!(f, "subsequent hashes error: {}", e),
             Error::AnchorHashNotFound => f.write_str("anchor hash not found"),
             Error::AnchorHeaderUnmatched => f.write_str("anchor header unmatched"),
+            Error::NoHeaderTime => f.write_str("no header time"),
+            Error::TrustingPeriodExpired { expired_at, now } => write!(
+                f,
+                "trusting period expired: expired at {}, now {}",
+                expired_at, now
+            ),
+            Error::NonIncreasingHeight { trusted, actual } => write!(
+                f,
+                "non increasing height: trusted {}, actual {}",
+                trusted, actual
+            ),
+            Error::NonIncreasingTime => f.write_str("non increasing time"),
+            Error::UnmatchedMisbehaviourHeight { height_1, height_2 } => write!(
+                f,
+                "unmatched misbehaviour height: {}, {}",
+                height_1, height_2
+            ),
+            Error::NoConflictingHeaders => f.write_str("no conflicting headers"),
+            Error::UnmatchedChainId { expected, actual } => write!(
+                f,
+                "unmatched chain id: expected {}, actual {}",
+                expected, actual
+            ),
+            Error::HeaderFromFuture { time, now } => write!(
+                f,
+                "header from future beyond max clock drift: time {}, now {}",
+                time, now
+            ),
         }
     }
 }
@@ -64,18 +109,46 @@ impl From<ResponseDeliverTxProofError> for Error {
     }
 }
 
+impl From<StateProofError> for Error {
+    fn from(e: StateProofError) -> Self {
+        Self::StateProof(e)
+    }
+}
+
 impl From<SubsequentHashesError> for Error {
     fn from(e: SubsequentHashesError) -> Self {
         Self::SubsequentHashes(e)
     }
 }
 
+/// Options of the skipping verification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrustOptions {
+    pub trust_threshold: TrustThreshold,
+    /// seconds from the time of the anchor header, in which the validators of the anchor header are trusted.
+    /// it must be shorter than the unbonding period of the chain.
+    pub trusting_period: u64,
+}
+
+/// Options of the rotation of the commit secret, counted in the appends which appended at least one hash.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommitSecretRotation {
+    /// the commit secret is rotated automatically every `interval` appends. 0 disables the automatic rotation.
+    pub interval: u64,
+    /// commits made under the previous secret still verify until `grace_appends` appends after the rotation.
+    pub grace_appends: u64,
+}
+
 pub trait ReadonlyLightClientDB {
     fn get_hash_by_index(&mut self, index: usize) -> Option<HeaderHashWithHeight>;
     fn get_highest_hash(&mut self) -> Option<HeaderHashWithHeight>;
     fn get_hash_list_length(&mut self) -> usize;
     fn get_max_interval(&mut self) -> u64;
     fn get_commit_secret(&mut self) -> Vec<u8>;
+    fn get_previous_commit_secret(&mut self) -> Option<Vec<u8>>;
+    fn get_appends_since_rotation(&mut self) -> u64;
+    fn get_chain_id(&mut self) -> String;
+    fn get_max_clock_drift(&mut self) -> u64;
 }
 
 pub trait LightClientDB: ReadonlyLightClientDB {
@@ -83,6 +156,18 @@ pub trait LightClientDB: ReadonlyLightClientDB {
     fn append_block_hash(&mut self, hash: HeaderHashWithHeight) -> Result<(), Self::Error>;
     fn store_max_interval(&mut self, max_interval: u64) -> Result<(), Self::Error>;
     fn store_commit_secret(&mut self, secret: &[u8]) -> Result<(), Self::Error>;
+    fn store_previous_commit_secret(&mut self, secret: &[u8]) -> Result<(), Self::Error>;
+    fn store_appends_since_rotation(&mut self, appends: u64) -> Result<(), Self::Error>;
+    fn store_chain_id(&mut self, chain_id: &str) -> Result<(), Self::Error>;
+    fn store_max_clock_drift(&mut self, max_clock_drift: u64) -> Result<(), Self::Error>;
+
+    /// replaces the commit secret with the new one, keeping the current one as the previous secret.
+    fn rotate_commit_secret(&mut self, new_secret: &[u8]) -> Result<(), Self::Error> {
+        let current_secret = self.get_commit_secret();
+        self.store_previous_commit_secret(&current_secret)?;
+        self.store_commit_secret(new_secret)?;
+        self.store_appends_since_rotation(0)
+    }
 }
 
 pub struct LightClient<C: ReadonlyLightClientDB> {
@@ -94,12 +179,87 @@ impl<C: ReadonlyLightClientDB> LightClient<C> {
         Self { db }
     }
 
+    /// verifies the commit of the hashes with the current commit secret,
+    /// or with the previous one until `grace_appends` appends after the last rotation.
+    pub fn verify_committed_hashes(
+        &mut self,
+        committed_hashes: &CommittedHashes,
+        grace_appends: u64,
+    ) -> Result<(), Error> {
+        let result = committed_hashes.verify(&self.db.get_commit_secret());
+        if result.is_ok() || self.db.get_appends_since_rotation() >= grace_appends {
+            return Ok(result?);
+        }
+        match self.db.get_previous_commit_secret() {
+            Some(previous_secret) => Ok(committed_hashes.verify(&previous_secret)?),
+            None => Ok(result?),
+        }
+    }
+
+    /// checks that the anchor and the following hashes are in the hash list.
+    ///
+    /// The commit under a retired secret can no longer be verified,
+    /// but the hashes of the accepted commit stay recorded, which tells it apart from an invalid commit.
+    /// The following hashes skipped on the append, being lower than the highest hash at that time,
+    /// are recorded only if they were committed by other appends.
+    pub fn is_recorded(&mut self, hashes: &Hashes) -> bool {
+        match self.db.get_hash_by_index(hashes.anchor_index as usize) {
+            Some(anchor) if anchor.hash == hashes.anchor_hash => {}
+            _ => return false,
+        }
+        let mut index = hashes.anchor_index as usize + 1;
+        for following_hash in hashes.following_hashes.iter() {
+            loop {
+                match self.db.get_hash_by_index(index) {
+                    Some(stored) if stored.height < following_hash.height => index += 1,
+                    Some(stored) if stored == *following_hash => {
+                        index += 1;
+                        break;
+                    }
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
+
     pub fn verify_response_deliver_tx_proof(
         &mut self,
         response_deliver_tx_proof: &ResponseDeliverTxProof,
         block_hash_index: usize,
     ) -> Result<(), Error> {
         let highest_block_hash = response_deliver_tx_proof.verify()?;
+        self.verify_highest_block_hash(&highest_block_hash, block_hash_index)
+    }
+
+    pub fn verify_exec_tx_result_proof(
+        &mut self,
+        exec_tx_result_proof: &ExecTxResultProof,
+        block_hash_index: usize,
+    ) -> Result<(), Error> {
+        let highest_block_hash = exec_tx_result_proof.verify()?;
+        self.verify_highest_block_hash(&highest_block_hash, block_hash_index)
+    }
+
+    /// verifies the state proof of `key` in the store `store_key`.
+    /// `value` is None when the non-existence of the key is proven.
+    pub fn verify_state_proof(
+        &mut self,
+        state_proof: &StateProof,
+        block_hash_index: usize,
+        store_key: &[u8],
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        let highest_block_hash = state_proof.verify(store_key, key, value)?;
+        self.verify_highest_block_hash(&highest_block_hash, block_hash_index)
+    }
+
+    fn verify_highest_block_hash(
+        &mut self,
+        highest_block_hash: &[u8],
+        block_hash_index: usize,
+    ) -> Result<(), Error> {
         if let Some(stored_hash) = self.db.get_hash_by_index(block_hash_index) {
             if stored_hash.hash == highest_block_hash {
                 return Ok(());
@@ -108,13 +268,13 @@ impl<C: ReadonlyLightClientDB> LightClient<C> {
         Err(Error::InvalidHighestHash)
     }
 
-    pub fn verify_subsequent_light_blocks<S: ToString, E: Ed25519Verifier<S>>(
+    pub fn verify_subsequent_light_blocks<S: ToString, E: SignatureVerifier<S>>(
         &mut self,
         mut anchor_header: Header,
         anchor_header_index: usize,
         following_light_blocks: Vec<LightBlock>,
         commit_flags: Vec<bool>,
-        ed25519_verifier: &mut E,
+        signature_verifier: &mut E,
     ) -> Result<CommittedHashes, Error> {
         let recorded_anchor_hash = self
             .db
@@ -126,11 +286,13 @@ impl<C: ReadonlyLightClientDB> LightClient<C> {
         if following_light_blocks.len() != commit_flags.len() {
             return Err(Error::InvalidCommitFlagsLength);
         }
+        let anchor_time = header_time(&anchor_header)?.clone();
+        let mut previous_time = anchor_time.clone();
         let mut validators_hash = anchor_header.next_validators_hash;
         let mut following_hashes = Vec::new();
         let max_interval = self.db.get_max_interval();
         for (i, light_block) in following_light_blocks.iter().enumerate() {
-            self.verify_block(&validators_hash, &light_block, ed25519_verifier)?;
+            self.verify_block(&validators_hash, &light_block, signature_verifier)?;
             let header = light_block
                 .signed_header
                 .as_ref()
@@ -138,6 +300,12 @@ impl<C: ReadonlyLightClientDB> LightClient<C> {
                 .header
                 .as_ref()
                 .unwrap();
+            self.verify_chain_id(header)?;
+            let time = header_time(header)?;
+            if (time.seconds, time.nanos) <= (previous_time.seconds, previous_time.nanos) {
+                return Err(Error::NonIncreasingTime);
+            }
+            previous_time = time.clone();
             let actual_interval: u64 = header
                 .height
                 .checked_sub(anchor_header.height)
@@ -165,16 +333,181 @@ impl<C: ReadonlyLightClientDB> LightClient<C> {
                 anchor_hash: recorded_anchor_hash.hash,
                 anchor_index: anchor_header_index as u64,
                 following_hashes,
+                anchor_time: anchor_time.seconds,
+                highest_time: previous_time.seconds,
+            },
+            &self.db.get_commit_secret(),
+        )?)
+    }
+
+    /// verifies the light blocks from the anchor with the skipping verification of tendermint,
+    /// and returns the hashes of all of them committed.
+    ///
+    /// Unlike `verify_subsequent_light_blocks`, a light block can be far ahead of the previous one without `max_interval`.
+    /// The validators of the previous block which have more than the trust threshold of its voting power must sign such a block,
+    /// and the anchor header must be within the trusting period at `now` (unix time in seconds).
+    //https://github.com/tendermint/tendermint/blob/5e52a6ec558f789b642a231c257f8754b97637bc/light/verifier.go#L32
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_skipping_light_blocks<S: ToString, E: SignatureVerifier<S>>(
+        &mut self,
+        anchor_header: Header,
+        anchor_header_index: usize,
+        anchor_validator_set: ValidatorSet,
+        following_light_blocks: Vec<LightBlock>,
+        trust_options: TrustOptions,
+        now: i64,
+        signature_verifier: &mut E,
+    ) -> Result<CommittedHashes, Error> {
+        let recorded_anchor_hash = self.verify_trusted_anchor(
+            &anchor_header,
+            anchor_header_index,
+            &anchor_validator_set,
+            trust_options,
+            now,
+        )?;
+        let anchor_time = header_time(&anchor_header)?.seconds;
+        let mut trusted_header = anchor_header;
+        let mut trusted_validator_set = anchor_validator_set;
+        let mut following_hashes = Vec::with_capacity(following_light_blocks.len());
+        for light_block in following_light_blocks.into_iter() {
+            let header = verify_light_block_from_trusted(
+                &trusted_header,
+                &trusted_validator_set,
+                &light_block,
+                trust_options.trust_threshold,
+                signature_verifier,
+            )?;
+            self.verify_chain_id(header)?;
+            self.verify_not_from_future(header_time(header)?.seconds, now)?;
+            following_hashes.push(HeaderHashWithHeight {
+                hash: hash_header(header),
+                height: header.height,
+            });
+            trusted_header = header.clone();
+            // validator set of the light block is checked by `validate_light_block`.
+            trusted_validator_set = light_block.validator_set.unwrap();
+        }
+        Ok(CommittedHashes::new(
+            Hashes {
+                anchor_hash: recorded_anchor_hash.hash,
+                anchor_index: anchor_header_index as u64,
+                following_hashes,
+                anchor_time,
+                highest_time: header_time(&trusted_header)?.seconds,
             },
             &self.db.get_commit_secret(),
         )?)
     }
 
-    fn verify_block<S: ToString, E: Ed25519Verifier<S>>(
+    /// verifies the evidence that the validators trusted at the anchor header signed two conflicting light blocks at the same height,
+    /// and returns the height of them.
+    ///
+    /// Each light block is verified from the anchor as well as `verify_skipping_light_blocks`,
+    /// so the evidence can be submitted while the anchor header is within the trusting period at `now`.
+    //https://github.com/tendermint/tendermint/blob/5e52a6ec558f789b642a231c257f8754b97637bc/light/detector.go#L30
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_misbehaviour<S: ToString, E: SignatureVerifier<S>>(
+        &mut self,
+        anchor_header: Header,
+        anchor_header_index: usize,
+        anchor_validator_set: ValidatorSet,
+        light_block_1: LightBlock,
+        light_block_2: LightBlock,
+        trust_options: TrustOptions,
+        now: i64,
+        signature_verifier: &mut E,
+    ) -> Result<i64, Error> {
+        self.verify_trusted_anchor(
+            &anchor_header,
+            anchor_header_index,
+            &anchor_validator_set,
+            trust_options,
+            now,
+        )?;
+        let header_1 = verify_light_block_from_trusted(
+            &anchor_header,
+            &anchor_validator_set,
+            &light_block_1,
+            trust_options.trust_threshold,
+            signature_verifier,
+        )?;
+        let header_2 = verify_light_block_from_trusted(
+            &anchor_header,
+            &anchor_validator_set,
+            &light_block_2,
+            trust_options.trust_threshold,
+            signature_verifier,
+        )?;
+        for header in [header_1, header_2].iter() {
+            self.verify_chain_id(header)?;
+            self.verify_not_from_future(header_time(header)?.seconds, now)?;
+        }
+        if header_1.height != header_2.height {
+            return Err(Error::UnmatchedMisbehaviourHeight {
+                height_1: header_1.height,
+                height_2: header_2.height,
+            });
+        }
+        if hash_header(header_1) == hash_header(header_2) {
+            return Err(Error::NoConflictingHeaders);
+        }
+        Ok(header_1.height)
+    }
+
+    /// verifies that the anchor header is stored at `anchor_header_index`,
+    /// the validator set is the one of the anchor header and the anchor header is within the trusting period at `now`.
+    fn verify_trusted_anchor(
+        &mut self,
+        anchor_header: &Header,
+        anchor_header_index: usize,
+        anchor_validator_set: &ValidatorSet,
+        trust_options: TrustOptions,
+        now: i64,
+    ) -> Result<HeaderHashWithHeight, Error> {
+        let recorded_anchor_hash = self
+            .db
+            .get_hash_by_index(anchor_header_index)
+            .ok_or(Error::AnchorHashNotFound)?;
+        if hash_header(anchor_header) != recorded_anchor_hash.hash {
+            return Err(Error::AnchorHeaderUnmatched);
+        }
+        if hash_validator_set(anchor_validator_set) != anchor_header.validators_hash {
+            return Err(Error::UnmatchedValidatorsHash);
+        }
+        let expired_at = header_time(anchor_header)?
+            .seconds
+            .saturating_add(trust_options.trusting_period.try_into().unwrap_or(i64::MAX));
+        if expired_at <= now {
+            return Err(Error::TrustingPeriodExpired { expired_at, now });
+        }
+        Ok(recorded_anchor_hash)
+    }
+
+    fn verify_chain_id(&mut self, header: &Header) -> Result<(), Error> {
+        let chain_id = self.db.get_chain_id();
+        if header.chain_id != chain_id {
+            return Err(Error::UnmatchedChainId {
+                expected: chain_id,
+                actual: header.chain_id.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// verifies that the header time is not ahead of `now` by more than the max clock drift.
+    fn verify_not_from_future(&mut self, time: i64, now: i64) -> Result<(), Error> {
+        let max_clock_drift = self.db.get_max_clock_drift();
+        if time > now.saturating_add(max_clock_drift.try_into().unwrap_or(i64::MAX)) {
+            return Err(Error::HeaderFromFuture { time, now });
+        }
+        Ok(())
+    }
+
+    fn verify_block<S: ToString, E: SignatureVerifier<S>>(
         &self,
         validators_hash: &[u8],
         light_block: &LightBlock,
-        ed25519_verifier: &mut E,
+        signature_verifier: &mut E,
     ) -> Result<(), Error> {
         validate_light_block(&light_block)?;
         if validators_hash
@@ -189,16 +522,84 @@ impl<C: ReadonlyLightClientDB> LightClient<C> {
         {
             return Err(Error::UnmatchedValidatorsHash);
         }
-        verify_light_block(light_block, ed25519_verifier)?;
+        verify_light_block(light_block, signature_verifier)?;
         Ok(())
     }
 }
 
+fn header_time(header: &Header) -> Result<&Timestamp, Error> {
+    header.time.as_ref().ok_or(Error::NoHeaderTime)
+}
+
+/// verifies the light block following the trusted header, and returns the header of the light block.
+fn verify_light_block_from_trusted<'a, S: ToString, E: SignatureVerifier<S>>(
+    trusted_header: &Header,
+    trusted_validator_set: &ValidatorSet,
+    light_block: &'a LightBlock,
+    trust_threshold: TrustThreshold,
+    signature_verifier: &mut E,
+) -> Result<&'a Header, Error> {
+    validate_light_block(light_block)?;
+    let header = light_block
+        .signed_header
+        .as_ref()
+        .unwrap()
+        .header
+        .as_ref()
+        .unwrap();
+    if header.height <= trusted_header.height {
+        return Err(Error::NonIncreasingHeight {
+            trusted: trusted_header.height,
+            actual: header.height,
+        });
+    }
+    let (time, trusted_time) = (header_time(header)?, header_time(trusted_header)?);
+    if (time.seconds, time.nanos) <= (trusted_time.seconds, trusted_time.nanos) {
+        return Err(Error::NonIncreasingTime);
+    }
+    if header.height == trusted_header.height + 1 {
+        if header.validators_hash != trusted_header.next_validators_hash {
+            return Err(Error::UnmatchedValidatorsHash);
+        }
+    } else {
+        verify_light_block_trusting(
+            light_block,
+            trusted_validator_set,
+            trust_threshold,
+            signature_verifier,
+        )?;
+    }
+    verify_light_block(light_block, signature_verifier)?;
+    Ok(header)
+}
+
 impl<C: LightClientDB> LightClient<C> {
-    pub fn init(&mut self, header: Header, max_interval: u64) -> Result<(), Error> {
+    pub fn init(
+        &mut self,
+        header: Header,
+        max_interval: u64,
+        chain_id: String,
+        max_clock_drift: u64,
+        commit_secret: &[u8],
+    ) -> Result<(), Error> {
         if self.db.get_hash_list_length() > 0 {
             return Err(Error::AlreadyInitialized);
         }
+        if header.chain_id != chain_id {
+            return Err(Error::UnmatchedChainId {
+                expected: chain_id,
+                actual: header.chain_id,
+            });
+        }
+        self.db
+            .store_chain_id(&chain_id)
+            .map_err(|e| Error::LightClientDB(e.to_string()))?;
+        self.db
+            .store_max_clock_drift(max_clock_drift)
+            .map_err(|e| Error::LightClientDB(e.to_string()))?;
+        self.db
+            .store_commit_secret(commit_secret)
+            .map_err(|e| Error::LightClientDB(e.to_string()))?;
         self.db
             .append_block_hash(HeaderHashWithHeight {
                 hash: hash_header(&header),
@@ -210,11 +611,60 @@ impl<C: LightClientDB> LightClient<C> {
             .map_err(|e| Error::LightClientDB(e.to_string()))
     }
 
+    /// appends the hash of the header trusted without verification, e.g. the header given by the owner on recovery.
+    /// the header must be higher than the highest stored hash.
+    pub fn append_trusted_header(&mut self, header: &Header) -> Result<(), Error> {
+        let current_highest_hash_height = self
+            .db
+            .get_highest_hash()
+            .ok_or(Error::NoHighestHeaderHash)?
+            .height;
+        if header.height <= current_highest_hash_height {
+            return Err(Error::NonIncreasingHeight {
+                trusted: current_highest_hash_height,
+                actual: header.height,
+            });
+        }
+        self.db
+            .append_block_hash(HeaderHashWithHeight {
+                hash: hash_header(header),
+                height: header.height,
+            })
+            .map_err(|e| Error::LightClientDB(e.to_string()))
+    }
+
+    /// rotates the commit secret.
+    /// commits made under the current secret keep verifying within the grace window of the rotation.
+    pub fn rotate_commit_secret(&mut self, new_secret: &[u8]) -> Result<(), Error> {
+        self.db
+            .rotate_commit_secret(new_secret)
+            .map_err(|e| Error::LightClientDB(e.to_string()))
+    }
+
+    /// appends the hashes following the highest stored hash,
+    /// and returns the number of the appended hashes.
+    ///
+    /// The anchor header must be within `trusting_period` at `now` (unix time in seconds),
+    /// and the highest verified header must not be ahead of `now` by more than the max clock drift.
+    ///
+    /// The commit secret is rotated to `next_commit_secret` when the append reaches the interval of `rotation`.
     pub fn append_subsequent_hashes(
         &mut self,
         committed_hashes: CommittedHashes,
-    ) -> Result<(), Error> {
-        committed_hashes.verify(&self.db.get_commit_secret())?;
+        now: i64,
+        trusting_period: u64,
+        rotation: CommitSecretRotation,
+        next_commit_secret: &[u8],
+    ) -> Result<u32, Error> {
+        self.verify_committed_hashes(&committed_hashes, rotation.grace_appends)?;
+        let expired_at = committed_hashes
+            .hashes
+            .anchor_time
+            .saturating_add(trusting_period.try_into().unwrap_or(i64::MAX));
+        if expired_at <= now {
+            return Err(Error::TrustingPeriodExpired { expired_at, now });
+        }
+        self.verify_not_from_future(committed_hashes.hashes.highest_time, now)?;
         let stored_anchor_hash = self
             .db
             .get_hash_by_index(committed_hashes.hashes.anchor_index as usize)
@@ -232,11 +682,24 @@ impl<C: LightClientDB> LightClient<C> {
             .following_hashes
             .into_iter()
             .skip_while(|x| x.height <= current_highest_hash_height);
+        let mut appended = 0;
         for hash in appending_hashes {
             self.db
                 .append_block_hash(hash)
                 .map_err(|e| Error::LightClientDB(e.to_string()))?;
+            appended += 1;
         }
-        Ok(())
+        // appends with no new hash are not counted, so that replaying a commit does not force the rotation.
+        if appended > 0 {
+            let appends = self.db.get_appends_since_rotation() + 1;
+            if rotation.interval > 0 && appends >= rotation.interval {
+                self.rotate_commit_secret(next_commit_secret)?;
+            } else {
+                self.db
+                    .store_appends_since_rotation(appends)
+                    .map_err(|e| Error::LightClientDB(e.to_string()))?;
+            }
+        }
+        Ok(appended)
     }
 }
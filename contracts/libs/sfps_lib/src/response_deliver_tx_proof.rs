@@ -1,7 +1,8 @@
 use crate::header::hash_header;
 use crate::merkle::{Error as MerkleProofError, MerkleProof};
-use cosmos_proto::cosmos::base::abci::v1beta1::TxMsgData;
+use cosmos_proto::cosmos::base::abci::v1beta1::{MsgData, TxMsgData};
 use cosmos_proto::prost::Message;
+use cosmos_proto::secret::compute::v1beta1::MsgExecuteContractResponse;
 use cosmos_proto::tendermint::abci::ResponseDeliverTx;
 use cosmos_proto::tendermint::types::Header;
 use std::fmt;
@@ -13,6 +14,10 @@ pub enum Error {
     UnconnectedHeaders,
     ProstError(cosmos_proto::prost::DecodeError),
     MerkleProof(MerkleProofError),
+    DecodeTxMsgData(cosmos_proto::prost::DecodeError),
+    MsgIndexOutOfRange { index: usize, length: usize },
+    UnexpectedMsgResponseType(String),
+    DecodeMsgResponse(cosmos_proto::prost::DecodeError),
 }
 
 impl std::fmt::Display for Error {
@@ -23,6 +28,16 @@ impl std::fmt::Display for Error {
             Error::UnconnectedHeaders => f.write_str("unconnected headers"),
             Error::ProstError(e) => write!(f, "prost error: {}", e),
             Error::MerkleProof(e) => write!(f, "merkle proof error: {}", e),
+            Error::DecodeTxMsgData(e) => write!(f, "failed to decode tx msg data: {}", e),
+            Error::MsgIndexOutOfRange { index, length } => write!(
+                f,
+                "msg index out of range: index {}, length {}",
+                index, length
+            ),
+            Error::UnexpectedMsgResponseType(type_url) => {
+                write!(f, "unexpected msg response type: {}", type_url)
+            }
+            Error::DecodeMsgResponse(e) => write!(f, "failed to decode msg response: {}", e),
         }
     }
 }
@@ -39,14 +54,78 @@ impl From<MerkleProofError> for Error {
     }
 }
 
-pub fn tx_msg_data_of_response_deliver_tx(response_deliver_tx: &ResponseDeliverTx) -> TxMsgData {
-    TxMsgData::decode(response_deliver_tx.data.as_slice()).unwrap()
+pub fn tx_msg_data_of_response_deliver_tx(
+    response_deliver_tx: &ResponseDeliverTx,
+) -> Result<TxMsgData, Error> {
+    TxMsgData::decode(response_deliver_tx.data.as_slice()).map_err(Error::DecodeTxMsgData)
+}
+
+/// returns the data of the msg at `msg_index` in the tx.
+pub fn msg_data_of_response_deliver_tx(
+    response_deliver_tx: &ResponseDeliverTx,
+    msg_index: usize,
+) -> Result<MsgData, Error> {
+    let mut tx_msg_data = tx_msg_data_of_response_deliver_tx(response_deliver_tx)?;
+    let length = tx_msg_data.data.len();
+    if msg_index >= length {
+        return Err(Error::MsgIndexOutOfRange {
+            index: msg_index,
+            length,
+        });
+    }
+    Ok(tx_msg_data.data.swap_remove(msg_index))
+}
+
+pub const MSG_EXECUTE_CONTRACT_RESPONSE_TYPE_URL: &str =
+    "/secret.compute.v1beta1.MsgExecuteContractResponse";
+
+/// returns the data returned by the contract for the msg at `msg_index`.
+/// cosmos-sdk v0.46 and later puts it in `msg_responses` as MsgExecuteContractResponse,
+/// older versions put it in `data`.
+pub fn execute_contract_response_data(
+    mut tx_msg_data: TxMsgData,
+    msg_index: usize,
+) -> Result<Vec<u8>, Error> {
+    if tx_msg_data.msg_responses.is_empty() {
+        let length = tx_msg_data.data.len();
+        if msg_index >= length {
+            return Err(Error::MsgIndexOutOfRange {
+                index: msg_index,
+                length,
+            });
+        }
+        return Ok(tx_msg_data.data.swap_remove(msg_index).data);
+    }
+    let length = tx_msg_data.msg_responses.len();
+    if msg_index >= length {
+        return Err(Error::MsgIndexOutOfRange {
+            index: msg_index,
+            length,
+        });
+    }
+    let msg_response = tx_msg_data.msg_responses.swap_remove(msg_index);
+    if msg_response.type_url != MSG_EXECUTE_CONTRACT_RESPONSE_TYPE_URL {
+        return Err(Error::UnexpectedMsgResponseType(msg_response.type_url));
+    }
+    Ok(
+        MsgExecuteContractResponse::decode(msg_response.value.as_slice())
+            .map_err(Error::DecodeMsgResponse)?
+            .data,
+    )
 }
 
+/// encodes the deterministic fields of ResponseDeliverTx, which are committed to 'last_results_hash'.
 pub fn encode_response_deliver_tx_as_merkle_leaf(
     response_deliver_tx: &ResponseDeliverTx,
 ) -> Vec<u8> {
-    response_deliver_tx.encode_to_vec()
+    ResponseDeliverTx {
+        code: response_deliver_tx.code,
+        data: response_deliver_tx.data.clone(),
+        gas_wanted: response_deliver_tx.gas_wanted,
+        gas_used: response_deliver_tx.gas_used,
+        ..Default::default()
+    }
+    .encode_to_vec()
 }
 
 // Merkle Proof for ResponseDeliverTx.
@@ -54,6 +133,9 @@ pub fn encode_response_deliver_tx_as_merkle_leaf(
 // 1. (1 < a < length(headers)) header[a] contains hash of header[a-1] as it's last_block_id.hash'
 // 2. the first element of headers(= the lowest header of headers) contains merkle root of merkle proof as it's 'last_result_hash'
 // 3. merkle proof includes response_deliver_tx as it's leaf
+//
+// Only code, data, gas_wanted and gas_used of ResponseDeliverTx are committed to 'last_result_hash',
+// so log, info and events are not proven by this proof and must be empty in the leaf.
 #[derive(Clone, Debug, PartialEq)]
 pub struct ResponseDeliverTxProof {
     pub merkle_proof: MerkleProof,
@@ -77,7 +159,7 @@ impl ResponseDeliverTxProof {
 }
 
 // If given headers is connected, returns hash of the highest header.
-fn verify_headers_connected(headers: &[Header]) -> Result<Vec<u8>, Error> {
+pub(crate) fn verify_headers_connected(headers: &[Header]) -> Result<Vec<u8>, Error> {
     match headers.len() {
         0 => Err(Error::EmptyHeaders),
         _ => {
@@ -150,9 +232,9 @@ mod test {
             events: vec![],
             codespace: "".to_string(),
         };
-        let tx_msg_data = tx_msg_data_of_response_deliver_tx(&response_deliver_tx);
+        let tx_msg_data = tx_msg_data_of_response_deliver_tx(&response_deliver_tx).unwrap();
         assert_eq!(
-            tx_msg_data,
+            tx_msg_data.clone(),
             TxMsgData {
                 data: vec![MsgData {
                     msg_type: "/secret.compute.v1beta1.MsgExecuteContract".into(),
@@ -181,7 +263,95 @@ mod test {
                         44, 243, 87, 89, 80, 20, 176, 178, 246, 232, 107, 72, 200, 46, 220, 243,
                         108, 225
                     ],
-                }]
+                }],
+                msg_responses: vec![],
+            }
+        );
+        assert_eq!(
+            msg_data_of_response_deliver_tx(&response_deliver_tx, 0).unwrap(),
+            tx_msg_data.data[0]
+        );
+        assert_eq!(
+            msg_data_of_response_deliver_tx(&response_deliver_tx, 1).unwrap_err(),
+            Error::MsgIndexOutOfRange {
+                index: 1,
+                length: 1
+            }
+        );
+        let invalid_response_deliver_tx = ResponseDeliverTx {
+            data: vec![0xff],
+            ..response_deliver_tx
+        };
+        assert!(matches!(
+            tx_msg_data_of_response_deliver_tx(&invalid_response_deliver_tx),
+            Err(Error::DecodeTxMsgData(_))
+        ));
+        assert_eq!(
+            execute_contract_response_data(tx_msg_data.clone(), 0).unwrap(),
+            tx_msg_data.data[0].data
+        );
+    }
+
+    #[test]
+    fn test_response_deliver_tx_encode_as_merkle_leaf_deterministic() {
+        let response_deliver_tx = ResponseDeliverTx {
+            code: 0,
+            data: vec![1, 2, 3],
+            log: "log".to_string(),
+            info: "info".to_string(),
+            gas_used: 23757,
+            gas_wanted: 50000,
+            events: vec![cosmos_proto::tendermint::abci::Event {
+                r#type: "message".to_string(),
+                attributes: vec![],
+            }],
+            codespace: "codespace".to_string(),
+        };
+        assert_eq!(
+            encode_response_deliver_tx_as_merkle_leaf(&response_deliver_tx),
+            ResponseDeliverTx {
+                code: 0,
+                data: vec![1, 2, 3],
+                gas_used: 23757,
+                gas_wanted: 50000,
+                ..Default::default()
+            }
+            .encode_to_vec()
+        );
+    }
+
+    #[test]
+    fn test_execute_contract_response_data_msg_responses() {
+        use cosmos_proto::prost_types::Any;
+        let tx_msg_data = TxMsgData {
+            data: vec![],
+            msg_responses: vec![
+                Any {
+                    type_url: MSG_EXECUTE_CONTRACT_RESPONSE_TYPE_URL.to_string(),
+                    value: MsgExecuteContractResponse {
+                        data: vec![1, 2, 3],
+                    }
+                    .encode_to_vec(),
+                },
+                Any {
+                    type_url: "/cosmos.bank.v1beta1.MsgSendResponse".to_string(),
+                    value: vec![],
+                },
+            ],
+        };
+        assert_eq!(
+            execute_contract_response_data(tx_msg_data.clone(), 0).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            execute_contract_response_data(tx_msg_data.clone(), 1).unwrap_err(),
+            Error::UnexpectedMsgResponseType("/cosmos.bank.v1beta1.MsgSendResponse".to_string())
+        );
+        assert_eq!(
+            execute_contract_response_data(tx_msg_data, 2).unwrap_err(),
+            Error::MsgIndexOutOfRange {
+                index: 2,
+                length: 2
             }
         );
     }
@@ -0,0 +1,297 @@
+use crate::ics23::{
+    calculate_root, iavl_spec, tendermint_spec, verify_membership, verify_non_membership,
+    Error as Ics23Error,
+};
+use crate::response_deliver_tx_proof::{verify_headers_connected, Error as HeadersError};
+use cosmos_proto::ics23::CommitmentProof;
+use cosmos_proto::prost::Message;
+use cosmos_proto::tendermint::crypto::ProofOps;
+use cosmos_proto::tendermint::types::Header;
+use std::fmt;
+
+pub const PROOF_OP_IAVL: &str = "ics23:iavl";
+pub const PROOF_OP_SIMPLE: &str = "ics23:simple";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Headers(HeadersError),
+    Ics23(Ics23Error),
+    InvalidProofOpsLength(usize),
+    UnexpectedProofOpType { expected: String, actual: String },
+    UnmatchedProofOpKey,
+    DecodeCommitmentProof(cosmos_proto::prost::DecodeError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Headers(e) => write!(f, "headers error: {}", e),
+            Error::Ics23(e) => write!(f, "ics23 error: {}", e),
+            Error::InvalidProofOpsLength(length) => {
+                write!(f, "invalid proof ops length: {}", length)
+            }
+            Error::UnexpectedProofOpType { expected, actual } => write!(
+                f,
+                "unexpected proof op type: expected {}, actual {}",
+                expected, actual
+            ),
+            Error::UnmatchedProofOpKey => f.write_str("unmatched proof op key"),
+            Error::DecodeCommitmentProof(e) => {
+                write!(f, "failed to decode commitment proof: {}", e)
+            }
+        }
+    }
+}
+
+impl From<HeadersError> for Error {
+    fn from(e: HeadersError) -> Self {
+        Self::Headers(e)
+    }
+}
+
+impl From<Ics23Error> for Error {
+    fn from(e: Ics23Error) -> Self {
+        Self::Ics23(e)
+    }
+}
+
+// Proof of a key-value pair in a store of the multistore.
+// This proof is valid when and only when all of 1-3 proposition are correct.
+// 1. (1 < a < length(headers)) header[a] contains hash of header[a-1] as it's last_block_id.hash'
+// 2. the first element of headers(= the lowest header of headers) contains the multistore root as it's 'app_hash'
+// 3. proof_ops, which is returned by abci query with prove=true, proves the existence (or non-existence) of the key
+//    in the IAVL store and the existence of the store root in the multistore.
+//
+// The state committed to 'app_hash' of a header is the state after the previous block,
+// so the proof of abci query at height h must be verified with the header at height h + 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateProof {
+    pub proof_ops: ProofOps,
+    pub headers: Vec<Header>,
+}
+
+impl StateProof {
+    // if the state proof is correct, returns hash of the highest header of connected headers.
+    // `value` is None when the proof is of the non-existence of the key.
+    pub fn verify(
+        &self,
+        store_key: &[u8],
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        let highest_header_hash = verify_headers_connected(&self.headers)?;
+        let app_hash = &self.headers.first().unwrap().app_hash;
+        if self.proof_ops.ops.len() != 2 {
+            return Err(Error::InvalidProofOpsLength(self.proof_ops.ops.len()));
+        }
+        let store_proof = decode_proof_op(&self.proof_ops, 0, PROOF_OP_IAVL, key)?;
+        let multistore_proof = decode_proof_op(&self.proof_ops, 1, PROOF_OP_SIMPLE, store_key)?;
+
+        let store_root = calculate_root(&store_proof)?;
+        match value {
+            Some(value) => verify_membership(&store_proof, &iavl_spec(), &store_root, key, value)?,
+            None => verify_non_membership(&store_proof, &iavl_spec(), &store_root, key)?,
+        }
+        verify_membership(
+            &multistore_proof,
+            &tendermint_spec(),
+            app_hash,
+            store_key,
+            &store_root,
+        )?;
+        Ok(highest_header_hash)
+    }
+}
+
+fn decode_proof_op(
+    proof_ops: &ProofOps,
+    index: usize,
+    op_type: &str,
+    key: &[u8],
+) -> Result<CommitmentProof, Error> {
+    let proof_op = &proof_ops.ops[index];
+    if proof_op.r#type != op_type {
+        return Err(Error::UnexpectedProofOpType {
+            expected: op_type.to_string(),
+            actual: proof_op.r#type.clone(),
+        });
+    }
+    if proof_op.key != key {
+        return Err(Error::UnmatchedProofOpKey);
+    }
+    CommitmentProof::decode(proof_op.data.as_slice()).map_err(Error::DecodeCommitmentProof)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::header::hash_header;
+    use crate::ics23::calculate_existence_root;
+    use cosmos_proto::ics23::commitment_proof::Proof;
+    use cosmos_proto::ics23::{ExistenceProof, LeafOp, NonExistenceProof};
+    use cosmos_proto::prost_types::Timestamp;
+    use cosmos_proto::tendermint::crypto::ProofOp;
+    use cosmos_proto::tendermint::types::*;
+    use cosmos_proto::tendermint::version::*;
+
+    fn header(height: i64, last_block_hash: Vec<u8>, app_hash: Vec<u8>) -> Header {
+        Header {
+            version: Some(Consensus { app: 0, block: 11 }),
+            chain_id: "chain_id".to_string(),
+            height,
+            time: Some(Timestamp {
+                seconds: 0,
+                nanos: 0,
+            }),
+            last_block_id: Some(BlockId {
+                hash: last_block_hash,
+                part_set_header: Some(PartSetHeader {
+                    total: 0,
+                    hash: [0; 32].to_vec(),
+                }),
+            }),
+            last_commit_hash: [0; 32].to_vec(),
+            data_hash: [0; 32].to_vec(),
+            validators_hash: [0; 32].to_vec(),
+            next_validators_hash: [0; 32].to_vec(),
+            consensus_hash: [0; 32].to_vec(),
+            app_hash,
+            last_results_hash: [0; 32].to_vec(),
+            evidence_hash: [0; 32].to_vec(),
+            proposer_address: [0; 20].to_vec(),
+        }
+    }
+
+    fn proof_op(op_type: &str, key: &[u8], proof: Proof) -> ProofOp {
+        ProofOp {
+            r#type: op_type.to_string(),
+            key: key.to_vec(),
+            data: CommitmentProof { proof: Some(proof) }.encode_to_vec(),
+        }
+    }
+
+    // the store has only one key "key" and the multistore has only one store "compute".
+    fn state_proof(store_proof: Proof) -> StateProof {
+        let store_leaf = ExistenceProof {
+            key: b"key".to_vec(),
+            value: b"value".to_vec(),
+            leaf: Some(LeafOp {
+                prefix: vec![0, 2, 2],
+                ..iavl_spec().leaf_spec.unwrap()
+            }),
+            path: vec![],
+        };
+        let store_root = calculate_existence_root(&store_leaf).unwrap();
+        let multistore_leaf = ExistenceProof {
+            key: b"compute".to_vec(),
+            value: store_root,
+            leaf: tendermint_spec().leaf_spec,
+            path: vec![],
+        };
+        let app_hash = calculate_existence_root(&multistore_leaf).unwrap();
+        let first = header(10, [0; 32].to_vec(), app_hash);
+        let second = header(11, hash_header(&first), [0; 32].to_vec());
+        let store_proof = match store_proof {
+            Proof::Exist(_) => Proof::Exist(store_leaf),
+            Proof::Nonexist(proof) => Proof::Nonexist(NonExistenceProof {
+                left: Some(store_leaf),
+                ..proof
+            }),
+            proof => proof,
+        };
+        StateProof {
+            proof_ops: ProofOps {
+                ops: vec![
+                    proof_op(PROOF_OP_IAVL, b"key", store_proof),
+                    proof_op(PROOF_OP_SIMPLE, b"compute", Proof::Exist(multistore_leaf)),
+                ],
+            },
+            headers: vec![first, second],
+        }
+    }
+
+    #[test]
+    fn test_verify_state_proof_existence() {
+        let proof = state_proof(Proof::Exist(ExistenceProof::default()));
+        assert_eq!(
+            proof.verify(b"compute", b"key", Some(b"value")).unwrap(),
+            hash_header(&proof.headers[1])
+        );
+        assert_eq!(
+            proof
+                .verify(b"compute", b"key", Some(b"other_value"))
+                .unwrap_err(),
+            Error::Ics23(Ics23Error::UnmatchedValue)
+        );
+        assert_eq!(
+            proof.verify(b"bank", b"key", Some(b"value")).unwrap_err(),
+            Error::UnmatchedProofOpKey
+        );
+        assert_eq!(
+            proof.verify(b"compute", b"key", None).unwrap_err(),
+            Error::Ics23(Ics23Error::NoNonExistenceProof)
+        );
+
+        // the headers are connected, but the first one does not commit to the multistore root.
+        let mut invalid_app_hash = proof.clone();
+        invalid_app_hash.headers[0].app_hash = [0; 32].to_vec();
+        invalid_app_hash.headers[1] = header(
+            11,
+            hash_header(&invalid_app_hash.headers[0]),
+            [0; 32].to_vec(),
+        );
+        assert_eq!(
+            invalid_app_hash
+                .verify(b"compute", b"key", Some(b"value"))
+                .unwrap_err(),
+            Error::Ics23(Ics23Error::UnmatchedRoot)
+        );
+
+        let mut unconnected = proof.clone();
+        unconnected.headers[0].app_hash = [0; 32].to_vec();
+        assert!(matches!(
+            unconnected
+                .verify(b"compute", b"key", Some(b"value"))
+                .unwrap_err(),
+            Error::Headers(HeadersError::UnconnectedHeaders)
+        ));
+
+        let mut swapped = proof;
+        swapped.proof_ops.ops.swap(0, 1);
+        assert_eq!(
+            swapped
+                .verify(b"compute", b"key", Some(b"value"))
+                .unwrap_err(),
+            Error::UnexpectedProofOpType {
+                expected: PROOF_OP_IAVL.to_string(),
+                actual: PROOF_OP_SIMPLE.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_state_proof_non_existence() {
+        let proof = state_proof(Proof::Nonexist(NonExistenceProof::default()));
+        let mut proof_ops = proof.proof_ops.clone();
+        proof_ops.ops[0].key = b"other_key".to_vec();
+        let proof = StateProof { proof_ops, ..proof };
+        assert_eq!(
+            proof.verify(b"compute", b"other_key", None).unwrap(),
+            hash_header(&proof.headers[1])
+        );
+        assert_eq!(
+            proof
+                .verify(b"compute", b"other_key", Some(b"value"))
+                .unwrap_err(),
+            Error::Ics23(Ics23Error::NoExistenceProof)
+        );
+        let mut proof_ops = proof.proof_ops.clone();
+        proof_ops.ops[0].key = b"a".to_vec();
+        assert_eq!(
+            StateProof { proof_ops, ..proof }
+                .verify(b"compute", b"a", None)
+                .unwrap_err(),
+            Error::Ics23(Ics23Error::LeftKeyNotSmaller)
+        );
+    }
+}
@@ -1,5 +1,6 @@
+use hmac::{Hmac, Mac, NewMac};
 use schemars::JsonSchema;
-use sha2::{Digest, Sha256};
+use sha2::Sha256;
 use std::fmt;
 use std::string::ToString;
 
@@ -35,18 +36,31 @@ pub struct Hashes {
     pub anchor_hash: Vec<u8>,
     pub anchor_index: u64,
     pub following_hashes: Vec<HeaderHashWithHeight>,
+    /// time (unix seconds) of the anchor header, to check the staleness of the anchor on appending.
+    pub anchor_time: i64,
+    /// time (unix seconds) of the highest verified header, to check the clock drift on appending.
+    pub highest_time: i64,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug, JsonSchema)]
 pub struct Commit(Vec<u8>);
 
+type HmacSha256 = Hmac<Sha256>;
+
 impl Commit {
+    /// HMAC-SHA256 of the bincode serialized hashes keyed with the secret.
     pub fn new(hashes: &Hashes, secret: &[u8]) -> Result<Self, Error> {
+        Ok(Self(
+            Self::mac(hashes, secret)?.finalize().into_bytes().to_vec(),
+        ))
+    }
+
+    fn mac(hashes: &Hashes, secret: &[u8]) -> Result<HmacSha256, Error> {
         let hashes_binary = bincode::serialize(&hashes)?;
-        let mut digest = Sha256::default();
-        digest.update(hashes_binary);
-        digest.update(secret);
-        Ok(Self(digest.finalize().as_slice().into()))
+        // HMAC accepts keys of any length.
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(&hashes_binary);
+        Ok(mac)
     }
 }
 
@@ -65,11 +79,10 @@ impl CommittedHashes {
         })
     }
 
+    /// verifies the commit in constant time.
     pub fn verify(&self, secret: &[u8]) -> Result<(), Error> {
-        if self.commit == Commit::new(&self.hashes, secret)? {
-            Ok(())
-        } else {
-            Err(Error::InvalidCommit)
-        }
+        Commit::mac(&self.hashes, secret)?
+            .verify(&self.commit.0)
+            .map_err(|_| Error::InvalidCommit)
     }
 }
@@ -3,9 +3,15 @@ use cosmos_proto::tendermint::abci::ResponseDeliverTx;
 use cosmos_proto::tendermint::types::{Header, LightBlock};
 use serde::{Deserialize, Serialize};
 use sfps_lib::header_chain::{ChainDB, HeaderChain, ReadonlyChainDB};
+use sfps_lib::light_block::TrustThreshold;
+use sfps_lib::light_client::{
+    CommitSecretRotation, Error as LightClientError, LightClient, LightClientDB,
+    ReadonlyLightClientDB, TrustOptions,
+};
 use sfps_lib::merkle::MerkleProof;
 use sfps_lib::response_deliver_tx_proof::encode_response_deliver_tx_as_merkle_leaf;
 use sfps_lib::response_deliver_tx_proof::ResponseDeliverTxProof;
+use sfps_lib::subsequent_hashes::{Error as SubsequentHashesError, HeaderHashWithHeight};
 use std::fs::File;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -57,6 +63,86 @@ impl ChainDB for MemoryChainDB {
     }
 }
 
+// 10 seconds
+const MAX_CLOCK_DRIFT: u64 = 10;
+const NO_ROTATION: CommitSecretRotation = CommitSecretRotation {
+    interval: 0,
+    grace_appends: 0,
+};
+
+#[derive(Debug, Default)]
+struct MemoryLightClientDB {
+    hashes: Vec<HeaderHashWithHeight>,
+    max_interval: u64,
+    commit_secret: Vec<u8>,
+    previous_commit_secret: Option<Vec<u8>>,
+    appends_since_rotation: u64,
+    chain_id: String,
+    max_clock_drift: u64,
+}
+
+impl ReadonlyLightClientDB for MemoryLightClientDB {
+    fn get_hash_by_index(&mut self, index: usize) -> Option<HeaderHashWithHeight> {
+        self.hashes.get(index).cloned()
+    }
+    fn get_highest_hash(&mut self) -> Option<HeaderHashWithHeight> {
+        self.hashes.last().cloned()
+    }
+    fn get_hash_list_length(&mut self) -> usize {
+        self.hashes.len()
+    }
+    fn get_max_interval(&mut self) -> u64 {
+        self.max_interval
+    }
+    fn get_commit_secret(&mut self) -> Vec<u8> {
+        self.commit_secret.clone()
+    }
+    fn get_previous_commit_secret(&mut self) -> Option<Vec<u8>> {
+        self.previous_commit_secret.clone()
+    }
+    fn get_appends_since_rotation(&mut self) -> u64 {
+        self.appends_since_rotation
+    }
+    fn get_chain_id(&mut self) -> String {
+        self.chain_id.clone()
+    }
+    fn get_max_clock_drift(&mut self) -> u64 {
+        self.max_clock_drift
+    }
+}
+
+impl LightClientDB for MemoryLightClientDB {
+    type Error = String;
+    fn append_block_hash(&mut self, hash: HeaderHashWithHeight) -> Result<(), Self::Error> {
+        self.hashes.push(hash);
+        Ok(())
+    }
+    fn store_max_interval(&mut self, max_interval: u64) -> Result<(), Self::Error> {
+        self.max_interval = max_interval;
+        Ok(())
+    }
+    fn store_commit_secret(&mut self, secret: &[u8]) -> Result<(), Self::Error> {
+        self.commit_secret = secret.to_vec();
+        Ok(())
+    }
+    fn store_previous_commit_secret(&mut self, secret: &[u8]) -> Result<(), Self::Error> {
+        self.previous_commit_secret = Some(secret.to_vec());
+        Ok(())
+    }
+    fn store_appends_since_rotation(&mut self, appends: u64) -> Result<(), Self::Error> {
+        self.appends_since_rotation = appends;
+        Ok(())
+    }
+    fn store_chain_id(&mut self, chain_id: &str) -> Result<(), Self::Error> {
+        self.chain_id = chain_id.to_string();
+        Ok(())
+    }
+    fn store_max_clock_drift(&mut self, max_clock_drift: u64) -> Result<(), Self::Error> {
+        self.max_clock_drift = max_clock_drift;
+        Ok(())
+    }
+}
+
 struct DalekEd25519Verifier();
 
 impl sfps_lib::light_block::Ed25519Verifier<String> for DalekEd25519Verifier {
@@ -79,6 +165,12 @@ impl sfps_lib::light_block::Ed25519Verifier<String> for DalekEd25519Verifier {
     }
 }
 
+impl sfps_lib::light_block::Secp256k1Verifier<String> for DalekEd25519Verifier {
+    fn verify(&mut self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), String> {
+        Err("secp256k1 is not supported".to_string())
+    }
+}
+
 #[test]
 fn feature_test() {
     // read data from json file
@@ -144,3 +236,390 @@ fn feature_test() {
         .verify_response_deliver_tx_proof(&response_deliver_tx_proof, 9)
         .unwrap()
 }
+
+#[test]
+fn skipping_verification_test() {
+    let file = File::open("tests/testdata.json").unwrap();
+    let data: ChainData = serde_json::from_reader(file).unwrap();
+    let initial_header =
+        Header::decode(base64::decode(&data.initial_header).unwrap().as_slice()).unwrap();
+    let light_blocks: Vec<LightBlock> = data
+        .light_blocks
+        .iter()
+        .map(|block| LightBlock::decode(base64::decode(block).unwrap().as_slice()).unwrap())
+        .collect();
+    // the validator set does not change from the initial header to the last light block.
+    let anchor_validator_set = light_blocks[0].validator_set.clone().unwrap();
+    let header_of = |light_block: &LightBlock| {
+        light_block
+            .signed_header
+            .as_ref()
+            .unwrap()
+            .header
+            .as_ref()
+            .unwrap()
+            .clone()
+    };
+
+    let mut light_client = LightClient::new(MemoryLightClientDB::default());
+    light_client
+        .init(
+            initial_header.clone(),
+            1,
+            initial_header.chain_id.clone(),
+            MAX_CLOCK_DRIFT,
+            b"secret",
+        )
+        .unwrap();
+    let trust_options = TrustOptions {
+        trust_threshold: TrustThreshold::ONE_THIRD,
+        // 2 weeks
+        trusting_period: 14 * 24 * 60 * 60,
+    };
+    let anchor_time = initial_header.time.as_ref().unwrap().seconds;
+    let now = anchor_time + 60;
+
+    assert_eq!(
+        LightClient::new(MemoryLightClientDB::default())
+            .init(
+                initial_header.clone(),
+                1,
+                "other-chain".to_string(),
+                0,
+                b"secret"
+            )
+            .unwrap_err(),
+        LightClientError::UnmatchedChainId {
+            expected: "other-chain".to_string(),
+            actual: initial_header.chain_id.clone(),
+        }
+    );
+
+    // the last light block is too far for the subsequent verification.
+    assert_eq!(
+        light_client
+            .verify_subsequent_light_blocks(
+                initial_header.clone(),
+                0,
+                vec![light_blocks[8].clone()],
+                vec![true],
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::ExceedsInterval { max: 1, actual: 9 }
+    );
+
+    // trusting period expired
+    assert_eq!(
+        light_client
+            .verify_skipping_light_blocks(
+                initial_header.clone(),
+                0,
+                anchor_validator_set.clone(),
+                vec![light_blocks[8].clone()],
+                trust_options,
+                anchor_time + trust_options.trusting_period as i64,
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::TrustingPeriodExpired {
+            expired_at: anchor_time + trust_options.trusting_period as i64,
+            now: anchor_time + trust_options.trusting_period as i64,
+        }
+    );
+
+    // header of the other chain
+    let mut other_chain_light_client = LightClient::new(MemoryLightClientDB {
+        hashes: vec![HeaderHashWithHeight {
+            hash: sfps_lib::header::hash_header(&initial_header),
+            height: initial_header.height,
+        }],
+        max_interval: 1,
+        chain_id: "other-chain".to_string(),
+        ..Default::default()
+    });
+    assert_eq!(
+        other_chain_light_client
+            .verify_subsequent_light_blocks(
+                initial_header.clone(),
+                0,
+                vec![light_blocks[0].clone()],
+                vec![true],
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::UnmatchedChainId {
+            expected: "other-chain".to_string(),
+            actual: initial_header.chain_id.clone(),
+        }
+    );
+
+    // header from future beyond the max clock drift
+    let time_of_last_block = header_of(&light_blocks[8]).time.unwrap().seconds;
+    assert_eq!(
+        light_client
+            .verify_skipping_light_blocks(
+                initial_header.clone(),
+                0,
+                anchor_validator_set.clone(),
+                vec![light_blocks[8].clone()],
+                trust_options,
+                time_of_last_block - MAX_CLOCK_DRIFT as i64 - 1,
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::HeaderFromFuture {
+            time: time_of_last_block,
+            now: time_of_last_block - MAX_CLOCK_DRIFT as i64 - 1,
+        }
+    );
+
+    // non increasing height
+    assert_eq!(
+        light_client
+            .verify_skipping_light_blocks(
+                initial_header.clone(),
+                0,
+                anchor_validator_set.clone(),
+                vec![light_blocks[3].clone(), light_blocks[2].clone()],
+                trust_options,
+                now,
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::NonIncreasingHeight {
+            trusted: 1000004,
+            actual: 1000003,
+        }
+    );
+
+    // skips the blocks, with an adjacent block in the middle.
+    let committed_hashes = light_client
+        .verify_skipping_light_blocks(
+            initial_header.clone(),
+            0,
+            anchor_validator_set,
+            vec![
+                light_blocks[3].clone(),
+                light_blocks[4].clone(),
+                light_blocks[8].clone(),
+            ],
+            trust_options,
+            now,
+            &mut DalekEd25519Verifier {},
+        )
+        .unwrap();
+    assert_eq!(
+        committed_hashes.hashes.following_hashes,
+        [3, 4, 8]
+            .iter()
+            .map(|i| {
+                let header = header_of(&light_blocks[*i]);
+                HeaderHashWithHeight {
+                    hash: sfps_lib::header::hash_header(&header),
+                    height: header.height,
+                }
+            })
+            .collect::<Vec<_>>()
+    );
+    // the anchor is stale on appending
+    assert_eq!(
+        light_client
+            .append_subsequent_hashes(
+                committed_hashes.clone(),
+                anchor_time + trust_options.trusting_period as i64,
+                trust_options.trusting_period,
+                NO_ROTATION,
+                b"next secret"
+            )
+            .unwrap_err(),
+        LightClientError::TrustingPeriodExpired {
+            expired_at: anchor_time + trust_options.trusting_period as i64,
+            now: anchor_time + trust_options.trusting_period as i64,
+        }
+    );
+    // the highest header is from future on appending
+    assert_eq!(
+        light_client
+            .append_subsequent_hashes(
+                committed_hashes.clone(),
+                anchor_time,
+                trust_options.trusting_period,
+                NO_ROTATION,
+                b"next secret"
+            )
+            .unwrap_err(),
+        LightClientError::HeaderFromFuture {
+            time: time_of_last_block,
+            now: anchor_time,
+        }
+    );
+    // the secret is rotated by the append reaching the interval.
+    let rotation = CommitSecretRotation {
+        interval: 1,
+        grace_appends: 1,
+    };
+    assert_eq!(
+        light_client
+            .append_subsequent_hashes(
+                committed_hashes.clone(),
+                now,
+                trust_options.trusting_period,
+                rotation,
+                b"next secret"
+            )
+            .unwrap(),
+        3
+    );
+    // commits under the previous secret verify only within the grace window.
+    light_client
+        .verify_committed_hashes(&committed_hashes, rotation.grace_appends)
+        .unwrap();
+    assert_eq!(
+        light_client
+            .verify_committed_hashes(&committed_hashes, 0)
+            .unwrap_err(),
+        LightClientError::SubsequentHashes(SubsequentHashesError::InvalidCommit)
+    );
+    // replaying the commit appends nothing and does not count for the rotation.
+    assert_eq!(
+        light_client
+            .append_subsequent_hashes(
+                committed_hashes.clone(),
+                now,
+                trust_options.trusting_period,
+                rotation,
+                b"third secret"
+            )
+            .unwrap(),
+        0
+    );
+    // commits under the secret before the previous one never verify.
+    light_client.rotate_commit_secret(b"third secret").unwrap();
+    assert_eq!(
+        light_client
+            .verify_committed_hashes(&committed_hashes, rotation.grace_appends)
+            .unwrap_err(),
+        LightClientError::SubsequentHashes(SubsequentHashesError::InvalidCommit)
+    );
+    // but the accepted hashes under the retired secret are recorded, unlike the altered ones.
+    assert!(light_client.is_recorded(&committed_hashes.hashes));
+    let mut altered_hashes = committed_hashes.hashes.clone();
+    altered_hashes.following_hashes[0].hash = vec![0; 32];
+    assert!(!light_client.is_recorded(&altered_hashes));
+    let mut altered_hashes = committed_hashes.hashes.clone();
+    altered_hashes.anchor_index += 1;
+    assert!(!light_client.is_recorded(&altered_hashes));
+}
+
+#[test]
+fn misbehaviour_test() {
+    let file = File::open("tests/testdata.json").unwrap();
+    let data: ChainData = serde_json::from_reader(file).unwrap();
+    let initial_header =
+        Header::decode(base64::decode(&data.initial_header).unwrap().as_slice()).unwrap();
+    let light_blocks: Vec<LightBlock> = data
+        .light_blocks
+        .iter()
+        .map(|block| LightBlock::decode(base64::decode(block).unwrap().as_slice()).unwrap())
+        .collect();
+    let anchor_validator_set = light_blocks[0].validator_set.clone().unwrap();
+
+    let mut light_client = LightClient::new(MemoryLightClientDB::default());
+    light_client
+        .init(
+            initial_header.clone(),
+            1,
+            initial_header.chain_id.clone(),
+            MAX_CLOCK_DRIFT,
+            b"secret",
+        )
+        .unwrap();
+    let trust_options = TrustOptions {
+        trust_threshold: TrustThreshold::ONE_THIRD,
+        trusting_period: 14 * 24 * 60 * 60,
+    };
+    let now = initial_header.time.as_ref().unwrap().seconds + 60;
+
+    // the same light block is not a conflict.
+    assert_eq!(
+        light_client
+            .verify_misbehaviour(
+                initial_header.clone(),
+                0,
+                anchor_validator_set.clone(),
+                light_blocks[8].clone(),
+                light_blocks[8].clone(),
+                trust_options,
+                now,
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::NoConflictingHeaders
+    );
+
+    // light blocks at the different heights are not a conflict.
+    assert_eq!(
+        light_client
+            .verify_misbehaviour(
+                initial_header.clone(),
+                0,
+                anchor_validator_set.clone(),
+                light_blocks[3].clone(),
+                light_blocks[8].clone(),
+                trust_options,
+                now,
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::UnmatchedMisbehaviourHeight {
+            height_1: 1000004,
+            height_2: 1000009,
+        }
+    );
+
+    // the conflicting header is not signed by the validators.
+    let mut forged = light_blocks[8].clone();
+    forged
+        .signed_header
+        .as_mut()
+        .unwrap()
+        .header
+        .as_mut()
+        .unwrap()
+        .app_hash = vec![0; 32];
+    assert!(matches!(
+        light_client
+            .verify_misbehaviour(
+                initial_header.clone(),
+                0,
+                anchor_validator_set,
+                light_blocks[8].clone(),
+                forged,
+                trust_options,
+                now,
+                &mut DalekEd25519Verifier {},
+            )
+            .unwrap_err(),
+        LightClientError::LightBlock(_)
+    ));
+
+    // the header trusted on recovery must be higher than the highest stored hash.
+    assert_eq!(
+        light_client
+            .append_trusted_header(&initial_header)
+            .unwrap_err(),
+        LightClientError::NonIncreasingHeight {
+            trusted: initial_header.height,
+            actual: initial_header.height,
+        }
+    );
+    let header = light_blocks[8]
+        .signed_header
+        .as_ref()
+        .unwrap()
+        .header
+        .clone()
+        .unwrap();
+    light_client.append_trusted_header(&header).unwrap();
+}
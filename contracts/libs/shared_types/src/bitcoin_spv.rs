@@ -3,8 +3,11 @@ use crate::state_proxy::client::Seed;
 use crate::CanonicalContractReference;
 use crate::Canonicalize;
 use crate::ContractReference;
+use crate::RelayCallback;
 use cosmwasm_std::Api;
 use cosmwasm_std::Binary;
+use cosmwasm_std::CanonicalAddr;
+use cosmwasm_std::HumanAddr;
 use cosmwasm_std::StdResult;
 use schemars::JsonSchema;
 use secret_toolkit::utils::calls::{HandleCallback, Query};
@@ -13,22 +16,44 @@ use serde::{Deserialize, Serialize};
 /// Contract Config set at contrat init.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct Config {
-    /// "bitcoin" | "testnet" | 'regtest"
+    /// "bitcoin" | "testnet" | "signet" | "testnet4" | 'regtest"
     pub bitcoin_network: String,
     /// minimum block needed for tx confirmed
     pub confirmation: u8,
+    /// number of the latest headers kept in the chain db.
+    /// older headers are pruned except the ones at difficulty adjustment height.
+    /// None disables pruning.
+    pub retained_header_length: Option<u32>,
+    /// challenge script of signet blocks.
+    /// None uses the challenge of the default signet.
+    pub signet_challenge: Option<Binary>,
+    /// enables compact proof of work sync with AddCompactPowProof.
+    /// None disables it, and all the headers must be submitted with AddHeaders.
+    pub compact_pow: Option<CompactPowConfig>,
 
     pub state_proxy: ContractReference,
+
+    /// owner allowed to add checkpoints. it can be a multisig contract.
+    /// checkpoints are trusted without the skipped headers, see AddCheckpoint.
+    pub owner: HumanAddr,
+    /// relayers allowed to add filter headers in addition to the owner.
+    /// filter headers have no commitment in the blocks, so they are trusted as submitted.
+    pub filter_header_relayers: Vec<HumanAddr>,
 }
 
 /// Contract Config set at contrat init.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CanonicalConfig {
-    /// "bitcoin" | "testnet" | 'regtest"
+    /// "bitcoin" | "testnet" | "signet" | "testnet4" | 'regtest"
     pub bitcoin_network: String,
     /// minimum block needed for tx confirmed
     pub confirmation: u8,
+    pub retained_header_length: Option<u32>,
+    pub signet_challenge: Option<Binary>,
+    pub compact_pow: Option<CompactPowConfig>,
     pub state_proxy: CanonicalContractReference,
+    pub owner: CanonicalAddr,
+    pub filter_header_relayers: Vec<CanonicalAddr>,
 }
 
 impl Canonicalize for Config {
@@ -37,18 +62,64 @@ impl Canonicalize for Config {
         Ok(CanonicalConfig {
             bitcoin_network: self.bitcoin_network,
             confirmation: self.confirmation,
+            retained_header_length: self.retained_header_length,
+            signet_challenge: self.signet_challenge,
+            compact_pow: self.compact_pow,
             state_proxy: self.state_proxy.into_canonical(api)?,
+            owner: self.owner.into_canonical(api)?,
+            filter_header_relayers: self
+                .filter_header_relayers
+                .into_iter()
+                .map(|relayer| relayer.into_canonical(api))
+                .collect::<StdResult<Vec<CanonicalAddr>>>()?,
         })
     }
     fn from_canonical<A: Api>(canonical: Self::Canonicalized, api: &A) -> StdResult<Self> {
         Ok(Self {
             bitcoin_network: canonical.bitcoin_network,
             confirmation: canonical.confirmation,
+            retained_header_length: canonical.retained_header_length,
+            signet_challenge: canonical.signet_challenge,
+            compact_pow: canonical.compact_pow,
             state_proxy: ContractReference::from_canonical(canonical.state_proxy, api)?,
+            owner: HumanAddr::from_canonical(canonical.owner, api)?,
+            filter_header_relayers: canonical
+                .filter_header_relayers
+                .into_iter()
+                .map(|relayer| HumanAddr::from_canonical(relayer, api))
+                .collect::<StdResult<Vec<HumanAddr>>>()?,
         })
     }
 }
 
+/// Config of compact proof of work sync.
+///
+/// Compact proof jumps the chain far ahead by sampling the skipped headers,
+/// instead of validating all of them.
+/// The new tip has only probabilistic work guarantees:
+/// a commitment whose headers have valid work only for a fraction f of them
+/// passes with probability at most f^sample_count.
+/// The seed is drawn by the contract after the commitment (CommitCompactPowProof),
+/// and a commitment can not be replaced until its proof is accepted,
+/// so the seeds can only be ground with the commitments of other addresses.
+/// The contract accepts at most one commitment in commit_interval blocks,
+/// so a forged chain passes with probability at most n * f^sample_count
+/// within n * commit_interval blocks.
+/// Retargets and timestamps of the skipped headers are not validated,
+/// and headers below the new tip are no longer provable.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct CompactPowConfig {
+    /// number of headers randomly sampled from a proof.
+    pub sample_count: u32,
+    /// easiest target of the sampled headers in compact format.
+    /// it should be close to the current difficulty,
+    /// since forging headers under this target is as cheap as the target.
+    pub max_target_bits: u32,
+    /// minimum number of blocks between two commitments of any provers.
+    /// it bounds the number of the seeds drawn while the tip stays.
+    pub commit_interval: u64,
+}
+
 // Bitcoin Merkle Proof Std Message
 #[derive(JsonSchema, Serialize, Deserialize, Clone, Debug, Default)]
 pub struct MerkleProofMsg {
@@ -56,6 +127,46 @@ pub struct MerkleProofMsg {
     pub siblings: Vec<String>,
 }
 
+// Bitcoin Tx Output Std Message
+#[derive(JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TxOutMsg {
+    /// value in satoshi
+    pub value: u64,
+    pub script_pubkey: Binary,
+    /// address of the script_pubkey on the configured network.
+    /// None for non-standard scripts.
+    pub address: Option<String>,
+}
+
+// Block Reward Std Message
+#[derive(JsonSchema, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BlockRewardMsg {
+    /// block subsidy in satoshi
+    pub subsidy: u64,
+    /// total value of the coinbase outputs in satoshi.
+    /// fees of the block are the value over the subsidy, if the miner claimed all of them.
+    pub coinbase_value: u64,
+}
+
+// Signet Header Std Message
+#[derive(JsonSchema, Serialize, Deserialize, Clone, Debug)]
+pub struct SignetHeaderMsg {
+    pub header: Binary,
+    /// coinbase tx carrying the block solution in the witness commitment output.
+    pub coinbase_tx: Binary,
+    pub coinbase_merkle_proof: MerkleProofMsg,
+}
+
+// Sampled Header of Compact Proof of Work Std Message
+#[derive(JsonSchema, Serialize, Deserialize, Clone, Debug)]
+pub struct SampledHeaderMsg {
+    pub header: Binary,
+    /// merkle proof of the block hash against the commitment.
+    pub merkle_proof: MerkleProofMsg,
+    /// merkle proof of the previous block hash. None for the first header.
+    pub prev_merkle_proof: Option<MerkleProofMsg>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct InitialHeader {
     pub height: u32,
@@ -66,8 +177,13 @@ pub struct InitialHeader {
 pub struct InitMsg {
     pub bitcoin_network: String,
     pub confirmation: u8,
+    pub retained_header_length: Option<u32>,
+    pub signet_challenge: Option<Binary>,
+    pub compact_pow: Option<CompactPowConfig>,
     pub initial_header: Option<InitialHeader>,
     pub state_proxy: ContractReference,
+    pub owner: HumanAddr,
+    pub filter_header_relayers: Vec<HumanAddr>,
     pub seed: Seed,
 }
 
@@ -79,6 +195,56 @@ pub enum HandleMsg {
     AddHeaders {
         tip_height: u32,
         headers: Vec<Binary>,
+        /// reports the number of the accepted headers to the relayer contract.
+        relay_callback: Option<RelayCallback>,
+    },
+    /// adds signet headers with the block solutions.
+    AddSignetHeaders {
+        tip_height: u32,
+        headers: Vec<SignetHeaderMsg>,
+    },
+    /// jumps the chain forward to a trusted header at difficulty adjustment height.
+    /// only the owner can add checkpoints.
+    /// the skipped headers are not verified and their work is estimated with the target of the header,
+    /// so the owner is trusted to add a header of the most-work chain.
+    /// headers below the checkpoint are no longer provable.
+    AddCheckpoint { height: u32, header: Binary },
+    /// commits to the skipped headers of a compact proof of work,
+    /// and draws the seed of the samples from the prng of the contract.
+    /// the sample indexes are queried with CompactPowSampleIndexes.
+    /// each prover has one commitment until its proof is accepted,
+    /// and no commitment is accepted within commit_interval blocks from the last one.
+    /// only available if compact_pow is set in the config.
+    CommitCompactPowProof {
+        height: u32,
+        /// merkle root of the block hashes of the skipped headers in hex.
+        commitment: String,
+        /// number of the committed headers, from the next of the tip to `height`.
+        leaf_count: u32,
+    },
+    /// jumps the chain forward to the header at difficulty adjustment height
+    /// with the sampled headers of the commitment of the sender.
+    AddCompactPowProof {
+        height: u32,
+        commitment: String,
+        leaf_count: u32,
+        /// sampled headers in the order of the sample indexes.
+        /// the first one is connected to the tip and the last one is the header at `height`.
+        samples: Vec<SampledHeaderMsg>,
+    },
+    /// adds BIP157 filter headers of the basic filters of the blocks from start_height.
+    /// each filter header is bound to the hash of the stored block at its height,
+    /// and chained from the filter header of the previous block.
+    /// filter hashes are not validated against the block contents,
+    /// so only the owner and the filter header relayers in the config can add them,
+    /// and conflicting filter headers are rejected unless they are sent by the owner.
+    AddFilterHeaders {
+        start_height: u32,
+        /// BIP157 filter hashes in hex.
+        filter_hashes: Vec<String>,
+        /// filter header of the block before start_height, anchoring the filter header chain.
+        /// only the owner can set it, except zero hash before the genesis block.
+        prev_filter_header: Option<String>,
     },
 }
 
@@ -93,10 +259,56 @@ pub enum QueryMsg {
         height: u32,
     },
     BestHeaderHash {},
+    OldestProvableHeight {},
     VerifyMerkleProof {
         height: u32,
         tx: Binary,
         merkle_proof: MerkleProofMsg,
+        /// merkle proof of the coinbase tx in the same block.
+        /// it fixes the depth of merkle tree so that an inner node can not be proven as a tx.
+        coinbase_merkle_proof: Option<MerkleProofMsg>,
+        /// coinbase tx of the block proven by coinbase_merkle_proof, required with it.
+        /// the height is checked against the height in the coinbase tx (BIP34).
+        coinbase_tx: Option<Binary>,
+    },
+    /// verifies tx same as VerifyMerkleProof and returns the decoded tx.
+    /// confirmations in the answer counts both the block of tx and the tip.
+    VerifyAndDecodeTx {
+        height: u32,
+        tx: Binary,
+        merkle_proof: MerkleProofMsg,
+        coinbase_merkle_proof: Option<MerkleProofMsg>,
+        coinbase_tx: Option<Binary>,
+    },
+    /// verifies tx with serialized MerkleBlock, the output of bitcoind gettxoutproof.
+    VerifyTxOutProof {
+        tx: Binary,
+        proof: Binary,
+    },
+    /// verifies tx including its witness, with the witness commitment in the coinbase tx (BIP141).
+    VerifyWitnessMerkleProof {
+        height: u32,
+        tx: Binary,
+        coinbase_tx: Binary,
+        /// merkle proof of the coinbase tx against the merkle root of the block.
+        coinbase_merkle_proof: MerkleProofMsg,
+        /// merkle proof of wtxid against the witness root committed by the coinbase tx.
+        witness_merkle_proof: MerkleProofMsg,
+    },
+    /// verifies the BIP158 basic filter of the block against the stored filter header,
+    /// and matches the scripts against the filter.
+    /// no match shows that the block has no output to and no input from the scripts,
+    /// as far as the owner and the filter header relayers submitted the honest filter headers.
+    VerifyBlockFilter {
+        height: u32,
+        filter: Binary,
+        /// filter header of the previous block in hex.
+        prev_filter_header: String,
+        scripts: Vec<Binary>,
+    },
+    /// sample indexes of the compact proof of work committed by the prover.
+    CompactPowSampleIndexes {
+        prover: HumanAddr,
     },
     Config {},
 }
@@ -108,8 +320,44 @@ impl Query for QueryMsg {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryAnswer {
-    BlockHeader { header: Binary },
-    BestHeaderHash { hash: String },
+    BlockHeader {
+        header: Binary,
+    },
+    BestHeaderHash {
+        hash: String,
+    },
+    OldestProvableHeight {
+        height: u32,
+    },
     Config(Config),
-    VerifyMerkleProof { success: bool },
+    VerifyMerkleProof {
+        success: bool,
+    },
+    VerifyAndDecodeTx {
+        txid: String,
+        outputs: Vec<TxOutMsg>,
+        confirmations: u32,
+        block_time: u32,
+        /// reward of the block, set if the coinbase tx is given.
+        block_reward: Option<BlockRewardMsg>,
+    },
+    VerifyTxOutProof {
+        success: bool,
+        height: u32,
+    },
+    VerifyWitnessMerkleProof {
+        success: bool,
+    },
+    CompactPowSampleIndexes {
+        height: u32,
+        commitment: String,
+        leaf_count: u32,
+        indexes: Vec<u32>,
+    },
+    VerifyBlockFilter {
+        /// true if any of the scripts matches the filter.
+        /// it can be a false positive with probability 1/784931 per script.
+        matched: bool,
+        confirmations: u32,
+    },
 }
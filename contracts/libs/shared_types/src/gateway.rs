@@ -87,11 +87,16 @@ pub enum HandleMsg {
         height: u32,
         tx: Binary,
         merkle_proof: bitcoin_spv::MerkleProofMsg,
+        /// coinbase tx of the block, which proves merkle_proof is not of an inner node.
+        coinbase_tx: Binary,
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg,
     },
     ReleaseIncorrectAmountBTC {
         height: u32,
         tx: Binary,
         merkle_proof: bitcoin_spv::MerkleProofMsg,
+        coinbase_tx: Binary,
+        coinbase_merkle_proof: bitcoin_spv::MerkleProofMsg,
         recipient_address: String,
         fee_per_vb: u64,
     },
@@ -1,4 +1,5 @@
 use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, StdResult};
+use secret_toolkit::utils::HandleCallback;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "bitcoin_spv")]
@@ -67,3 +68,27 @@ impl Canonicalize for ContractReference {
         })
     }
 }
+
+/// Callback requested by a relayer contract (Shuriken) with relayed messages.
+/// The receiving contract reports the accepted submissions to the contract with RelayCallbackMsg.
+#[derive(Serialize, Deserialize, schemars::JsonSchema, Debug, Clone, PartialEq)]
+pub struct RelayCallback {
+    pub contract: ContractReference,
+    /// relayer credited for the accepted submissions.
+    pub relayer: HumanAddr,
+}
+
+/// Report of the accepted submissions of a relayer.
+/// It is handled as the same named HandleMsg of the callback contract.
+#[derive(Serialize, Deserialize, schemars::JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayCallbackMsg {
+    /// headers newly stored on the best chain of bitcoin_spv.
+    HeadersAccepted { relayer: HumanAddr, count: u32 },
+    /// hashes appended to sfps.
+    CommittedHashesAccepted { relayer: HumanAddr, count: u32 },
+}
+
+impl HandleCallback for RelayCallbackMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
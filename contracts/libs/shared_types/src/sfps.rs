@@ -1,15 +1,18 @@
-use super::{ContractReference, BLOCK_SIZE};
+use super::{ContractReference, RelayCallback, BLOCK_SIZE};
 use crate::state_proxy;
-use cosmwasm_std::{from_binary, Binary, Querier, StdError, StdResult};
+use cosmwasm_std::{from_binary, Binary, HumanAddr, Querier, StdError, StdResult};
 use schemars::JsonSchema;
 use secret_toolkit::utils::{HandleCallback, Query};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 pub use sfps_lib;
-pub use sfps_lib::cosmos_proto::tendermint::abci::ResponseDeliverTx;
-pub use sfps_lib::cosmos_proto::tendermint::types::{Header, LightBlock};
+pub use sfps_lib::cosmos_proto::tendermint::abci::{ExecTxResult, ResponseDeliverTx};
+pub use sfps_lib::cosmos_proto::tendermint::crypto::ProofOps;
+pub use sfps_lib::cosmos_proto::tendermint::types::{Header, LightBlock, ValidatorSet};
+pub use sfps_lib::exec_tx_result_proof::ExecTxResultProof;
 pub use sfps_lib::merkle::MerkleProof;
 pub use sfps_lib::response_deliver_tx_proof::ResponseDeliverTxProof;
+pub use sfps_lib::state_proof::StateProof;
 pub use sfps_lib::subsequent_hashes::{CommittedHashes, HeaderHashWithHeight};
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -26,6 +29,35 @@ pub struct InitMsg {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct Config {
     pub state_proxy: ContractReference,
+    /// selects the wire format of the tx results of the chain.
+    #[serde(default)]
+    pub chain_version: ChainVersion,
+    /// owner allowed to recover the client frozen by misbehaviour. it can be a multisig contract.
+    pub owner: HumanAddr,
+    /// chain id of the headers, e.g. "secret-4".
+    pub chain_id: String,
+    /// seconds by which the header time can be ahead of the block time of this contract.
+    pub max_clock_drift: u64,
+}
+
+/// Consensus engine version of the chain, which determines how the tx results are committed to 'last_results_hash'.
+#[derive(Serialize, Deserialize, Clone, Copy, JsonSchema, Debug, PartialEq)]
+pub enum ChainVersion {
+    /// ResponseDeliverTx of Tendermint v0.34.
+    #[serde(rename = "tendermint_0_34")]
+    Tendermint034,
+    /// ResponseDeliverTx of CometBFT v0.37.
+    #[serde(rename = "cometbft_0_37")]
+    CometBft037,
+    /// ExecTxResult in ResponseFinalizeBlock of CometBFT v0.38.
+    #[serde(rename = "cometbft_0_38")]
+    CometBft038,
+}
+
+impl Default for ChainVersion {
+    fn default() -> Self {
+        Self::Tendermint034
+    }
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
@@ -35,8 +67,16 @@ pub enum QueryAnswer {
     CurrentHighestHeaderHash { hash: Binary, height: i64 },
     HashListLength { length: u64 },
     HashByIndex { hash: Binary, height: i64 },
+    HashIndexForHeight { index: u64, height: i64 },
+    Hashes { hashes: Vec<HeaderHashWithHeight> },
+    ChainVersion { chain_version: ChainVersion },
+    FrozenHeight { height: Option<i64> },
     VerifyResponseDeliverTxProof { decrypted_data: Binary },
     VerifySubsequentLightBlocks { committed_hashes: CommittedHashes },
+    /// `recorded` is true when the hashes are in the hash list,
+    /// so the commit which is not valid any more was made under a retired secret rather than forged.
+    VerifyCommittedHashes { valid: bool, recorded: bool },
+    VerifyStateProof { height: i64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, JsonSchema, Debug, PartialEq)]
@@ -48,6 +88,18 @@ pub enum QueryMsg {
     HashByIndex {
         index: u64,
     },
+    /// answers the smallest index of the hash whose height is at or above `height`, and the height of the hash.
+    HashIndexForHeight {
+        height: i64,
+    },
+    /// answers at most `limit` hashes from the index `start`.
+    Hashes {
+        start: u64,
+        limit: u32,
+    },
+    ChainVersion {},
+    /// height of the conflicting light blocks if the client is frozen by misbehaviour.
+    FrozenHeight {},
     VerifyResponseDeliverTxProof {
         merkle_proof: MerkleProof,
         #[schemars(with = "Vec<String>")]
@@ -56,6 +108,9 @@ pub enum QueryMsg {
 
         block_hash_index: u64,
         encryption_key: Binary,
+        /// index of the msg in the tx whose data is decrypted.
+        #[serde(default)]
+        msg_index: u64,
     },
     VerifySubsequentLightBlocks {
         #[schemars(with = "String")]
@@ -67,6 +122,29 @@ pub enum QueryMsg {
         following_light_blocks: Vec<LightBlock>,
         commit_flags: Vec<bool>,
     },
+    /// checks the commit of the hashes with the commit secret,
+    /// or with the previous one within the grace window of the rotation,
+    /// and whether the hashes are recorded in the hash list.
+    VerifyCommittedHashes {
+        committed_hashes: CommittedHashes,
+    },
+    /// verifies the ICS23 proof of the key-value pair in the store against app_hash of the first header,
+    /// and answers the height of the first header.
+    /// the highest header must be stored at block_hash_index.
+    VerifyStateProof {
+        /// proof ops of abci query with prove=true.
+        #[schemars(with = "String")]
+        #[serde(with = "serde_proto_message")]
+        proof_ops: ProofOps,
+        #[schemars(with = "Vec<String>")]
+        #[serde(with = "serde_proto_message_array")]
+        headers: Vec<Header>,
+        block_hash_index: u64,
+        store_key: String,
+        key: Binary,
+        /// None to verify the non-existence of the key.
+        value: Option<Binary>,
+    },
 }
 
 impl Query for QueryMsg {
@@ -80,12 +158,14 @@ pub fn verify_response_deliver_tx_proof<Q: Querier, H: DeserializeOwned>(
     headers: Vec<Header>,
     block_hash_index: u64,
     encryption_key: Binary,
+    msg_index: u64,
 ) -> StdResult<H> {
     let answer: QueryAnswer = (QueryMsg::VerifyResponseDeliverTxProof {
         merkle_proof,
         headers,
         block_hash_index,
         encryption_key,
+        msg_index,
     })
     .query(querier, sfps_reference.hash, sfps_reference.address)?;
     match answer {
@@ -96,12 +176,90 @@ pub fn verify_response_deliver_tx_proof<Q: Querier, H: DeserializeOwned>(
     }
 }
 
+/// answers whether the commit is valid, and whether the hashes are recorded in the hash list.
+pub fn verify_committed_hashes<Q: Querier>(
+    querier: &Q,
+    sfps_reference: ContractReference,
+    committed_hashes: CommittedHashes,
+) -> StdResult<(bool, bool)> {
+    let answer: QueryAnswer = (QueryMsg::VerifyCommittedHashes { committed_hashes }).query(
+        querier,
+        sfps_reference.hash,
+        sfps_reference.address,
+    )?;
+    match answer {
+        QueryAnswer::VerifyCommittedHashes { valid, recorded } => Ok((valid, recorded)),
+        _ => Err(StdError::generic_err("unexpected answer")),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn verify_state_proof<Q: Querier>(
+    querier: &Q,
+    sfps_reference: ContractReference,
+    proof_ops: ProofOps,
+    headers: Vec<Header>,
+    block_hash_index: u64,
+    store_key: String,
+    key: Binary,
+    value: Option<Binary>,
+) -> StdResult<i64> {
+    let answer: QueryAnswer = (QueryMsg::VerifyStateProof {
+        proof_ops,
+        headers,
+        block_hash_index,
+        store_key,
+        key,
+        value,
+    })
+    .query(querier, sfps_reference.hash, sfps_reference.address)?;
+    match answer {
+        QueryAnswer::VerifyStateProof { height } => Ok(height),
+        _ => Err(StdError::generic_err("unexpected answer")),
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, JsonSchema, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 // Workaround for exports schemas for duplicated name types on different modules.
 #[schemars(rename = "SFPSHandleMsg")]
 pub enum HandleMsg {
-    AppendSubsequentHashes { committed_hashes: CommittedHashes },
+    AppendSubsequentHashes {
+        committed_hashes: CommittedHashes,
+        /// reports the number of the appended hashes to the relayer contract.
+        relay_callback: Option<RelayCallback>,
+    },
+    /// freezes the client with the evidence that the validators trusted at the anchor header
+    /// signed two conflicting light blocks at the same height.
+    /// while the client is frozen, the proofs, the light blocks and the committed hashes are not verified.
+    SubmitMisbehaviour {
+        #[schemars(with = "String")]
+        #[serde(with = "serde_proto_message")]
+        anchor_header: Header,
+        anchor_header_index: u64,
+        /// validator set whose hash is validators_hash of the anchor header.
+        #[schemars(with = "String")]
+        #[serde(with = "serde_proto_message")]
+        anchor_validator_set: ValidatorSet,
+        #[schemars(with = "String")]
+        #[serde(with = "serde_proto_message")]
+        light_block_1: LightBlock,
+        #[schemars(with = "String")]
+        #[serde(with = "serde_proto_message")]
+        light_block_2: LightBlock,
+    },
+    /// unfreezes the client, or restarts the client whose highest hash is beyond the trusting period.
+    /// only the owner can recover the client.
+    /// trusted_header is appended as the highest hash, so that the following light blocks are verified from it.
+    /// it is required to recover the expired client.
+    RecoverClient {
+        #[schemars(with = "Option<String>")]
+        #[serde(default, with = "serde_proto_message_option")]
+        trusted_header: Option<Header>,
+    },
+    /// replaces the commit secret with a new one generated from the entropy. only the owner can rotate the secret.
+    /// commits under the previous secret still verify within the grace window.
+    RotateCommitSecret { entropy: Binary },
 }
 
 impl HandleCallback for HandleMsg {
@@ -134,6 +292,40 @@ pub mod serde_proto_message {
     }
 }
 
+pub mod serde_proto_message_option {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use sfps_lib::cosmos_proto::prost::Message;
+
+    pub fn deserialize<'de, D, M>(deserializer: D) -> Result<Option<M>, D::Error>
+    where
+        D: Deserializer<'de>,
+        M: Message + Default,
+    {
+        let string: Option<String> = Option::deserialize(deserializer)?;
+        string
+            .map(|string| {
+                let bin = base64::decode(&string).map_err(D::Error::custom)?;
+                M::decode(bin.as_slice()).map_err(D::Error::custom)
+            })
+            .transpose()
+    }
+
+    pub fn serialize<S, M>(value: &Option<M>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        M: Message,
+    {
+        match value {
+            Some(value) => {
+                let base64_string = base64::encode(value.encode_to_vec().as_slice());
+                serializer.serialize_some(&base64_string)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
 pub mod serde_proto_message_array {
     use serde::de::Error as _;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -188,4 +380,24 @@ mod test {
         let mut deserializer = serde_json::Deserializer::from_str(&json);
         let _: Vec<Header> = serde_proto_message_array::deserialize(&mut deserializer).unwrap();
     }
+
+    #[test]
+    fn test_serde_proto_message_option() {
+        let msg: HandleMsg = serde_json::from_str(r#"{"recover_client":{}}"#).unwrap();
+        assert_eq!(
+            msg,
+            HandleMsg::RecoverClient {
+                trusted_header: None
+            }
+        );
+        let msg = HandleMsg::RecoverClient {
+            trusted_header: Some(Header {
+                height: 10,
+                ..Default::default()
+            }),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(json, r#"{"recover_client":{"trusted_header":"GAo="}}"#);
+        assert_eq!(serde_json::from_str::<HandleMsg>(&json).unwrap(), msg);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::{
     sfps, state_proxy, CanonicalContractReference, Canonicalize, ContractReference, BLOCK_SIZE,
 };
-use cosmwasm_std::{Api, Binary, StdResult};
+use cosmwasm_std::{Api, Binary, CanonicalAddr, HumanAddr, StdResult, Uint128};
 use schemars::JsonSchema;
 use secret_toolkit::utils::calls::{HandleCallback, Query};
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,10 @@ pub struct Config {
     pub bitcoin_spv: ContractReference,
     pub sfps: ContractReference,
     pub state_proxy: ContractReference,
+    /// owner allowed to update the reward rates and budget. it can be a multisig contract.
+    pub owner: HumanAddr,
+    /// SNIP-20 token (SNB) paid to relayers as reward.
+    pub reward_token: ContractReference,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -18,6 +22,8 @@ pub struct CanonicalConfig {
     pub bitcoin_spv: CanonicalContractReference,
     pub sfps: CanonicalContractReference,
     pub state_proxy: CanonicalContractReference,
+    pub owner: CanonicalAddr,
+    pub reward_token: CanonicalContractReference,
 }
 
 impl Canonicalize for Config {
@@ -27,6 +33,8 @@ impl Canonicalize for Config {
             bitcoin_spv: self.bitcoin_spv.into_canonical(api)?,
             sfps: self.sfps.into_canonical(api)?,
             state_proxy: self.state_proxy.into_canonical(api)?,
+            owner: self.owner.into_canonical(api)?,
+            reward_token: self.reward_token.into_canonical(api)?,
         })
     }
     fn from_canonical<A: Api>(canonical: Self::Canonicalized, api: &A) -> StdResult<Self> {
@@ -34,15 +42,79 @@ impl Canonicalize for Config {
             bitcoin_spv: ContractReference::from_canonical(canonical.bitcoin_spv, api)?,
             sfps: ContractReference::from_canonical(canonical.sfps, api)?,
             state_proxy: ContractReference::from_canonical(canonical.state_proxy, api)?,
+            owner: HumanAddr::from_canonical(canonical.owner, api)?,
+            reward_token: ContractReference::from_canonical(canonical.reward_token, api)?,
         })
     }
 }
 
+/// Reward rates and the remaining budget of relayer rewards.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RewardConfig {
+    /// reward per header newly stored on the best chain of bitcoin_spv.
+    pub header_reward: Uint128,
+    /// reward per hash appended to sfps.
+    pub committed_hash_reward: Uint128,
+    /// total reward which can still be credited.
+    /// credits are cut at the budget, so it must not exceed the reward token balance of the contract.
+    pub budget: Uint128,
+}
+
+/// Registration of the relayers.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RelayerConfig {
+    /// only the allowed relayers can relay the submissions if true.
+    pub registration_required: bool,
+    /// minimum bond of the reward token to be allowed.
+    pub bond_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayerStatus {
+    /// bonded but not allowed yet.
+    Pending,
+    /// allowed by the owner.
+    Allowed,
+    /// removed from the allow-list by the owner. the bond can be withdrawn.
+    Removed,
+    /// the bond was slashed by the owner.
+    Slashed,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RelayerInfo {
+    pub address: HumanAddr,
+    pub bond: Uint128,
+    pub status: RelayerStatus,
+}
+
+/// Snip20ReceiveMsg should be de/serialized under `Receive()` variant in a HandleMsg
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct Snip20ReceiveMsg {
+    pub sender: HumanAddr,
+    pub from: HumanAddr,
+    pub amount: Uint128,
+    pub memo: Option<String>,
+    pub msg: Option<Binary>,
+}
+
+/// msg sent with the reward token to the contract.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// adds the sent amount to the bond of the sender.
+    Bond {},
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub struct InitMsg {
     pub seed: state_proxy::client::Seed,
     #[schemars(inline)]
     pub config: Config,
+    pub reward_config: RewardConfig,
+    pub relayer_config: RelayerConfig,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
@@ -55,6 +127,28 @@ pub enum HandleMsg {
     SFPSProxyAppendSubsequentHashes {
         committed_hashes: sfps::CommittedHashes,
     },
+    /// callback from bitcoin_spv crediting the relayer.
+    HeadersAccepted { relayer: HumanAddr, count: u32 },
+    /// callback from sfps crediting the relayer.
+    CommittedHashesAccepted { relayer: HumanAddr, count: u32 },
+    /// transfers all the credited reward of the sender.
+    ClaimReward {},
+    /// only the owner can update the reward config.
+    SetRewardConfig { reward_config: RewardConfig },
+    /// bonds the reward token sent by `Send` of SNIP-20.
+    Receive(Snip20ReceiveMsg),
+    /// withdraws the bond of the sender. allowed relayers must be removed first.
+    Unbond {},
+    /// only the owner can allow the bonded relayers.
+    AllowRelayer { relayer: HumanAddr },
+    /// only the owner can remove the relayers from the allow-list.
+    RemoveRelayer { relayer: HumanAddr },
+    /// only the owner can update the relayer config.
+    SetRelayerConfig { relayer_config: RelayerConfig },
+    /// only the owner can slash the bond of the relayer. the bond is added to the reward budget.
+    /// the submissions are verified by bitcoin_spv and sfps in the same tx as the relay,
+    /// so the faults of the relayers (e.g. spamming or censoring) are judged by the owner off-chain.
+    Slash { relayer: HumanAddr },
 }
 
 impl HandleCallback for HandleMsg {
@@ -65,6 +159,16 @@ impl HandleCallback for HandleMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    RewardConfig {},
+    /// unclaimed reward of the relayer.
+    Reward {
+        relayer: HumanAddr,
+    },
+    RelayerConfig {},
+    Relayers {
+        page: u32,
+        page_size: u32,
+    },
 }
 
 impl Query for QueryMsg {
@@ -75,4 +179,8 @@ impl Query for QueryMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryAnswer {
     Config(Config),
+    RewardConfig(RewardConfig),
+    Reward { amount: Uint128 },
+    RelayerConfig(RelayerConfig),
+    Relayers(Vec<RelayerInfo>),
 }
@@ -1,4 +1,6 @@
-mod api_ed25519_verifier;
+use sfps_lib::light_client::CommitSecretRotation;
+
+mod api_signature_verifier;
 mod handle;
 mod init;
 mod query;
@@ -9,5 +11,12 @@ pub use query::query;
 
 pub const CONTRACT_LABEL: &[u8] = b"sfps";
 
+/// the commit secret is rotated every 100 appends.
+/// commits queried just before the rotation are still appended within the following 5 appends.
+pub const COMMIT_SECRET_ROTATION: CommitSecretRotation = CommitSecretRotation {
+    interval: 100,
+    grace_appends: 5,
+};
+
 #[cfg(test)]
 mod test;
@@ -1,11 +1,11 @@
 use cosmwasm_std::Api;
-use sfps_lib::light_block::Ed25519Verifier;
+use sfps_lib::light_block::{Ed25519Verifier, Secp256k1Verifier};
 
-pub struct ApiEd25519Verifier<'a, A: Api> {
+pub struct ApiSignatureVerifier<'a, A: Api> {
     pub api: &'a A,
 }
 
-impl<'a, A: Api> Ed25519Verifier<String> for ApiEd25519Verifier<'a, A> {
+impl<'a, A: Api> Ed25519Verifier<String> for ApiSignatureVerifier<'a, A> {
     fn verify_batch(
         &mut self,
         messages: &[&[u8]],
@@ -31,6 +31,25 @@ impl<'a, A: Api> Ed25519Verifier<String> for ApiEd25519Verifier<'a, A> {
     }
 }
 
+impl<'a, A: Api> Secp256k1Verifier<String> for ApiSignatureVerifier<'a, A> {
+    fn verify(
+        &mut self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<(), String> {
+        if self
+            .api
+            .secp256k1_verify(message_hash, signature, public_key)
+            .map_err(|_| "verify failed: verify function returns error")?
+        {
+            Ok(())
+        } else {
+            Err("verify failed: verify function returns false".to_string())
+        }
+    }
+}
+
 #[cfg(feature = "full_signatures_test")]
 fn repeat_element<T: Clone>(element: &T, time: usize) -> Vec<T> {
     let mut vec = Vec::with_capacity(time);
@@ -1,19 +1,32 @@
-use crate::state::StorageLightClientDB;
+use crate::contract::api_signature_verifier::ApiSignatureVerifier;
+use crate::state::{
+    ensure_not_frozen, gen_commit_secret, read_frozen_height, read_highest_hash_time, read_owner,
+    write_frozen_height, write_highest_hash_time, StorageLightClientDB,
+};
 use cosmwasm_std::{
-    Api, Env, Extern, HandleResponse, HandleResult, Querier, StdError, StdResult, Storage,
+    Api, Binary, Env, Extern, HandleResponse, HandleResult, Querier, StdError, StdResult, Storage,
 };
-use secret_toolkit::utils::pad_handle_result;
-use sfps_lib::light_client::LightClient;
+use secret_toolkit::utils::{pad_handle_result, HandleCallback};
+use sfps_lib::light_block::TrustThreshold;
+use sfps_lib::light_client::{LightClient, ReadonlyLightClientDB, TrustOptions};
 use sfps_lib::subsequent_hashes::CommittedHashes;
-use shared_types::sfps::HandleMsg;
+use shared_types::sfps::{HandleMsg, Header, LightBlock, ValidatorSet};
 use shared_types::state_proxy::client::{Secp256k1ApiSigner, StateProxyDeps};
-use shared_types::BLOCK_SIZE;
+use shared_types::{RelayCallback, RelayCallbackMsg, BLOCK_SIZE};
+
+use super::{COMMIT_SECRET_ROTATION, CONTRACT_LABEL};
 
-use super::CONTRACT_LABEL;
+/// trust options of the validators of the anchor header,
+/// used to verify misbehaviour and to check the staleness of the anchor of the appended hashes.
+/// the trusting period is shorter than the unbonding period (21 days) of Secret Network.
+const TRUST_OPTIONS: TrustOptions = TrustOptions {
+    trust_threshold: TrustThreshold::ONE_THIRD,
+    trusting_period: 14 * 24 * 60 * 60,
+};
 
 pub fn handle<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
     let mut deps = StateProxyDeps::restore(
@@ -24,8 +37,30 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         &Secp256k1ApiSigner::new(&deps.api),
     )?;
     let result = match msg {
-        HandleMsg::AppendSubsequentHashes { committed_hashes } => {
-            try_append_subsequent_hashes(&mut deps, committed_hashes)
+        HandleMsg::AppendSubsequentHashes {
+            committed_hashes,
+            relay_callback,
+        } => try_append_subsequent_hashes(&mut deps, env, committed_hashes, relay_callback),
+        HandleMsg::SubmitMisbehaviour {
+            anchor_header,
+            anchor_header_index,
+            anchor_validator_set,
+            light_block_1,
+            light_block_2,
+        } => try_submit_misbehaviour(
+            &mut deps,
+            env,
+            anchor_header,
+            anchor_header_index,
+            anchor_validator_set,
+            light_block_1,
+            light_block_2,
+        ),
+        HandleMsg::RecoverClient { trusted_header } => {
+            try_recover_client(&mut deps, env, trusted_header)
+        }
+        HandleMsg::RotateCommitSecret { entropy } => {
+            try_rotate_commit_secret(&mut deps, env, entropy)
         }
     };
 
@@ -43,16 +78,160 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 
 fn try_append_subsequent_hashes<A: Api, Q: Querier>(
     deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
     committed_hashes: CommittedHashes,
+    relay_callback: Option<RelayCallback>,
 ) -> HandleResult {
+    ensure_not_frozen(&deps.storage)?;
+    // only the callback contract can request the callback, otherwise anyone could credit any relayer.
+    if let Some(relay_callback) = relay_callback.as_ref() {
+        if relay_callback.contract.address != env.message.sender {
+            return Err(StdError::generic_err(
+                "relay callback is not requested by the callback contract",
+            ));
+        }
+    }
+    // the secret used if this append reaches the rotation interval.
+    // the prng seed is unknown outside the contract, so the block time is enough as the entropy.
+    let next_commit_secret = gen_commit_secret(
+        &mut deps.storage,
+        &deps.api.canonical_address(&env.message.sender)?,
+        &env.block.time.to_be_bytes(),
+    )?;
+    let max_clock_drift =
+        StorageLightClientDB::from_readonly_storage(&deps.storage).get_max_clock_drift();
     let light_client_db = StorageLightClientDB::from_storage(&mut deps.storage);
     let mut light_client = LightClient::new(light_client_db);
-    light_client
-        .append_subsequent_hashes(committed_hashes)
+    let appended = light_client
+        .append_subsequent_hashes(
+            committed_hashes,
+            env.block.time as i64,
+            TRUST_OPTIONS.trusting_period,
+            COMMIT_SECRET_ROTATION,
+            &next_commit_secret,
+        )
         .map_err(|e| StdError::generic_err(e.to_string()))?;
+    if appended > 0 {
+        // the appended headers are not from the future beyond the max clock drift.
+        write_highest_hash_time(
+            &mut deps.storage,
+            (env.block.time as i64).saturating_add(max_clock_drift as i64),
+        )?;
+    }
+    let mut messages = vec![];
+    if let Some(relay_callback) = relay_callback {
+        if appended > 0 {
+            messages.push(
+                RelayCallbackMsg::CommittedHashesAccepted {
+                    relayer: relay_callback.relayer,
+                    count: appended,
+                }
+                .to_cosmos_msg(
+                    relay_callback.contract.hash,
+                    relay_callback.contract.address,
+                    None,
+                )?,
+            );
+        }
+    }
     Ok(HandleResponse {
-        messages: vec![],
+        messages,
         log: vec![],
         data: None,
     })
 }
+
+fn try_submit_misbehaviour<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    anchor_header: Header,
+    anchor_header_index: u64,
+    anchor_validator_set: ValidatorSet,
+    light_block_1: LightBlock,
+    light_block_2: LightBlock,
+) -> HandleResult {
+    if let Some(height) = read_frozen_height(&deps.storage)? {
+        return Err(StdError::generic_err(format!(
+            "client is already frozen at height {}",
+            height
+        )));
+    }
+    let light_client_db = StorageLightClientDB::from_readonly_storage(&deps.storage);
+    let mut light_client = LightClient::new(light_client_db);
+    let height = light_client
+        .verify_misbehaviour(
+            anchor_header,
+            anchor_header_index as usize,
+            anchor_validator_set,
+            light_block_1,
+            light_block_2,
+            TRUST_OPTIONS,
+            env.block.time as i64,
+            &mut ApiSignatureVerifier { api: &deps.api },
+        )
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    write_frozen_height(&mut deps.storage, Some(height))?;
+    Ok(HandleResponse::default())
+}
+
+fn try_recover_client<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    trusted_header: Option<Header>,
+) -> HandleResult {
+    if deps.api.canonical_address(&env.message.sender)? != read_owner(&deps.storage)? {
+        return Err(StdError::unauthorized());
+    }
+    if read_frozen_height(&deps.storage)?.is_none() {
+        // the client whose highest hash is beyond the trusting period can not append any more hashes,
+        // as the anchor of the appended hashes must be within it.
+        let expired = match read_highest_hash_time(&deps.storage)? {
+            Some(time) => {
+                time.saturating_add(TRUST_OPTIONS.trusting_period as i64) <= env.block.time as i64
+            }
+            None => false,
+        };
+        if !expired {
+            return Err(StdError::generic_err(
+                "client is neither frozen nor expired",
+            ));
+        }
+        if trusted_header.is_none() {
+            return Err(StdError::generic_err(
+                "trusted header is required to recover the expired client",
+            ));
+        }
+    }
+    if let Some(trusted_header) = trusted_header {
+        let trusted_header_time = trusted_header
+            .time
+            .as_ref()
+            .ok_or_else(|| StdError::generic_err("no header time"))?
+            .seconds;
+        let light_client_db = StorageLightClientDB::from_storage(&mut deps.storage);
+        let mut light_client = LightClient::new(light_client_db);
+        light_client
+            .append_trusted_header(&trusted_header)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        write_highest_hash_time(&mut deps.storage, trusted_header_time)?;
+    }
+    write_frozen_height(&mut deps.storage, None)?;
+    Ok(HandleResponse::default())
+}
+
+fn try_rotate_commit_secret<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    env: Env,
+    entropy: Binary,
+) -> HandleResult {
+    let sender = deps.api.canonical_address(&env.message.sender)?;
+    if sender != read_owner(&deps.storage)? {
+        return Err(StdError::unauthorized());
+    }
+    let new_secret = gen_commit_secret(&mut deps.storage, &sender, entropy.as_slice())?;
+    let light_client_db = StorageLightClientDB::from_storage(&mut deps.storage);
+    LightClient::new(light_client_db)
+        .rotate_commit_secret(&new_secret)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    Ok(HandleResponse::default())
+}
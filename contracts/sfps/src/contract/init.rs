@@ -1,13 +1,17 @@
 use crate::contract::CONTRACT_LABEL;
-use crate::state::StorageLightClientDB;
+use crate::state::{
+    gen_commit_secret, write_chain_version, write_highest_hash_time, write_owner,
+    StorageLightClientDB, PREFIX_PRNG,
+};
 use cosmwasm_std::{Api, Env, Extern, InitResponse, Querier, StdError, StdResult, Storage};
 use sfps_lib::light_client::LightClient;
+use shared_types::prng::init_prng;
 use shared_types::sfps::InitMsg;
 use shared_types::state_proxy::client::{Secp256k1ApiSigner, StateProxyDeps};
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
     let mut deps = StateProxyDeps::init(
@@ -20,10 +24,37 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         &Secp256k1ApiSigner::new(&deps.api),
     )?;
 
+    write_chain_version(&mut deps.storage, msg.config.chain_version)?;
+    write_owner(
+        &mut deps.storage,
+        &deps.api.canonical_address(&msg.config.owner)?,
+    );
+
+    init_prng(&mut deps.storage, PREFIX_PRNG, &env, msg.entropy.as_slice())?;
+    let commit_secret = gen_commit_secret(
+        &mut deps.storage,
+        &deps.api.canonical_address(&env.message.sender)?,
+        msg.entropy.as_slice(),
+    )?;
+
+    let initial_header_time = msg
+        .initial_header
+        .time
+        .as_ref()
+        .ok_or_else(|| StdError::generic_err("no header time"))?
+        .seconds;
+    write_highest_hash_time(&mut deps.storage, initial_header_time)?;
+
     let chaindb = StorageLightClientDB::from_storage(&mut deps.storage);
     let mut light_client = LightClient::new(chaindb);
     light_client
-        .init(msg.initial_header, msg.max_interval)
+        .init(
+            msg.initial_header,
+            msg.max_interval,
+            msg.config.chain_id,
+            msg.config.max_clock_drift,
+            &commit_secret,
+        )
         .map_err(|e| StdError::generic_err(e.to_string()))?;
 
     Ok(InitResponse {
@@ -38,7 +69,7 @@ mod test {
     use cosmwasm_std::testing::*;
     #[test]
     fn test_init() {
-        let init_msg_json = r#"{"seed": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=","config":{"state_proxy":{"address":"address","hash": "hash"}},"max_interval":10,"initial_header":"CgIICxIIc2VjcmV0LTQYwIQ9IgwIw6P0jAYQtaOfsQEqSAogtuqUfryBu9/yUD5L5ohj/Niyzz44955n/iQO83cWzeoSJAgBEiCU0ANcdBhca90ER7l69oj7YDHNEb0pz7lyoRqU+pHbvzIgsD/4zfRY74ub9Zb45/CqswPKk5Iiege/cqgaS/CDzwk6ICZVWiA4fVTWRBskcgDJ34oGMbYNLbvk2MJ0nMIBD/ifQiCe+7oc6mtMro8nx/Fugw+7/u1quNNSRd4mPWP+T3IRsEognvu6HOprTK6PJ8fxboMPu/7tarjTUkXeJj1j/k9yEbBSIHF75UIu/uz1xIubXqSv2cTC4AKmdsoulRLK54Ato32SWiCLihxZRJGEH8Bx0hvuPhW6tC3nOY6EWR9bfeoQnP66QGIgxzJ8au+AnupslgZSUUVNV9m8ae9045iV3D0Pkr33p7tqIOOwxEKY/BwUmvv0yJlvuSQnrkHkZJuTTKSVmRt4UrhVchQDXA/dn7uUwoktl7saawriZL0wGA==","entropy":"iJiTDs6+YrZHITULnyhjFWW4ciVxKWJ3+O5PYt2pBSM="}"#;
+        let init_msg_json = r#"{"seed": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=","config":{"state_proxy":{"address":"address","hash": "hash"},"owner":"owner","chain_id":"secret-4","max_clock_drift":10},"max_interval":10,"initial_header":"CgIICxIIc2VjcmV0LTQYwIQ9IgwIw6P0jAYQtaOfsQEqSAogtuqUfryBu9/yUD5L5ohj/Niyzz44955n/iQO83cWzeoSJAgBEiCU0ANcdBhca90ER7l69oj7YDHNEb0pz7lyoRqU+pHbvzIgsD/4zfRY74ub9Zb45/CqswPKk5Iiege/cqgaS/CDzwk6ICZVWiA4fVTWRBskcgDJ34oGMbYNLbvk2MJ0nMIBD/ifQiCe+7oc6mtMro8nx/Fugw+7/u1quNNSRd4mPWP+T3IRsEognvu6HOprTK6PJ8fxboMPu/7tarjTUkXeJj1j/k9yEbBSIHF75UIu/uz1xIubXqSv2cTC4AKmdsoulRLK54Ato32SWiCLihxZRJGEH8Bx0hvuPhW6tC3nOY6EWR9bfeoQnP66QGIgxzJ8au+AnupslgZSUUVNV9m8ae9045iV3D0Pkr33p7tqIOOwxEKY/BwUmvv0yJlvuSQnrkHkZJuTTKSVmRt4UrhVchQDXA/dn7uUwoktl7saawriZL0wGA==","entropy":"iJiTDs6+YrZHITULnyhjFWW4ciVxKWJ3+O5PYt2pBSM="}"#;
         let init_msg = cosmwasm_std::from_slice(init_msg_json.as_bytes()).unwrap();
         let mut deps = mock_dependencies(20, &[]);
         let env = mock_env("initializer", &[]);
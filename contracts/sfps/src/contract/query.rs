@@ -1,22 +1,33 @@
-use crate::contract::api_ed25519_verifier::ApiEd25519Verifier;
-use crate::state::StorageLightClientDB;
+use crate::contract::api_signature_verifier::ApiSignatureVerifier;
+use crate::state::{
+    ensure_not_frozen, read_chain_version, read_frozen_height, StorageLightClientDB,
+};
 use aes_siv::aead::generic_array::GenericArray;
 use aes_siv::siv::Aes128Siv;
 use cosmwasm_std::QueryResponse;
 use cosmwasm_std::{
     to_binary, Api, Binary, Extern, Querier, QueryResult, StdError, StdResult, Storage,
 };
+use sfps_lib::cosmos_proto::tendermint::crypto::ProofOps;
 use sfps_lib::cosmos_proto::tendermint::types::Header;
 use sfps_lib::cosmos_proto::tendermint::types::LightBlock;
+use sfps_lib::exec_tx_result_proof::{tx_msg_data_of_exec_tx_result, ExecTxResultProof};
 use sfps_lib::light_client::{LightClient, ReadonlyLightClientDB};
 use sfps_lib::merkle::MerkleProof;
-use sfps_lib::response_deliver_tx_proof::tx_msg_data_of_response_deliver_tx;
 use sfps_lib::response_deliver_tx_proof::ResponseDeliverTxProof;
-use shared_types::sfps::{QueryAnswer, QueryMsg};
+use sfps_lib::response_deliver_tx_proof::{
+    execute_contract_response_data, tx_msg_data_of_response_deliver_tx,
+};
+use sfps_lib::state_proof::StateProof;
+use sfps_lib::subsequent_hashes::CommittedHashes;
+use shared_types::sfps::{ChainVersion, QueryAnswer, QueryMsg};
 use shared_types::state_proxy::client::Secp256k1ApiSigner;
 use shared_types::state_proxy::client::StateProxyDeps;
 
-use super::CONTRACT_LABEL;
+use super::{COMMIT_SECRET_ROTATION, CONTRACT_LABEL};
+
+/// maximum number of the hashes answered by a Hashes query.
+const MAX_HASHES_LIMIT: u32 = 100;
 
 pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
     let deps = StateProxyDeps::restore(
@@ -31,18 +42,26 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
         QueryMsg::MaxInterval {} => query_max_interval(&deps.storage),
         QueryMsg::CurrentHighestHeaderHash {} => query_current_highest_block_hash(&deps.storage),
         QueryMsg::HashByIndex { index } => query_hash_by_index(&deps.storage, index),
+        QueryMsg::HashIndexForHeight { height } => {
+            query_hash_index_for_height(&deps.storage, height)
+        }
+        QueryMsg::Hashes { start, limit } => query_hashes(&deps.storage, start, limit),
         QueryMsg::HashListLength {} => query_hash_list_length(&deps.storage),
+        QueryMsg::ChainVersion {} => query_chain_version(&deps.storage),
+        QueryMsg::FrozenHeight {} => query_frozen_height(&deps.storage),
         QueryMsg::VerifyResponseDeliverTxProof {
             merkle_proof,
             headers,
             block_hash_index,
             encryption_key,
+            msg_index,
         } => query_verify_response_deliver_tx_proof(
             &deps,
             merkle_proof,
             headers,
             block_hash_index,
             encryption_key,
+            msg_index,
         ),
         QueryMsg::VerifySubsequentLightBlocks {
             anchor_header,
@@ -56,6 +75,25 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             following_light_blocks,
             commit_flags,
         ),
+        QueryMsg::VerifyCommittedHashes { committed_hashes } => {
+            query_verify_committed_hashes(&deps.storage, committed_hashes)
+        }
+        QueryMsg::VerifyStateProof {
+            proof_ops,
+            headers,
+            block_hash_index,
+            store_key,
+            key,
+            value,
+        } => query_verify_state_proof(
+            &deps.storage,
+            proof_ops,
+            headers,
+            block_hash_index,
+            store_key,
+            key,
+            value,
+        ),
     };
     Ok(result?)
 }
@@ -99,34 +137,103 @@ fn query_hash_by_index<S: Storage>(storage: &S, index: u64) -> Result<QueryRespo
     Ok(to_binary(&response)?)
 }
 
+fn query_hash_index_for_height<S: Storage>(
+    storage: &S,
+    height: i64,
+) -> Result<QueryResponse, StdError> {
+    let mut light_client_db = StorageLightClientDB::from_readonly_storage(storage);
+    let index = light_client_db
+        .get_index_for_height(height)
+        .ok_or_else(|| StdError::generic_err("no hash at or above the height"))?;
+    // this unwrap is ok because the index is of the stored hash
+    let hash = light_client_db.get_hash_by_index(index).unwrap();
+    let response = QueryAnswer::HashIndexForHeight {
+        index: index as u64,
+        height: hash.height,
+    };
+    Ok(to_binary(&response)?)
+}
+
+fn query_hashes<S: Storage>(
+    storage: &S,
+    start: u64,
+    limit: u32,
+) -> Result<QueryResponse, StdError> {
+    let mut light_client_db = StorageLightClientDB::from_readonly_storage(storage);
+    let end = light_client_db
+        .get_hash_list_length()
+        .min((start as usize).saturating_add(limit.min(MAX_HASHES_LIMIT) as usize));
+    let hashes = (start as usize..end)
+        .map(|index| light_client_db.get_hash_by_index(index).unwrap())
+        .collect();
+    let response = QueryAnswer::Hashes { hashes };
+    Ok(to_binary(&response)?)
+}
+
+fn query_chain_version<S: Storage>(storage: &S) -> Result<QueryResponse, StdError> {
+    let response = QueryAnswer::ChainVersion {
+        chain_version: read_chain_version(storage)?,
+    };
+    Ok(to_binary(&response)?)
+}
+
+fn query_frozen_height<S: Storage>(storage: &S) -> Result<QueryResponse, StdError> {
+    let response = QueryAnswer::FrozenHeight {
+        height: read_frozen_height(storage)?,
+    };
+    Ok(to_binary(&response)?)
+}
+
 fn query_verify_response_deliver_tx_proof<A: Api, Q: Querier>(
     deps: &StateProxyDeps<A, Q>,
     merkle_proof: MerkleProof,
     headers: Vec<Header>,
     block_hash_index: u64,
     encryption_key: Binary,
+    msg_index: u64,
 ) -> Result<QueryResponse, StdError> {
-    let response_deliver_tx_proof = ResponseDeliverTxProof {
-        merkle_proof,
-        headers,
-    };
-    // Verify Merkle Proof
+    ensure_not_frozen(&deps.storage)?;
+    let chain_version = read_chain_version(&deps.storage)?;
     let chaindb = StorageLightClientDB::from_readonly_storage(&deps.storage);
     let mut light_client = LightClient::new(chaindb);
-    light_client
-        .verify_response_deliver_tx_proof(&response_deliver_tx_proof, block_hash_index as usize)
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    // Verify Merkle Proof with the tx result format of the chain version
+    let tx_msg_data = match chain_version {
+        ChainVersion::Tendermint034 | ChainVersion::CometBft037 => {
+            let response_deliver_tx_proof = ResponseDeliverTxProof {
+                merkle_proof,
+                headers,
+            };
+            light_client
+                .verify_response_deliver_tx_proof(
+                    &response_deliver_tx_proof,
+                    block_hash_index as usize,
+                )
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            response_deliver_tx_proof
+                .leaf_response_deliver_tx()
+                .and_then(|leaf| tx_msg_data_of_response_deliver_tx(&leaf))
+        }
+        ChainVersion::CometBft038 => {
+            let exec_tx_result_proof = ExecTxResultProof {
+                merkle_proof,
+                headers,
+            };
+            light_client
+                .verify_exec_tx_result_proof(&exec_tx_result_proof, block_hash_index as usize)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            exec_tx_result_proof
+                .leaf_exec_tx_result()
+                .and_then(|leaf| tx_msg_data_of_exec_tx_result(&leaf))
+        }
+    }
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
 
     // Decrypt response data from contract
-    let encrypted_response_from_contract = &tx_msg_data_of_response_deliver_tx(
-        &response_deliver_tx_proof
-            .leaf_response_deliver_tx()
-            .map_err(|e| StdError::generic_err(e.to_string()))?,
-    )
-    .data[0]
-        .data;
+    let encrypted_response_from_contract =
+        execute_contract_response_data(tx_msg_data, msg_index as usize)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
     let decrypted_data = decrypt_response_from_contract(
-        encrypted_response_from_contract,
+        &encrypted_response_from_contract,
         encryption_key.as_slice(),
     )?;
 
@@ -141,6 +248,7 @@ fn query_verify_subsequent_light_blocks<A: Api, Q: Querier>(
     following_light_blocks: Vec<LightBlock>,
     commit_flags: Vec<bool>,
 ) -> Result<QueryResponse, StdError> {
+    ensure_not_frozen(&deps.storage)?;
     let chaindb = StorageLightClientDB::from_readonly_storage(&deps.storage);
     let mut light_client = LightClient::new(chaindb);
     let committed_hashes = light_client
@@ -149,13 +257,56 @@ fn query_verify_subsequent_light_blocks<A: Api, Q: Querier>(
             anchor_header_index as usize,
             following_light_blocks,
             commit_flags,
-            &mut ApiEd25519Verifier { api: &deps.api },
+            &mut ApiSignatureVerifier { api: &deps.api },
         )
         .map_err(|e| StdError::generic_err(e.to_string()))?;
     let res = QueryAnswer::VerifySubsequentLightBlocks { committed_hashes };
     Ok(to_binary(&res)?)
 }
 
+fn query_verify_committed_hashes<S: Storage>(
+    storage: &S,
+    committed_hashes: CommittedHashes,
+) -> Result<QueryResponse, StdError> {
+    ensure_not_frozen(storage)?;
+    let light_client_db = StorageLightClientDB::from_readonly_storage(storage);
+    let mut light_client = LightClient::new(light_client_db);
+    let valid = light_client
+        .verify_committed_hashes(&committed_hashes, COMMIT_SECRET_ROTATION.grace_appends)
+        .is_ok();
+    let recorded = light_client.is_recorded(&committed_hashes.hashes);
+    let res = QueryAnswer::VerifyCommittedHashes { valid, recorded };
+    Ok(to_binary(&res)?)
+}
+
+fn query_verify_state_proof<S: Storage>(
+    storage: &S,
+    proof_ops: ProofOps,
+    headers: Vec<Header>,
+    block_hash_index: u64,
+    store_key: String,
+    key: Binary,
+    value: Option<Binary>,
+) -> Result<QueryResponse, StdError> {
+    ensure_not_frozen(storage)?;
+    let state_proof = StateProof { proof_ops, headers };
+    let chaindb = StorageLightClientDB::from_readonly_storage(storage);
+    let mut light_client = LightClient::new(chaindb);
+    light_client
+        .verify_state_proof(
+            &state_proof,
+            block_hash_index as usize,
+            store_key.as_bytes(),
+            key.as_slice(),
+            value.as_ref().map(|value| value.as_slice()),
+        )
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let res = QueryAnswer::VerifyStateProof {
+        height: state_proof.headers[0].height,
+    };
+    Ok(to_binary(&res)?)
+}
+
 pub fn decrypt_response_from_contract(data: &[u8], encryption_key: &[u8]) -> StdResult<Binary> {
     let mut cipher = Aes128Siv::new(*GenericArray::from_slice(encryption_key));
     let plain_text = cipher
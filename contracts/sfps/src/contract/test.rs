@@ -7,7 +7,8 @@ use sfps_lib::cosmos_proto::prost::Message;
 use sfps_lib::header::hash_header;
 use sfps_lib::subsequent_hashes::HeaderHashWithHeight;
 use sfps_lib::{cosmos_proto::cosmos::base::abci::v1beta1::TxMsgData, subsequent_hashes::Commit};
-use shared_types::sfps::{HandleMsg, InitMsg, LightBlock, QueryAnswer, QueryMsg};
+use shared_types::sfps::{HandleMsg, Header, InitMsg, LightBlock, QueryAnswer, QueryMsg};
+use shared_types::{ContractReference, RelayCallback};
 
 pub struct SFPSRunner {}
 impl ContractRunner for SFPSRunner {
@@ -72,6 +73,23 @@ fn header_hash_with_height_of_light_block(light_block: &LightBlock) -> HeaderHas
     }
 }
 
+// env at the time of the light block.
+fn mock_env_at(light_block: &LightBlock) -> Env {
+    let mut env = mock_env("commtier", &[]);
+    env.block.time = light_block
+        .signed_header
+        .as_ref()
+        .unwrap()
+        .header
+        .as_ref()
+        .unwrap()
+        .time
+        .as_ref()
+        .unwrap()
+        .seconds as u64;
+    env
+}
+
 #[test]
 fn test_verify_subsequent_light_blocks() {
     let init_msg_json = r#"
@@ -81,7 +99,10 @@ fn test_verify_subsequent_light_blocks() {
                 "state_proxy": {
                     "address":"state_proxy_address",
                     "hash": "state_proxy_hash"
-                }
+                },
+                "owner": "owner",
+                "chain_id": "pulsar-2",
+                "max_clock_drift": 10
             },
             "max_interval": 50,
             "initial_header": "CgIICxIIcHVsc2FyLTIYqo3rASILCNzO15YGEMSMzEwqSAog7HH435bYkdOJVKB7OomEJTYjp5uQgjz+phKRlKFs7mESJAgBEiC3K3aPryqmS1ltw39xiAtsPDqbJZdxNsRjpW6o9JJALDIgYzwmHJ4rhhnO8iTHcELLEiaDahrhvtajCXIDirUSBBY6IOOwxEKY/BwUmvv0yJlvuSQnrkHkZJuTTKSVmRt4UrhVQiA3z6HuyyugpHDrYFs6XMLXNrA7vKyN7YqaSOfhRa9pDkogzvkeAn9oilZZOhn02eQoAwZ98jcWJTOYZqfZgxCuPEdSIASAkbx93Cg/d7+/kdc8RNpYw9+KnLyGdAXYt/ParaIvWiBQ1sDjRRnOBFsG3hGPuqhPgNfYy75AAE1gZfzLz4FQp2IgAuS/S819bJvvdw1WS/jGVLSW35v5yFHe/yq55aoXMRBqIOOwxEKY/BwUmvv0yJlvuSQnrkHkZJuTTKSVmRt4UrhVchQxXwpT9yfpGxzEDdqCMdJ72mIlXA==",
@@ -236,8 +257,9 @@ fn test_verify_subsequent_light_blocks() {
     assert_eq!(err.to_string(), "Generic error: anchor hash not found");
 }
 
-#[test]
-fn test_append_subsequent_hashes() {
+// initializes the client with the anchor header of pulsar-2 at index 0,
+// and returns it with the following light blocks of heights [3851968, 3851990, 3852012, 3852034, 3852056].
+fn init_with_light_blocks() -> (Context, Header, Vec<LightBlock>) {
     let init_msg_json = r#"
         {   
             "seed": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
@@ -245,7 +267,10 @@ fn test_append_subsequent_hashes() {
                 "state_proxy": {
                     "address":"state_proxy_address",
                     "hash": "state_proxy_hash"
-                }
+                },
+                "owner": "owner",
+                "chain_id": "pulsar-2",
+                "max_clock_drift": 10
             },
             "max_interval": 50,
             "initial_header": "CgIICxIIcHVsc2FyLTIYqo3rASILCNzO15YGEMSMzEwqSAog7HH435bYkdOJVKB7OomEJTYjp5uQgjz+phKRlKFs7mESJAgBEiC3K3aPryqmS1ltw39xiAtsPDqbJZdxNsRjpW6o9JJALDIgYzwmHJ4rhhnO8iTHcELLEiaDahrhvtajCXIDirUSBBY6IOOwxEKY/BwUmvv0yJlvuSQnrkHkZJuTTKSVmRt4UrhVQiA3z6HuyyugpHDrYFs6XMLXNrA7vKyN7YqaSOfhRa9pDkogzvkeAn9oilZZOhn02eQoAwZ98jcWJTOYZqfZgxCuPEdSIASAkbx93Cg/d7+/kdc8RNpYw9+KnLyGdAXYt/ParaIvWiBQ1sDjRRnOBFsG3hGPuqhPgNfYy75AAE1gZfzLz4FQp2IgAuS/S819bJvvdw1WS/jGVLSW35v5yFHe/yq55aoXMRBqIOOwxEKY/BwUmvv0yJlvuSQnrkHkZJuTTKSVmRt4UrhVchQxXwpT9yfpGxzEDdqCMdJ72mIlXA==",
@@ -280,6 +305,12 @@ fn test_append_subsequent_hashes() {
         } => (anchor_header, following_light_blocks),
         _ => unreachable!(),
     };
+    (context, anchor_header, following_light_blocks)
+}
+
+#[test]
+fn test_append_subsequent_hashes() {
+    let (mut context, anchor_header, following_light_blocks) = init_with_light_blocks();
     let query_msg_1 = QueryMsg::VerifySubsequentLightBlocks {
         anchor_header: anchor_header.clone(),
         anchor_header_index: 0,
@@ -307,29 +338,68 @@ fn test_append_subsequent_hashes() {
             _ => unreachable!(),
         };
 
+    // the headers are from future at the block time before them.
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        mock_env_at(&following_light_blocks[0]),
+        HandleMsg::AppendSubsequentHashes {
+            committed_hashes: committed_hashes_2.clone(),
+            relay_callback: None,
+        },
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("header from future"));
+
+    // the relay callback is honoured only if it is requested by the callback contract.
+    let relay_callback = RelayCallback {
+        contract: ContractReference {
+            address: "shuriken".into(),
+            hash: "shuriken_hash".into(),
+        },
+        relayer: "commtier".into(),
+    };
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        mock_env_at(&following_light_blocks[4]),
+        HandleMsg::AppendSubsequentHashes {
+            committed_hashes: committed_hashes_1.clone(),
+            relay_callback: Some(relay_callback.clone()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("relay callback is not requested by the callback contract")
+    );
+
+    let mut env = mock_env_at(&following_light_blocks[4]);
+    env.message.sender = "shuriken".into();
     SFPSRunner::run_handle(
         &mut context,
-        mock_env("commtier", &[]),
+        env,
         HandleMsg::AppendSubsequentHashes {
             committed_hashes: committed_hashes_1,
+            relay_callback: Some(relay_callback),
         },
     )
     .unwrap();
 
     SFPSRunner::run_handle(
         &mut context,
-        mock_env("commtier", &[]),
+        mock_env_at(&following_light_blocks[4]),
         HandleMsg::AppendSubsequentHashes {
             committed_hashes: committed_hashes_2.clone(),
+            relay_callback: None,
         },
     )
     .unwrap();
 
     SFPSRunner::run_handle(
         &mut context,
-        mock_env("commtier", &[]),
+        mock_env_at(&following_light_blocks[4]),
         HandleMsg::AppendSubsequentHashes {
             committed_hashes: committed_hashes_2.clone(),
+            relay_callback: None,
         },
     )
     .unwrap();
@@ -338,9 +408,10 @@ fn test_append_subsequent_hashes() {
         Commit::new(&invalid_commit_hashes.hashes, b"invalid_secret").unwrap();
     let err = SFPSRunner::run_handle(
         &mut context,
-        mock_env("commtier", &[]),
+        mock_env_at(&following_light_blocks[4]),
         HandleMsg::AppendSubsequentHashes {
             committed_hashes: invalid_commit_hashes.clone(),
+            relay_callback: None,
         },
     )
     .unwrap_err();
@@ -348,6 +419,78 @@ fn test_append_subsequent_hashes() {
         err,
         StdError::generic_err("subsequent hashes error: invalid commit")
     );
+    // the commit over the recorded hashes is harmless even if it is not valid.
+    let mut unrecorded_hashes = invalid_commit_hashes.clone();
+    unrecorded_hashes.hashes.following_hashes[0].hash = vec![0; 32];
+    for (committed_hashes, valid, recorded) in [
+        (committed_hashes_2.clone(), true, true),
+        (invalid_commit_hashes, false, true),
+        (unrecorded_hashes, false, false),
+    ] {
+        assert_eq!(
+            from_binary::<QueryAnswer>(
+                &SFPSRunner::run_query(
+                    &mut context,
+                    QueryMsg::VerifyCommittedHashes { committed_hashes },
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            QueryAnswer::VerifyCommittedHashes { valid, recorded }
+        );
+    }
+
+    // only the owner rotates the commit secret.
+    let rotate_commit_secret_msg = HandleMsg::RotateCommitSecret {
+        entropy: Binary::from(b"entropy".to_vec()),
+    };
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        mock_env("commtier", &[]),
+        rotate_commit_secret_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+    SFPSRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        rotate_commit_secret_msg.clone(),
+    )
+    .unwrap();
+    // commits under the previous secret still verify within the grace window,
+    // and are told apart from the invalid ones by the recorded hashes after the secret is retired.
+    let verify_committed_hashes_2 = |context: &mut Context| {
+        from_binary::<QueryAnswer>(
+            &SFPSRunner::run_query(
+                context,
+                QueryMsg::VerifyCommittedHashes {
+                    committed_hashes: committed_hashes_2.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        verify_committed_hashes_2(&mut context),
+        QueryAnswer::VerifyCommittedHashes {
+            valid: true,
+            recorded: true
+        }
+    );
+    SFPSRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        rotate_commit_secret_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        verify_committed_hashes_2(&mut context),
+        QueryAnswer::VerifyCommittedHashes {
+            valid: false,
+            recorded: true
+        }
+    );
 
     let query_msg_3 = QueryMsg::VerifySubsequentLightBlocks {
         anchor_header: following_light_blocks[0]
@@ -395,17 +538,19 @@ fn test_append_subsequent_hashes() {
         };
     SFPSRunner::run_handle(
         &mut context,
-        mock_env("commtier", &[]),
+        mock_env_at(&following_light_blocks[4]),
         HandleMsg::AppendSubsequentHashes {
             committed_hashes: committed_hashes_3.clone(),
+            relay_callback: None,
         },
     )
     .unwrap();
     SFPSRunner::run_handle(
         &mut context,
-        mock_env("commtier", &[]),
+        mock_env_at(&following_light_blocks[4]),
         HandleMsg::AppendSubsequentHashes {
             committed_hashes: committed_hashes_4.clone(),
+            relay_callback: None,
         },
     )
     .unwrap();
@@ -518,3 +663,295 @@ fn test_append_subsequent_hashes() {
         _ => unreachable!(),
     };
 }
+
+fn query_frozen_height(context: &mut Context) -> Option<i64> {
+    match from_binary(&SFPSRunner::run_query(context, QueryMsg::FrozenHeight {}).unwrap()).unwrap()
+    {
+        QueryAnswer::FrozenHeight { height } => height,
+        _ => unreachable!(),
+    }
+}
+
+// light block conflicting with the given one at the same height.
+// the mock api accepts any signature, so the commit only has to refer to the modified header.
+fn conflicting_light_block(light_block: &LightBlock) -> LightBlock {
+    let mut conflicting = light_block.clone();
+    let signed_header = conflicting.signed_header.as_mut().unwrap();
+    let header = signed_header.header.as_mut().unwrap();
+    header.app_hash = [0; 32].to_vec();
+    let hash = hash_header(header);
+    signed_header
+        .commit
+        .as_mut()
+        .unwrap()
+        .block_id
+        .as_mut()
+        .unwrap()
+        .hash = hash;
+    conflicting
+}
+
+#[test]
+fn test_submit_misbehaviour_and_recover_client() {
+    let (mut context, anchor_header, following_light_blocks) = init_with_light_blocks();
+    let light_block_header = |index: usize| {
+        following_light_blocks[index]
+            .signed_header
+            .clone()
+            .unwrap()
+            .header
+            .unwrap()
+    };
+    let env = mock_env_at(&following_light_blocks[4]);
+    // the validator set changes at every height, so the misbehaviour is anchored at the appended light block,
+    // whose validator set is given in it.
+    let committed_hashes = match from_binary(
+        &SFPSRunner::run_query(
+            &mut context,
+            QueryMsg::VerifySubsequentLightBlocks {
+                anchor_header,
+                anchor_header_index: 0,
+                following_light_blocks: following_light_blocks[..1].to_vec(),
+                commit_flags: vec![true],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+    {
+        QueryAnswer::VerifySubsequentLightBlocks { committed_hashes } => committed_hashes,
+        _ => unreachable!(),
+    };
+    SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        HandleMsg::AppendSubsequentHashes {
+            committed_hashes,
+            relay_callback: None,
+        },
+    )
+    .unwrap();
+    let verify_subsequent_light_blocks_msg = QueryMsg::VerifySubsequentLightBlocks {
+        anchor_header: light_block_header(0),
+        anchor_header_index: 1,
+        following_light_blocks: following_light_blocks[1..3].to_vec(),
+        commit_flags: vec![true, true],
+    };
+    let committed_hashes = match from_binary(
+        &SFPSRunner::run_query(&mut context, verify_subsequent_light_blocks_msg.clone()).unwrap(),
+    )
+    .unwrap()
+    {
+        QueryAnswer::VerifySubsequentLightBlocks { committed_hashes } => committed_hashes,
+        _ => unreachable!(),
+    };
+    let submit_misbehaviour_msg = |light_block_2: LightBlock| HandleMsg::SubmitMisbehaviour {
+        anchor_header: light_block_header(0),
+        anchor_header_index: 1,
+        anchor_validator_set: following_light_blocks[0].validator_set.clone().unwrap(),
+        light_block_1: following_light_blocks[1].clone(),
+        light_block_2,
+    };
+
+    // CASE: the same light blocks are not the misbehaviour
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        submit_misbehaviour_msg(following_light_blocks[1].clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("no conflicting headers"));
+
+    // CASE: the light blocks at the different heights are not the misbehaviour
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        submit_misbehaviour_msg(following_light_blocks[2].clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("unmatched misbehaviour height: 3851990, 3852012")
+    );
+    assert_eq!(query_frozen_height(&mut context), None);
+
+    // CASE: anyone freezes the client with the conflicting light blocks
+    let conflicting = conflicting_light_block(&following_light_blocks[1]);
+    SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        submit_misbehaviour_msg(conflicting.clone()),
+    )
+    .unwrap();
+    assert_eq!(query_frozen_height(&mut context), Some(3851990));
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        submit_misbehaviour_msg(conflicting),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("client is already frozen at height 3851990")
+    );
+
+    // the frozen client neither verifies nor appends.
+    let frozen_err = StdError::generic_err("client is frozen by misbehaviour at height 3851990");
+    assert_eq!(
+        SFPSRunner::run_query(&mut context, verify_subsequent_light_blocks_msg).unwrap_err(),
+        frozen_err
+    );
+    assert_eq!(
+        SFPSRunner::run_query(
+            &mut context,
+            QueryMsg::VerifyCommittedHashes {
+                committed_hashes: committed_hashes.clone(),
+            },
+        )
+        .unwrap_err(),
+        frozen_err
+    );
+    let append_subsequent_hashes_msg = HandleMsg::AppendSubsequentHashes {
+        committed_hashes,
+        relay_callback: None,
+    };
+    assert_eq!(
+        SFPSRunner::run_handle(
+            &mut context,
+            env.clone(),
+            append_subsequent_hashes_msg.clone()
+        )
+        .unwrap_err(),
+        frozen_err
+    );
+
+    // CASE: only the owner recovers the client
+    let trusted_header = light_block_header(4);
+    let recover_client_msg = HandleMsg::RecoverClient {
+        trusted_header: Some(trusted_header.clone()),
+    };
+    let err =
+        SFPSRunner::run_handle(&mut context, env.clone(), recover_client_msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+    SFPSRunner::run_handle(&mut context, mock_env("owner", &[]), recover_client_msg).unwrap();
+    assert_eq!(query_frozen_height(&mut context), None);
+    match from_binary(&SFPSRunner::run_query(&mut context, QueryMsg::HashListLength {}).unwrap())
+        .unwrap()
+    {
+        QueryAnswer::HashListLength { length } => assert_eq!(length, 3),
+        _ => unreachable!(),
+    };
+    match from_binary(
+        &SFPSRunner::run_query(&mut context, QueryMsg::HashByIndex { index: 2 }).unwrap(),
+    )
+    .unwrap()
+    {
+        QueryAnswer::HashByIndex { hash, height } => {
+            assert_eq!(hash.as_slice(), hash_header(&trusted_header).as_slice());
+            assert_eq!(height, 3852056);
+        }
+        _ => unreachable!(),
+    };
+
+    // the recovered client appends again, but the hashes below the trusted header are not appended.
+    SFPSRunner::run_handle(&mut context, env, append_subsequent_hashes_msg).unwrap();
+    match from_binary(&SFPSRunner::run_query(&mut context, QueryMsg::HashListLength {}).unwrap())
+        .unwrap()
+    {
+        QueryAnswer::HashListLength { length } => assert_eq!(length, 3),
+        _ => unreachable!(),
+    };
+
+    // CASE: the client neither frozen nor expired is not recovered
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        HandleMsg::RecoverClient {
+            trusted_header: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("client is neither frozen nor expired")
+    );
+}
+
+#[test]
+fn test_recover_expired_client() {
+    let (mut context, anchor_header, following_light_blocks) = init_with_light_blocks();
+    let trusting_period = 14 * 24 * 60 * 60;
+    let mut env = mock_env("owner", &[]);
+    env.block.time = anchor_header.time.as_ref().unwrap().seconds as u64 + trusting_period - 1;
+    let trusted_header = following_light_blocks[4]
+        .signed_header
+        .clone()
+        .unwrap()
+        .header
+        .unwrap();
+
+    // CASE: the anchor header is still within the trusting period
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        HandleMsg::RecoverClient {
+            trusted_header: Some(trusted_header.clone()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("client is neither frozen nor expired")
+    );
+
+    // CASE: the expired client needs the trusted header
+    env.block.time += 1;
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        env.clone(),
+        HandleMsg::RecoverClient {
+            trusted_header: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("trusted header is required to recover the expired client")
+    );
+
+    // CASE: only the owner recovers the expired client
+    let recover_client_msg = HandleMsg::RecoverClient {
+        trusted_header: Some(trusted_header.clone()),
+    };
+    let mut stranger_env = env.clone();
+    stranger_env.message.sender = "commtier".into();
+    let err =
+        SFPSRunner::run_handle(&mut context, stranger_env, recover_client_msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+    SFPSRunner::run_handle(&mut context, env.clone(), recover_client_msg).unwrap();
+    match from_binary(
+        &SFPSRunner::run_query(&mut context, QueryMsg::HashByIndex { index: 1 }).unwrap(),
+    )
+    .unwrap()
+    {
+        QueryAnswer::HashByIndex { hash, height } => {
+            assert_eq!(hash.as_slice(), hash_header(&trusted_header).as_slice());
+            assert_eq!(height, 3852056);
+        }
+        _ => unreachable!(),
+    };
+
+    // the recovered client is within the trusting period from the trusted header.
+    let err = SFPSRunner::run_handle(
+        &mut context,
+        env,
+        HandleMsg::RecoverClient {
+            trusted_header: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("client is neither frozen nor expired")
+    );
+}
@@ -1,17 +1,109 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{from_slice, to_vec, CanonicalAddr, StdError, StdResult};
 use cosmwasm_std::{ReadonlyStorage, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use rand::RngCore;
 use secret_toolkit::storage::AppendStore;
 use sfps_lib::light_client::{LightClientDB, ReadonlyLightClientDB};
 use sfps_lib::subsequent_hashes::HeaderHashWithHeight;
+use shared_types::prng::update_prng;
+use shared_types::sfps::ChainVersion;
 use std::collections::HashMap;
 use std::convert::TryInto;
 
 pub const PREFIX_CHAIN_DB: &[u8] = b"light_client_db";
 pub const PREFIX_PRNG: &[u8] = b"prng";
 pub const PREFIX_BLOCK_HASH: &[u8] = b"block_hash";
+pub const PREFIX_HEIGHT_INDEX: &[u8] = b"height_index";
 pub const MAX_INTERVAL_KEY: &[u8] = b"max_interval";
 pub const COMMIT_SECRET_KEY: &[u8] = b"commit_secret";
+pub const PREVIOUS_COMMIT_SECRET_KEY: &[u8] = b"previous_commit_secret";
+pub const APPENDS_SINCE_ROTATION_KEY: &[u8] = b"appends_since_rotation";
+pub const CHAIN_VERSION_KEY: &[u8] = b"chain_version";
+pub const CHAIN_ID_KEY: &[u8] = b"chain_id";
+pub const MAX_CLOCK_DRIFT_KEY: &[u8] = b"max_clock_drift";
+pub const OWNER_KEY: &[u8] = b"owner";
+pub const FROZEN_HEIGHT_KEY: &[u8] = b"frozen_height";
+pub const HIGHEST_HASH_TIME_KEY: &[u8] = b"highest_hash_time";
+
+pub fn write_chain_version<S: Storage>(
+    storage: &mut S,
+    chain_version: ChainVersion,
+) -> StdResult<()> {
+    storage.set(CHAIN_VERSION_KEY, &to_vec(&chain_version)?);
+    Ok(())
+}
+
+pub fn read_chain_version<S: ReadonlyStorage>(storage: &S) -> StdResult<ChainVersion> {
+    match storage.get(CHAIN_VERSION_KEY) {
+        Some(bytes) => from_slice(&bytes),
+        None => Ok(ChainVersion::default()),
+    }
+}
+
+pub fn write_owner<S: Storage>(storage: &mut S, owner: &CanonicalAddr) {
+    storage.set(OWNER_KEY, owner.as_slice());
+}
+
+pub fn read_owner<S: ReadonlyStorage>(storage: &S) -> StdResult<CanonicalAddr> {
+    storage
+        .get(OWNER_KEY)
+        .map(CanonicalAddr::from)
+        .ok_or_else(|| StdError::not_found("owner"))
+}
+
+/// stores the height of the conflicting light blocks, or None when the client is recovered.
+pub fn write_frozen_height<S: Storage>(storage: &mut S, height: Option<i64>) -> StdResult<()> {
+    match height {
+        Some(height) => storage.set(FROZEN_HEIGHT_KEY, &to_vec(&height)?),
+        None => storage.remove(FROZEN_HEIGHT_KEY),
+    }
+    Ok(())
+}
+
+pub fn read_frozen_height<S: ReadonlyStorage>(storage: &S) -> StdResult<Option<i64>> {
+    storage
+        .get(FROZEN_HEIGHT_KEY)
+        .map(|bytes| from_slice(&bytes))
+        .transpose()
+}
+
+/// fails while the client is frozen by misbehaviour.
+pub fn ensure_not_frozen<S: ReadonlyStorage>(storage: &S) -> StdResult<()> {
+    match read_frozen_height(storage)? {
+        Some(height) => Err(StdError::generic_err(format!(
+            "client is frozen by misbehaviour at height {}",
+            height
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// stores the upper bound of the time (unix time in seconds) of the highest hash.
+/// the time of the appended headers is not committed, so it is bounded by the block time of the append and the max clock drift.
+pub fn write_highest_hash_time<S: Storage>(storage: &mut S, time: i64) -> StdResult<()> {
+    storage.set(HIGHEST_HASH_TIME_KEY, &to_vec(&time)?);
+    Ok(())
+}
+
+/// None for the client whose highest hash was stored before the time is tracked.
+pub fn read_highest_hash_time<S: ReadonlyStorage>(storage: &S) -> StdResult<Option<i64>> {
+    storage
+        .get(HIGHEST_HASH_TIME_KEY)
+        .map(|bytes| from_slice(&bytes))
+        .transpose()
+}
+
+/// generates a commit secret from the prng updated with the sender and the entropy.
+pub fn gen_commit_secret<S: Storage>(
+    storage: &mut S,
+    sender: &CanonicalAddr,
+    entropy: &[u8],
+) -> StdResult<Vec<u8>> {
+    let mut rng = update_prng(storage, PREFIX_PRNG, sender, entropy)?;
+    let mut secret = vec![0; 32];
+    rng.fill_bytes(&mut secret);
+    Ok(secret)
+}
 
 pub struct StorageLightClientDB<S: ReadonlyStorage> {
     storage: S,
@@ -31,6 +123,30 @@ impl<S: ReadonlyStorage> StorageLightClientDB<S> {
             max_interval_cache: None,
         }
     }
+
+    /// returns the smallest index of the hash whose height is at or above `height`.
+    /// the hashes appended before the height index map was introduced are found by binary search,
+    /// as the heights of the hash list are increasing.
+    pub fn get_index_for_height(&mut self, height: i64) -> Option<usize> {
+        let height_index = ReadonlyPrefixedStorage::new(PREFIX_HEIGHT_INDEX, &self.storage);
+        if let Some(bytes) = height_index.get(&height.to_be_bytes()) {
+            return Some(u32::from_be_bytes(bytes.try_into().unwrap()) as usize);
+        }
+        let length = self.get_hash_list_length();
+        if length == 0 || self.get_highest_hash()?.height < height {
+            return None;
+        }
+        let (mut low, mut high) = (0, length - 1);
+        while low < high {
+            let middle = (low + high) / 2;
+            if self.get_hash_by_index(middle)?.height < height {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        Some(low)
+    }
 }
 
 impl<'a, S: ReadonlyStorage> StorageLightClientDB<ReadonlyPrefixedStorage<'a, S>> {
@@ -105,6 +221,27 @@ impl<S: ReadonlyStorage> ReadonlyLightClientDB for StorageLightClientDB<S> {
     fn get_commit_secret(&mut self) -> Vec<u8> {
         self.storage.get(COMMIT_SECRET_KEY).unwrap_or_default()
     }
+    fn get_previous_commit_secret(&mut self) -> Option<Vec<u8>> {
+        self.storage.get(PREVIOUS_COMMIT_SECRET_KEY)
+    }
+    fn get_appends_since_rotation(&mut self) -> u64 {
+        self.storage
+            .get(APPENDS_SINCE_ROTATION_KEY)
+            .map(|bytes| u64::from_ne_bytes(bytes.try_into().unwrap()))
+            .unwrap_or_default()
+    }
+    fn get_chain_id(&mut self) -> String {
+        self.storage
+            .get(CHAIN_ID_KEY)
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+            .unwrap_or_default()
+    }
+    fn get_max_clock_drift(&mut self) -> u64 {
+        self.storage
+            .get(MAX_CLOCK_DRIFT_KEY)
+            .map(|bytes| u64::from_ne_bytes(bytes.try_into().unwrap()))
+            .unwrap_or_default()
+    }
 }
 
 impl<S: Storage> LightClientDB for StorageLightClientDB<S> {
@@ -114,6 +251,11 @@ impl<S: Storage> LightClientDB for StorageLightClientDB<S> {
         self.hash_by_index_cache.insert(index, block_hash.clone());
         self.hash_list_length_cache = Some(index + 1);
         self.highest_hash_cache = Some(block_hash.clone());
+        let mut height_index = PrefixedStorage::new(PREFIX_HEIGHT_INDEX, &mut self.storage);
+        height_index.set(
+            &block_hash.height.to_be_bytes(),
+            &(index as u32).to_be_bytes(),
+        );
         let store = AppendStore::<HeaderHashWithHeight>::new(PREFIX_BLOCK_HASH);
         Ok(store.push(&mut self.storage, &block_hash)?)
     }
@@ -127,12 +269,31 @@ impl<S: Storage> LightClientDB for StorageLightClientDB<S> {
         self.storage.set(COMMIT_SECRET_KEY, secret);
         Ok(())
     }
+    fn store_previous_commit_secret(&mut self, secret: &[u8]) -> Result<(), Self::Error> {
+        self.storage.set(PREVIOUS_COMMIT_SECRET_KEY, secret);
+        Ok(())
+    }
+    fn store_appends_since_rotation(&mut self, appends: u64) -> Result<(), Self::Error> {
+        self.storage
+            .set(APPENDS_SINCE_ROTATION_KEY, &appends.to_ne_bytes());
+        Ok(())
+    }
+    fn store_chain_id(&mut self, chain_id: &str) -> Result<(), Self::Error> {
+        self.storage.set(CHAIN_ID_KEY, chain_id.as_bytes());
+        Ok(())
+    }
+    fn store_max_clock_drift(&mut self, max_clock_drift: u64) -> Result<(), Self::Error> {
+        self.storage
+            .set(MAX_CLOCK_DRIFT_KEY, &max_clock_drift.to_ne_bytes());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use cosmwasm_std::testing::*;
+    use shared_types::prng::init_prng;
 
     #[test]
     fn test_store_block_hash() {
@@ -151,4 +312,83 @@ mod test {
         assert_eq!(db.get_hash_by_index(0).unwrap(), header_hash_with_height);
         assert_eq!(db.get_hash_list_length(), 1);
     }
+
+    #[test]
+    fn test_get_index_for_height() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageLightClientDB::from_storage(&mut storage);
+        assert_eq!(db.get_index_for_height(1), None);
+        for height in [10, 20, 30] {
+            db.append_block_hash(HeaderHashWithHeight {
+                hash: vec![0; 32],
+                height,
+            })
+            .unwrap();
+        }
+        let mut db = StorageLightClientDB::from_readonly_storage(&storage);
+        assert_eq!(db.get_index_for_height(1), Some(0));
+        assert_eq!(db.get_index_for_height(10), Some(0));
+        assert_eq!(db.get_index_for_height(11), Some(1));
+        assert_eq!(db.get_index_for_height(20), Some(1));
+        assert_eq!(db.get_index_for_height(29), Some(2));
+        assert_eq!(db.get_index_for_height(30), Some(2));
+        assert_eq!(db.get_index_for_height(31), None);
+    }
+
+    #[test]
+    fn test_chain_version() {
+        let mut storage = MockStorage::new();
+        assert_eq!(
+            read_chain_version(&storage).unwrap(),
+            ChainVersion::Tendermint034
+        );
+        write_chain_version(&mut storage, ChainVersion::CometBft038).unwrap();
+        assert_eq!(
+            read_chain_version(&storage).unwrap(),
+            ChainVersion::CometBft038
+        );
+    }
+
+    #[test]
+    fn test_frozen_height() {
+        let mut storage = MockStorage::new();
+        assert_eq!(read_frozen_height(&storage).unwrap(), None);
+        ensure_not_frozen(&storage).unwrap();
+        write_frozen_height(&mut storage, Some(100)).unwrap();
+        assert_eq!(read_frozen_height(&storage).unwrap(), Some(100));
+        assert_eq!(
+            ensure_not_frozen(&storage).unwrap_err(),
+            StdError::generic_err("client is frozen by misbehaviour at height 100")
+        );
+        write_frozen_height(&mut storage, None).unwrap();
+        assert_eq!(read_frozen_height(&storage).unwrap(), None);
+    }
+
+    #[test]
+    fn test_gen_commit_secret() {
+        let mut storage = MockStorage::new();
+        let sender = CanonicalAddr::from(vec![1; 20].as_slice());
+        assert!(gen_commit_secret(&mut storage, &sender, b"entropy").is_err());
+        init_prng(&mut storage, PREFIX_PRNG, &mock_env("sender", &[]), b"seed").unwrap();
+        let secret = gen_commit_secret(&mut storage, &sender, b"entropy").unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_ne!(
+            gen_commit_secret(&mut storage, &sender, b"entropy").unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn test_commit_secret_rotation() {
+        let mut storage = MockStorage::new();
+        let mut db = StorageLightClientDB::from_storage(&mut storage);
+        db.store_commit_secret(b"secret").unwrap();
+        assert_eq!(db.get_previous_commit_secret(), None);
+        assert_eq!(db.get_appends_since_rotation(), 0);
+        db.store_appends_since_rotation(3).unwrap();
+        db.rotate_commit_secret(b"next secret").unwrap();
+        assert_eq!(db.get_commit_secret(), b"next secret".to_vec());
+        assert_eq!(db.get_previous_commit_secret(), Some(b"secret".to_vec()));
+        assert_eq!(db.get_appends_since_rotation(), 0);
+    }
 }
@@ -1,19 +1,27 @@
-use crate::state::{read_config, write_config};
+use crate::state::{
+    credit_reward, read_config, read_relayer, read_relayer_addresses, read_relayer_config,
+    read_reward, read_reward_config, write_config, write_relayer, write_relayer_config,
+    write_reward, write_reward_config, Relayer,
+};
 use cosmwasm_std::{
-    to_binary, Api, Env, Extern, HandleResponse, InitResponse, InitResult, Querier, QueryResult,
-    StdError, StdResult, Storage,
+    to_binary, Api, CosmosMsg, Env, Extern, HandleResponse, HumanAddr, InitResponse, InitResult,
+    Querier, QueryResult, StdError, StdResult, Storage, Uint128,
 };
+use secret_toolkit::snip20;
 use secret_toolkit::utils::calls::HandleCallback;
 use secret_toolkit::utils::pad_handle_result;
-use shared_types::shuriken::{HandleMsg, InitMsg, QueryAnswer, QueryMsg};
+use shared_types::shuriken::{
+    Config, HandleMsg, InitMsg, QueryAnswer, QueryMsg, ReceiveMsg, RelayerInfo, RelayerStatus,
+    Snip20ReceiveMsg,
+};
 use shared_types::state_proxy::client::{Secp256k1ApiSigner, StateProxyDeps};
-use shared_types::{bitcoin_spv, sfps, BLOCK_SIZE};
+use shared_types::{bitcoin_spv, sfps, ContractReference, RelayCallback, BLOCK_SIZE};
 
 const CONTRACT_LABEL: &[u8] = b"shuriken";
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> InitResult {
     let mut deps = StateProxyDeps::init(
@@ -25,9 +33,21 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         msg.config.state_proxy.clone(),
         &Secp256k1ApiSigner::new(&deps.api),
     )?;
+    // the bond is deposited by `Send` of the reward token.
+    let register_receive = snip20::register_receive_msg(
+        env.contract_code_hash,
+        None,
+        BLOCK_SIZE,
+        msg.config.reward_token.hash.clone(),
+        msg.config.reward_token.address.clone(),
+    )?;
     write_config(&mut deps.storage, msg.config, &deps.api)?;
+    write_reward_config(&mut deps.storage, &msg.reward_config)?;
+    write_relayer_config(&mut deps.storage, &msg.relayer_config)?;
     Ok(InitResponse {
-        messages: deps.storage.cosmos_msgs()?,
+        messages: deps
+            .storage
+            .add_messages_to_state_proxy_msg(vec![register_receive])?,
         log: vec![],
     })
 }
@@ -45,14 +65,24 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         &Secp256k1ApiSigner::new(&deps.api),
     )?;
     let config = read_config(&deps.storage, &deps.api)?;
+    // relayed messages request the target contracts to report the accepted submissions back.
+    let relay_callback = RelayCallback {
+        contract: ContractReference {
+            address: env.contract.address.clone(),
+            hash: env.contract_code_hash.clone(),
+        },
+        relayer: env.message.sender.clone(),
+    };
     let messages = match msg {
         HandleMsg::BitcoinSPVAddHeaders {
             tip_height,
             headers,
         } => {
+            check_relayer(&deps, &env.message.sender)?;
             vec![bitcoin_spv::HandleMsg::AddHeaders {
                 tip_height,
                 headers,
+                relay_callback: Some(relay_callback),
             }
             .to_cosmos_msg(
                 config.bitcoin_spv.hash,
@@ -61,13 +91,74 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             )?]
         }
         HandleMsg::SFPSProxyAppendSubsequentHashes { committed_hashes } => {
-            vec![
-                sfps::HandleMsg::AppendSubsequentHashes { committed_hashes }.to_cosmos_msg(
-                    config.sfps.hash,
-                    config.sfps.address,
-                    None,
-                )?,
-            ]
+            check_relayer(&deps, &env.message.sender)?;
+            vec![sfps::HandleMsg::AppendSubsequentHashes {
+                committed_hashes,
+                relay_callback: Some(relay_callback),
+            }
+            .to_cosmos_msg(config.sfps.hash, config.sfps.address, None)?]
+        }
+        HandleMsg::HeadersAccepted { relayer, count } => {
+            if env.message.sender != config.bitcoin_spv.address {
+                return Err(StdError::unauthorized());
+            }
+            check_relayer(&deps, &relayer)?;
+            let relayer = deps.api.canonical_address(&relayer)?;
+            credit_reward(&mut deps.storage, &relayer, count, |reward_config| {
+                reward_config.header_reward
+            })?;
+            vec![]
+        }
+        HandleMsg::CommittedHashesAccepted { relayer, count } => {
+            if env.message.sender != config.sfps.address {
+                return Err(StdError::unauthorized());
+            }
+            check_relayer(&deps, &relayer)?;
+            let relayer = deps.api.canonical_address(&relayer)?;
+            credit_reward(&mut deps.storage, &relayer, count, |reward_config| {
+                reward_config.committed_hash_reward
+            })?;
+            vec![]
+        }
+        HandleMsg::ClaimReward {} => try_claim_reward(&mut deps, env.message.sender, config)?,
+        HandleMsg::SetRewardConfig { reward_config } => {
+            if env.message.sender != config.owner {
+                return Err(StdError::unauthorized());
+            }
+            write_reward_config(&mut deps.storage, &reward_config)?;
+            vec![]
+        }
+        HandleMsg::Receive(receive) => {
+            if env.message.sender != config.reward_token.address {
+                return Err(StdError::unauthorized());
+            }
+            try_receive(&mut deps, receive)?
+        }
+        HandleMsg::Unbond {} => try_unbond(&mut deps, env.message.sender, config)?,
+        HandleMsg::AllowRelayer { relayer } => {
+            if env.message.sender != config.owner {
+                return Err(StdError::unauthorized());
+            }
+            try_allow_relayer(&mut deps, relayer)?
+        }
+        HandleMsg::RemoveRelayer { relayer } => {
+            if env.message.sender != config.owner {
+                return Err(StdError::unauthorized());
+            }
+            try_remove_relayer(&mut deps, relayer)?
+        }
+        HandleMsg::SetRelayerConfig { relayer_config } => {
+            if env.message.sender != config.owner {
+                return Err(StdError::unauthorized());
+            }
+            write_relayer_config(&mut deps.storage, &relayer_config)?;
+            vec![]
+        }
+        HandleMsg::Slash { relayer } => {
+            if env.message.sender != config.owner {
+                return Err(StdError::unauthorized());
+            }
+            try_slash(&mut deps, relayer)?
         }
     };
 
@@ -82,6 +173,156 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     )
 }
 
+fn try_claim_reward<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    sender: HumanAddr,
+    config: Config,
+) -> StdResult<Vec<CosmosMsg>> {
+    let relayer = deps.api.canonical_address(&sender)?;
+    let amount = read_reward(&deps.storage, &relayer)?;
+    if amount.is_zero() {
+        return Err(StdError::generic_err("no reward"));
+    }
+    write_reward(&mut deps.storage, &relayer, Uint128::zero())?;
+    Ok(vec![snip20::transfer_msg(
+        sender,
+        amount,
+        None,
+        None,
+        BLOCK_SIZE,
+        config.reward_token.hash,
+        config.reward_token.address,
+    )?])
+}
+
+/// checks that the sender can relay the submissions.
+fn check_relayer<A: Api, Q: Querier>(
+    deps: &StateProxyDeps<A, Q>,
+    sender: &HumanAddr,
+) -> StdResult<()> {
+    if !read_relayer_config(&deps.storage)?.registration_required {
+        return Ok(());
+    }
+    let relayer = deps.api.canonical_address(sender)?;
+    match read_relayer(&deps.storage, &relayer)? {
+        Some(Relayer {
+            status: RelayerStatus::Allowed,
+            ..
+        }) => Ok(()),
+        _ => Err(StdError::generic_err("not allowed relayer")),
+    }
+}
+
+fn try_receive<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    receive: Snip20ReceiveMsg,
+) -> StdResult<Vec<CosmosMsg>> {
+    let receive_msg: ReceiveMsg = cosmwasm_std::from_binary(
+        receive
+            .msg
+            .as_ref()
+            .ok_or_else(|| StdError::generic_err("no receive msg"))?,
+    )?;
+    match receive_msg {
+        ReceiveMsg::Bond {} => {
+            let relayer = deps.api.canonical_address(&receive.from)?;
+            let mut value = read_relayer(&deps.storage, &relayer)?.unwrap_or(Relayer {
+                bond: Uint128::zero(),
+                status: RelayerStatus::Pending,
+            });
+            match value.status {
+                RelayerStatus::Slashed => return Err(StdError::generic_err("slashed relayer")),
+                RelayerStatus::Removed => value.status = RelayerStatus::Pending,
+                RelayerStatus::Pending | RelayerStatus::Allowed => {}
+            }
+            value.bond += receive.amount;
+            write_relayer(&mut deps.storage, &relayer, &value)?;
+        }
+    }
+    Ok(vec![])
+}
+
+fn try_unbond<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    sender: HumanAddr,
+    config: Config,
+) -> StdResult<Vec<CosmosMsg>> {
+    let relayer = deps.api.canonical_address(&sender)?;
+    let mut value = read_relayer(&deps.storage, &relayer)?
+        .ok_or_else(|| StdError::generic_err("relayer not found"))?;
+    if value.status == RelayerStatus::Allowed {
+        return Err(StdError::generic_err("allowed relayer can not unbond"));
+    }
+    if value.bond.is_zero() {
+        return Err(StdError::generic_err("no bond"));
+    }
+    let amount = value.bond;
+    value.bond = Uint128::zero();
+    write_relayer(&mut deps.storage, &relayer, &value)?;
+    Ok(vec![snip20::transfer_msg(
+        sender,
+        amount,
+        None,
+        None,
+        BLOCK_SIZE,
+        config.reward_token.hash,
+        config.reward_token.address,
+    )?])
+}
+
+fn try_allow_relayer<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    relayer: HumanAddr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let relayer = deps.api.canonical_address(&relayer)?;
+    let mut value = read_relayer(&deps.storage, &relayer)?
+        .ok_or_else(|| StdError::generic_err("relayer not found"))?;
+    if value.status == RelayerStatus::Slashed {
+        return Err(StdError::generic_err("slashed relayer"));
+    }
+    if value.bond < read_relayer_config(&deps.storage)?.bond_amount {
+        return Err(StdError::generic_err("insufficient bond"));
+    }
+    value.status = RelayerStatus::Allowed;
+    write_relayer(&mut deps.storage, &relayer, &value)?;
+    Ok(vec![])
+}
+
+fn try_remove_relayer<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    relayer: HumanAddr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let relayer = deps.api.canonical_address(&relayer)?;
+    let mut value = read_relayer(&deps.storage, &relayer)?
+        .ok_or_else(|| StdError::generic_err("relayer not found"))?;
+    if value.status != RelayerStatus::Allowed {
+        return Err(StdError::generic_err("relayer not allowed"));
+    }
+    value.status = RelayerStatus::Removed;
+    write_relayer(&mut deps.storage, &relayer, &value)?;
+    Ok(vec![])
+}
+
+/// slashes the whole bond of the relayer, and adds it to the reward budget.
+fn try_slash<A: Api, Q: Querier>(
+    deps: &mut StateProxyDeps<A, Q>,
+    relayer: HumanAddr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let relayer = deps.api.canonical_address(&relayer)?;
+    let mut value = read_relayer(&deps.storage, &relayer)?
+        .ok_or_else(|| StdError::generic_err("relayer not found"))?;
+    if value.status == RelayerStatus::Slashed {
+        return Err(StdError::generic_err("already slashed"));
+    }
+    let mut reward_config = read_reward_config(&deps.storage)?;
+    reward_config.budget += value.bond;
+    write_reward_config(&mut deps.storage, &reward_config)?;
+    value.bond = Uint128::zero();
+    value.status = RelayerStatus::Slashed;
+    write_relayer(&mut deps.storage, &relayer, &value)?;
+    Ok(vec![])
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
     let deps = StateProxyDeps::restore(
         &deps.storage,
@@ -95,5 +336,38 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             let config = read_config(&deps.storage, &deps.api)?;
             to_binary(&QueryAnswer::Config(config))
         }
+        QueryMsg::RewardConfig {} => {
+            let reward_config = read_reward_config(&deps.storage)?;
+            to_binary(&QueryAnswer::RewardConfig(reward_config))
+        }
+        QueryMsg::Reward { relayer } => {
+            let relayer = deps.api.canonical_address(&relayer)?;
+            let amount = read_reward(&deps.storage, &relayer)?;
+            to_binary(&QueryAnswer::Reward { amount })
+        }
+        QueryMsg::RelayerConfig {} => {
+            let relayer_config = read_relayer_config(&deps.storage)?;
+            to_binary(&QueryAnswer::RelayerConfig(relayer_config))
+        }
+        QueryMsg::Relayers { page, page_size } => {
+            let relayers = read_relayer_addresses(&deps.storage)?
+                .into_iter()
+                .skip(page as usize * page_size as usize)
+                .take(page_size as usize)
+                .map(|address| {
+                    // relayers in the list are always stored.
+                    let value = read_relayer(&deps.storage, &address)?.unwrap();
+                    Ok(RelayerInfo {
+                        address: deps.api.human_address(&address)?,
+                        bond: value.bond,
+                        status: value.status,
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&QueryAnswer::Relayers(relayers))
+        }
     }
 }
+
+#[cfg(test)]
+mod test;
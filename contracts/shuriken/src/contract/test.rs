@@ -0,0 +1,435 @@
+use super::*;
+use contract_test_utils::context::{
+    Context, STATE_PROXY_CONTRACT_ADDRESS, STATE_PROXY_CONTRACT_HASH,
+};
+use contract_test_utils::contract_runner::ContractRunner;
+use contract_test_utils::mock_env;
+use cosmwasm_std::{from_binary, Binary, HandleResult};
+use shared_types::shuriken::{RelayerConfig, RewardConfig};
+use shared_types::state_proxy::client::Seed;
+
+pub struct ShurikenRunner {}
+impl ContractRunner for ShurikenRunner {
+    type InitMsg = InitMsg;
+
+    type HandleMsg = HandleMsg;
+
+    type QueryMsg = QueryMsg;
+
+    fn init(
+        deps: &mut contract_test_utils::context::ClientDeps,
+        env: Env,
+        msg: Self::InitMsg,
+    ) -> StdResult<InitResponse> {
+        init(deps, env, msg)
+    }
+
+    fn handle(
+        deps: &mut contract_test_utils::context::ClientDeps,
+        env: Env,
+        msg: Self::HandleMsg,
+    ) -> HandleResult {
+        handle(deps, env, msg)
+    }
+
+    fn query(
+        deps: &contract_test_utils::context::ClientDeps,
+        msg: Self::QueryMsg,
+    ) -> StdResult<Binary> {
+        query(deps, msg)
+    }
+}
+
+fn contract_reference(name: &str) -> ContractReference {
+    ContractReference {
+        address: format!("{}_address", name).into(),
+        hash: format!("{}_hash", name),
+    }
+}
+
+fn init_helper(registration_required: bool) -> Context {
+    let mut context = Context::new::<()>(vec![]);
+    let init_msg = InitMsg {
+        seed: Seed::default(),
+        config: Config {
+            bitcoin_spv: contract_reference("bitcoin_spv"),
+            sfps: contract_reference("sfps"),
+            state_proxy: ContractReference {
+                address: STATE_PROXY_CONTRACT_ADDRESS.into(),
+                hash: STATE_PROXY_CONTRACT_HASH.into(),
+            },
+            owner: "owner".into(),
+            reward_token: contract_reference("reward_token"),
+        },
+        reward_config: RewardConfig {
+            header_reward: Uint128(10),
+            committed_hash_reward: Uint128(100),
+            budget: Uint128(1000),
+        },
+        relayer_config: RelayerConfig {
+            registration_required,
+            bond_amount: Uint128(500),
+        },
+    };
+    ShurikenRunner::run_init(&mut context, mock_env("initializer", &[]), init_msg).unwrap();
+    context
+}
+
+fn bond(context: &mut Context, relayer: &str, amount: u128) -> StdResult<HandleResponse> {
+    ShurikenRunner::run_handle(
+        context,
+        mock_env("reward_token_address", &[]),
+        HandleMsg::Receive(Snip20ReceiveMsg {
+            sender: relayer.into(),
+            from: relayer.into(),
+            amount: Uint128(amount),
+            memo: None,
+            msg: Some(to_binary(&ReceiveMsg::Bond {}).unwrap()),
+        }),
+    )
+}
+
+fn query_relayers(context: &mut Context) -> Vec<RelayerInfo> {
+    let response = ShurikenRunner::run_query(
+        context,
+        QueryMsg::Relayers {
+            page: 0,
+            page_size: 10,
+        },
+    )
+    .unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::Relayers(relayers) => relayers,
+        _ => unreachable!(),
+    }
+}
+
+fn query_reward(context: &mut Context, relayer: &str) -> Uint128 {
+    let response = ShurikenRunner::run_query(
+        context,
+        QueryMsg::Reward {
+            relayer: relayer.into(),
+        },
+    )
+    .unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::Reward { amount } => amount,
+        _ => unreachable!(),
+    }
+}
+
+fn query_reward_config(context: &mut Context) -> RewardConfig {
+    let response = ShurikenRunner::run_query(context, QueryMsg::RewardConfig {}).unwrap();
+    match from_binary(&response).unwrap() {
+        QueryAnswer::RewardConfig(reward_config) => reward_config,
+        _ => unreachable!(),
+    }
+}
+
+fn headers_accepted(relayer: &str, count: u32) -> HandleMsg {
+    HandleMsg::HeadersAccepted {
+        relayer: relayer.into(),
+        count,
+    }
+}
+
+fn add_headers() -> HandleMsg {
+    HandleMsg::BitcoinSPVAddHeaders {
+        tip_height: 1,
+        headers: vec![Binary::from(vec![0u8; 80])],
+    }
+}
+
+#[test]
+fn test_register_relayer() {
+    let mut context = init_helper(true);
+
+    // CASE: not registered relayer can not relay
+    let err = ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), add_headers())
+        .unwrap_err();
+    assert_eq!(err, StdError::generic_err("not allowed relayer"));
+
+    // CASE: bond is sent by other than the reward token
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("relayer", &[]),
+        HandleMsg::Receive(Snip20ReceiveMsg {
+            sender: "relayer".into(),
+            from: "relayer".into(),
+            amount: Uint128(500),
+            memo: None,
+            msg: Some(to_binary(&ReceiveMsg::Bond {}).unwrap()),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+
+    // CASE: insufficient bond
+    bond(&mut context, "relayer", 300).unwrap();
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        HandleMsg::AllowRelayer {
+            relayer: "relayer".into(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("insufficient bond"));
+    // pending relayer can not relay
+    let err = ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), add_headers())
+        .unwrap_err();
+    assert_eq!(err, StdError::generic_err("not allowed relayer"));
+
+    // CASE: only the owner allows the relayer
+    bond(&mut context, "relayer", 200).unwrap();
+    let allow_relayer_msg = HandleMsg::AllowRelayer {
+        relayer: "relayer".into(),
+    };
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("relayer", &[]),
+        allow_relayer_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+    ShurikenRunner::run_handle(&mut context, mock_env("owner", &[]), allow_relayer_msg).unwrap();
+    assert_eq!(
+        query_relayers(&mut context),
+        vec![RelayerInfo {
+            address: "relayer".into(),
+            bond: Uint128(500),
+            status: RelayerStatus::Allowed,
+        }]
+    );
+
+    // the allowed relayer relays the headers with the callback to this contract.
+    let response =
+        ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), add_headers()).unwrap();
+    assert_eq!(
+        response.messages.last().unwrap(),
+        &bitcoin_spv::HandleMsg::AddHeaders {
+            tip_height: 1,
+            headers: vec![Binary::from(vec![0u8; 80])],
+            relay_callback: Some(RelayCallback {
+                contract: ContractReference {
+                    address: cosmwasm_std::testing::MOCK_CONTRACT_ADDR.into(),
+                    hash: "".into(),
+                },
+                relayer: "relayer".into(),
+            }),
+        }
+        .to_cosmos_msg(
+            "bitcoin_spv_hash".into(),
+            "bitcoin_spv_address".into(),
+            None
+        )
+        .unwrap()
+    );
+
+    // CASE: allowed relayer can not unbond until removed
+    let err =
+        ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), HandleMsg::Unbond {})
+            .unwrap_err();
+    assert_eq!(err, StdError::generic_err("allowed relayer can not unbond"));
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        HandleMsg::RemoveRelayer {
+            relayer: "relayer".into(),
+        },
+    )
+    .unwrap();
+    let response =
+        ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), HandleMsg::Unbond {})
+            .unwrap();
+    assert_eq!(
+        response.messages.last().unwrap(),
+        &snip20::transfer_msg(
+            "relayer".into(),
+            Uint128(500),
+            None,
+            None,
+            BLOCK_SIZE,
+            "reward_token_hash".into(),
+            "reward_token_address".into(),
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        query_relayers(&mut context),
+        vec![RelayerInfo {
+            address: "relayer".into(),
+            bond: Uint128::zero(),
+            status: RelayerStatus::Removed,
+        }]
+    );
+}
+
+#[test]
+fn test_credit_and_claim_reward() {
+    let mut context = init_helper(true);
+    bond(&mut context, "relayer", 500).unwrap();
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        HandleMsg::AllowRelayer {
+            relayer: "relayer".into(),
+        },
+    )
+    .unwrap();
+
+    // CASE: callback from other than bitcoin_spv
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("relayer", &[]),
+        headers_accepted("relayer", 1),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+
+    // CASE: callback crediting a not allowed relayer
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("bitcoin_spv_address", &[]),
+        headers_accepted("stranger", 1),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("not allowed relayer"));
+
+    // CASE: credited by the callbacks
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("bitcoin_spv_address", &[]),
+        headers_accepted("relayer", 3),
+    )
+    .unwrap();
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("sfps_address", &[]),
+        HandleMsg::CommittedHashesAccepted {
+            relayer: "relayer".into(),
+            count: 2,
+        },
+    )
+    .unwrap();
+    assert_eq!(query_reward(&mut context, "relayer"), Uint128(230));
+    assert_eq!(query_reward_config(&mut context).budget, Uint128(770));
+
+    // CASE: credit is cut at the budget
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("sfps_address", &[]),
+        HandleMsg::CommittedHashesAccepted {
+            relayer: "relayer".into(),
+            count: 10,
+        },
+    )
+    .unwrap();
+    assert_eq!(query_reward(&mut context, "relayer"), Uint128(1000));
+    assert_eq!(query_reward_config(&mut context).budget, Uint128::zero());
+
+    // CASE: claim transfers all the reward
+    let response = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("relayer", &[]),
+        HandleMsg::ClaimReward {},
+    )
+    .unwrap();
+    assert_eq!(
+        response.messages.last().unwrap(),
+        &snip20::transfer_msg(
+            "relayer".into(),
+            Uint128(1000),
+            None,
+            None,
+            BLOCK_SIZE,
+            "reward_token_hash".into(),
+            "reward_token_address".into(),
+        )
+        .unwrap()
+    );
+    assert_eq!(query_reward(&mut context, "relayer"), Uint128::zero());
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("relayer", &[]),
+        HandleMsg::ClaimReward {},
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("no reward"));
+}
+
+#[test]
+fn test_credit_without_registration() {
+    let mut context = init_helper(false);
+    ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), add_headers()).unwrap();
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("bitcoin_spv_address", &[]),
+        headers_accepted("relayer", 2),
+    )
+    .unwrap();
+    assert_eq!(query_reward(&mut context, "relayer"), Uint128(20));
+}
+
+#[test]
+fn test_slash() {
+    let mut context = init_helper(true);
+    bond(&mut context, "relayer", 500).unwrap();
+    ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        HandleMsg::AllowRelayer {
+            relayer: "relayer".into(),
+        },
+    )
+    .unwrap();
+    let slash_msg = HandleMsg::Slash {
+        relayer: "relayer".into(),
+    };
+
+    // CASE: only the owner slashes
+    let err =
+        ShurikenRunner::run_handle(&mut context, mock_env("stranger", &[]), slash_msg.clone())
+            .unwrap_err();
+    assert_eq!(err, StdError::unauthorized());
+
+    // CASE: the bond is added to the reward budget
+    ShurikenRunner::run_handle(&mut context, mock_env("owner", &[]), slash_msg.clone()).unwrap();
+    assert_eq!(
+        query_relayers(&mut context),
+        vec![RelayerInfo {
+            address: "relayer".into(),
+            bond: Uint128::zero(),
+            status: RelayerStatus::Slashed,
+        }]
+    );
+    assert_eq!(query_reward_config(&mut context).budget, Uint128(1500));
+
+    // CASE: slashed relayer can neither relay, be credited, bond again nor be allowed
+    let err = ShurikenRunner::run_handle(&mut context, mock_env("relayer", &[]), add_headers())
+        .unwrap_err();
+    assert_eq!(err, StdError::generic_err("not allowed relayer"));
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("bitcoin_spv_address", &[]),
+        headers_accepted("relayer", 1),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("not allowed relayer"));
+    let err = bond(&mut context, "relayer", 500).unwrap_err();
+    assert_eq!(err, StdError::generic_err("slashed relayer"));
+    let err = ShurikenRunner::run_handle(
+        &mut context,
+        mock_env("owner", &[]),
+        HandleMsg::AllowRelayer {
+            relayer: "relayer".into(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("slashed relayer"));
+
+    // CASE: already slashed
+    let err =
+        ShurikenRunner::run_handle(&mut context, mock_env("owner", &[]), slash_msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("already slashed"));
+}
@@ -1,10 +1,23 @@
-use cosmwasm_std::{Api, ReadonlyStorage, StdResult, Storage};
+use cosmwasm_std::{Api, CanonicalAddr, ReadonlyStorage, StdResult, Storage, Uint128};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use secret_toolkit::serialization::Bincode2;
 use secret_toolkit::storage::Item;
-use shared_types::shuriken::{CanonicalConfig, Config};
+use serde::{Deserialize, Serialize};
+use shared_types::shuriken::{CanonicalConfig, Config, RelayerConfig, RelayerStatus, RewardConfig};
 use shared_types::Canonicalize;
 
 const CONFIG_KEY: &[u8] = b"config";
+const REWARD_CONFIG_KEY: &[u8] = b"reward_config";
+const RELAYER_CONFIG_KEY: &[u8] = b"relayer_config";
+const RELAYER_ADDRESSES_KEY: &[u8] = b"relayer_addresses";
+const PREFIX_REWARDS: &[u8] = b"rewards";
+const PREFIX_RELAYERS: &[u8] = b"relayers";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Relayer {
+    pub bond: Uint128,
+    pub status: RelayerStatus,
+}
 
 pub fn read_config<S: ReadonlyStorage, A: Api>(storage: &S, api: &A) -> StdResult<Config> {
     let store = Item::<CanonicalConfig, Bincode2>::new(CONFIG_KEY);
@@ -16,3 +29,98 @@ pub fn write_config<S: Storage, A: Api>(storage: &mut S, config: Config, api: &A
     let store = Item::<CanonicalConfig, Bincode2>::new(CONFIG_KEY);
     store.save(storage, &config.into_canonical(api)?)
 }
+
+pub fn read_reward_config<S: ReadonlyStorage>(storage: &S) -> StdResult<RewardConfig> {
+    Item::<RewardConfig, Bincode2>::new(REWARD_CONFIG_KEY).load(storage)
+}
+
+pub fn write_reward_config<S: Storage>(
+    storage: &mut S,
+    reward_config: &RewardConfig,
+) -> StdResult<()> {
+    Item::<RewardConfig, Bincode2>::new(REWARD_CONFIG_KEY).save(storage, reward_config)
+}
+
+/// reads unclaimed reward of the relayer.
+pub fn read_reward<S: ReadonlyStorage>(storage: &S, relayer: &CanonicalAddr) -> StdResult<Uint128> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_REWARDS, storage);
+    Ok(Item::<Uint128, Bincode2>::new(relayer.as_slice())
+        .may_load(&storage)?
+        .unwrap_or_default())
+}
+
+pub fn write_reward<S: Storage>(
+    storage: &mut S,
+    relayer: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_REWARDS, storage);
+    Item::<Uint128, Bincode2>::new(relayer.as_slice()).save(&mut storage, &amount)
+}
+
+/// credits the reward of `count` submissions to the relayer within the remaining budget.
+/// returns the credited amount.
+pub fn credit_reward<S: Storage>(
+    storage: &mut S,
+    relayer: &CanonicalAddr,
+    count: u32,
+    reward_per_submission: impl Fn(&RewardConfig) -> Uint128,
+) -> StdResult<Uint128> {
+    let mut reward_config = read_reward_config(storage)?;
+    let amount = Uint128(
+        (reward_per_submission(&reward_config).u128() * count as u128)
+            .min(reward_config.budget.u128()),
+    );
+    if amount.is_zero() {
+        return Ok(amount);
+    }
+    reward_config.budget = (reward_config.budget - amount)?;
+    write_reward_config(storage, &reward_config)?;
+    let reward = read_reward(storage, relayer)?;
+    write_reward(storage, relayer, reward + amount)?;
+    Ok(amount)
+}
+
+pub fn read_relayer_config<S: ReadonlyStorage>(storage: &S) -> StdResult<RelayerConfig> {
+    Item::<RelayerConfig, Bincode2>::new(RELAYER_CONFIG_KEY).load(storage)
+}
+
+pub fn write_relayer_config<S: Storage>(
+    storage: &mut S,
+    relayer_config: &RelayerConfig,
+) -> StdResult<()> {
+    Item::<RelayerConfig, Bincode2>::new(RELAYER_CONFIG_KEY).save(storage, relayer_config)
+}
+
+pub fn read_relayer<S: ReadonlyStorage>(
+    storage: &S,
+    relayer: &CanonicalAddr,
+) -> StdResult<Option<Relayer>> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_RELAYERS, storage);
+    Item::<Relayer, Bincode2>::new(relayer.as_slice()).may_load(&storage)
+}
+
+/// writes the relayer, and adds the address to the relayer list if it is new.
+pub fn write_relayer<S: Storage>(
+    storage: &mut S,
+    relayer: &CanonicalAddr,
+    value: &Relayer,
+) -> StdResult<()> {
+    if read_relayer(storage, relayer)?.is_none() {
+        let mut addresses = read_relayer_addresses(storage)?;
+        addresses.push(relayer.clone());
+        Item::<Vec<CanonicalAddr>, Bincode2>::new(RELAYER_ADDRESSES_KEY)
+            .save(storage, &addresses)?;
+    }
+    let mut storage = PrefixedStorage::new(PREFIX_RELAYERS, storage);
+    Item::<Relayer, Bincode2>::new(relayer.as_slice()).save(&mut storage, value)
+}
+
+/// addresses of the relayers in the order of the registration.
+pub fn read_relayer_addresses<S: ReadonlyStorage>(storage: &S) -> StdResult<Vec<CanonicalAddr>> {
+    Ok(
+        Item::<Vec<CanonicalAddr>, Bincode2>::new(RELAYER_ADDRESSES_KEY)
+            .may_load(storage)?
+            .unwrap_or_default(),
+    )
+}
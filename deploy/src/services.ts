@@ -46,14 +46,17 @@ export const deployBitcoinSPV = async (
 export const deploySFPS = async (
     deployer: ContractDeployer,
     state: ContractReference,
+    owner: ContractReference
 ): Promise<ContractReference> => {
     const initialSnHeader = (
         await deployer.client.query.tendermint.getLatestBlock({})
     ).block!.header!;
     console.log(JSON.stringify(initialSnHeader));
     const entropy = Buffer.from(crypto.randomBytes(32));
+    const { chain_version, max_clock_drift, ...sfpsInitMsg } =
+        require('./init_msg/sfps.json')[deployer.environment];
     const initMsg = {
-        ...require('./init_msg/sfps.json')[deployer.environment],
+        ...sfpsInitMsg,
         initial_header: Buffer.from(
             Header.encode(initialSnHeader).finish()
         ).toString('base64'),
@@ -61,6 +64,10 @@ export const deploySFPS = async (
         seed: entropy.toString('base64'),
         config: {
             state_proxy: state,
+            chain_version,
+            owner: owner.address,
+            chain_id: initialSnHeader.chainId,
+            max_clock_drift,
         }
     };
     return deployer.deployContract('sfps', initMsg, 'SFPS');
@@ -126,15 +133,28 @@ export const deployShuriken = async (
     deployer: ContractDeployer,
     bitcoinSPV: ContractReference,
     sfps: ContractReference,
-    state_proxy: ContractReference
+    state_proxy: ContractReference,
+    owner: ContractReference,
+    rewardToken: ContractReference
 ): Promise<ContractReference> => {
     const initMsg = {
         config: {
             bitcoin_spv: bitcoinSPV,
             sfps: sfps,
-            state_proxy: state_proxy
+            state_proxy: state_proxy,
+            owner: owner.address,
+            reward_token: rewardToken,
         },
         seed: crypto.randomBytes(32).toString('base64'),
+        reward_config: {
+            header_reward: '0',
+            committed_hash_reward: '0',
+            budget: '0',
+        },
+        relayer_config: {
+            registration_required: false,
+            bond_amount: '0',
+        },
     };
     return deployer.deployContract('shuriken', initMsg, 'SHURIKEN');
 };
@@ -200,9 +220,9 @@ export const main = async () => {
     const multisig = await deployMultisig(deployer);
     const log = await deployLog(deployer);
     const bitcoinSPV = await deployBitcoinSPV(deployer, state);
-    const sfps = await deploySFPS(deployer, state);
+    const sfps = await deploySFPS(deployer, state, multisig);
     const sbtc = await deploySBTC(deployer);
-    await deploySNB(deployer);
+    const snb = await deploySNB(deployer);
     const gateway = await deployGateway(
         deployer,
         bitcoinSPV,
@@ -212,7 +232,7 @@ export const main = async () => {
         multisig,
         state
     );
-    await deployShuriken(deployer, bitcoinSPV, sfps, state);
+    await deployShuriken(deployer, bitcoinSPV, sfps, state, multisig, snb);
     await deployVesting(deployer);
     await setupLog(deployer, log, gateway);
     deployer.exportDeployReport();
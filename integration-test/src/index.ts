@@ -261,6 +261,8 @@ void (async () => {
         })!;
         const tree = MerkleTree.fromTxs(block.transactions!);
         const mp = tree.merkleProof(tx.getHash());
+        const coinbaseTx = block.transactions![0];
+        const coinbaseMp = tree.merkleProof(coinbaseTx.getHash());
 
         const queryVerifyMerkleProofResult =
             await bitcoinSPVClient.verifyMerkleProof(blockHeight, tx, mp);
@@ -271,6 +273,8 @@ void (async () => {
                 blockHeight,
                 tx,
                 mp,
+                coinbaseTx,
+                coinbaseMp,
                 receiveAddress,
                 100
             );
@@ -344,13 +348,21 @@ void (async () => {
             })!;
             const tree = MerkleTree.fromTxs(block.transactions!);
             const mp = tree.merkleProof(tx.getHash());
+            const coinbaseTx = block.transactions![0];
+            const coinbaseMp = tree.merkleProof(coinbaseTx.getHash());
 
             const queryVerifyMerkleProofResult =
                 await bitcoinSPVClient.verifyMerkleProof(blockHeight, tx, mp);
             assert.isTrue(queryVerifyMerkleProofResult);
             // Execute
             await feeReporter.report(
-                await gatewayClient.verifyMintTx(blockHeight, tx, mp)
+                await gatewayClient.verifyMintTx(
+                    blockHeight,
+                    tx,
+                    mp,
+                    coinbaseTx,
+                    coinbaseMp
+                )
             );
 
             // Assert Balance